@@ -0,0 +1,80 @@
+use chia_protocol::{Bytes32, CoinState, CoinStateFilters, Program, SpendBundle, TransactionAck};
+use chia_sdk_types::{ChainReader, ChainWriter};
+
+use crate::{ClientError, Peer};
+
+impl ChainReader for Peer {
+    type Error = ClientError;
+
+    async fn peak_height(&self) -> Result<u32, ClientError> {
+        self.peak()
+            .map(|peak| peak.height)
+            .ok_or(ClientError::MissingPeak)
+    }
+
+    async fn coin_states(&self, coin_ids: Vec<Bytes32>) -> Result<Vec<CoinState>, ClientError> {
+        let genesis_challenge = self.genesis_challenge()?;
+
+        self.request_coin_state(coin_ids, None, genesis_challenge, false)
+            .await?
+            .map(|response| response.coin_states)
+            .map_err(|rejection| ClientError::Rejection(rejection.reason))
+    }
+
+    async fn coin_states_by_puzzle_hash(
+        &self,
+        puzzle_hashes: Vec<Bytes32>,
+        include_hinted: bool,
+    ) -> Result<Vec<CoinState>, ClientError> {
+        let mut previous_height = None;
+        let mut header_hash = self.genesis_challenge()?;
+        let mut coin_states = Vec::new();
+
+        loop {
+            let response = self
+                .request_puzzle_state(
+                    puzzle_hashes.clone(),
+                    previous_height,
+                    header_hash,
+                    CoinStateFilters::new(true, true, include_hinted, 0),
+                    false,
+                )
+                .await?
+                .map_err(|rejection| ClientError::Rejection(rejection.reason))?;
+
+            coin_states.extend(response.coin_states);
+
+            if response.is_finished {
+                return Ok(coin_states);
+            }
+
+            previous_height = Some(response.height);
+            header_hash = response.header_hash;
+        }
+    }
+
+    async fn children(&self, coin_id: Bytes32) -> Result<Vec<CoinState>, ClientError> {
+        Ok(self.request_children(coin_id).await?.coin_states)
+    }
+
+    async fn puzzle_and_solution(
+        &self,
+        coin_id: Bytes32,
+        spent_height: u32,
+    ) -> Result<Option<(Program, Program)>, ClientError> {
+        Ok(self
+            .request_puzzle_and_solution(coin_id, spent_height)
+            .await?
+            .ok()
+            .map(|response| (response.puzzle, response.solution)))
+    }
+}
+
+impl ChainWriter for Peer {
+    async fn submit_transaction(
+        &mut self,
+        spend_bundle: SpendBundle,
+    ) -> Result<TransactionAck, ClientError> {
+        self.send_transaction(spend_bundle).await
+    }
+}
@@ -10,8 +10,9 @@ use std::{
 use chia_protocol::Message;
 use tokio::sync::{mpsc, Mutex};
 use tokio_tungstenite::Connector;
+use tracing::warn;
 
-use crate::{connect_peer, ClientError, Network, Peer, PeerOptions};
+use crate::{connect_peer, ClientError, InboundAction, Network, Peer, PeerOptions};
 
 #[derive(Clone)]
 pub struct Client {
@@ -67,8 +68,12 @@ impl Client {
     pub async fn connect(
         &self,
         socket_addr: SocketAddr,
-        options: PeerOptions,
+        mut options: PeerOptions,
     ) -> Result<mpsc::Receiver<Message>, ClientError> {
+        options
+            .genesis_challenge
+            .get_or_insert(self.network.genesis_challenge);
+
         let (peer, receiver) = connect_peer(
             self.network_id.clone(),
             self.connector.clone(),
@@ -84,6 +89,25 @@ impl Client {
             return Err(ClientError::BannedPeer);
         }
 
+        let violation = peer.violation();
+        let client_state = self.state.clone();
+        let ban = options.inbound_action == InboundAction::Ban;
+
+        tokio::spawn(async move {
+            let Some(violation) = violation.await else {
+                return;
+            };
+
+            let mut state = client_state.lock().await;
+
+            if ban {
+                warn!("Banning peer {ip_addr} due to inbound violation: {violation:?}");
+                state.ban(ip_addr);
+            } else {
+                state.disconnect(&ip_addr);
+            }
+        });
+
         state.peers.insert(peer.socket_addr().ip(), peer);
 
         Ok(receiver)
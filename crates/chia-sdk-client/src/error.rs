@@ -1,7 +1,9 @@
-use chia_protocol::{NodeType, ProtocolMessageTypes};
+use chia_protocol::{NodeType, ProtocolMessageTypes, RejectStateReason};
 use thiserror::Error;
 use tokio::sync::oneshot::error::RecvError;
 
+use crate::{HeaderChainError, InboundViolation};
+
 #[derive(Debug, Error)]
 pub enum ClientError {
     #[error("SSL error: {0}")]
@@ -55,4 +57,32 @@ pub enum ClientError {
 
     #[error("The peer is banned")]
     BannedPeer,
+
+    #[cfg(any(feature = "native-tls", feature = "rustls"))]
+    #[error("HTTP error: {0}")]
+    Http(#[from] reqwest::Error),
+
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("RPC error: {0}")]
+    Rpc(String),
+
+    #[error("The peer rejected the request: {0:?}")]
+    Rejection(RejectStateReason),
+
+    #[error("The peer hasn't announced a peak yet")]
+    MissingPeak,
+
+    #[error("The genesis challenge of the network is unknown")]
+    MissingGenesisChallenge,
+
+    #[error("Invalid header chain: {0}")]
+    HeaderChain(#[from] HeaderChainError),
+
+    #[error("The peer rejected the request for headers {0} to {1}")]
+    RejectedHeaders(u32, u32),
+
+    #[error("Inbound message rejected: {0:?}")]
+    InboundViolation(InboundViolation),
 }
@@ -0,0 +1,648 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    future::Future,
+};
+
+use chia_protocol::{Bytes32, HeaderBlock, NewPeakWallet, WeightProof};
+use thiserror::Error;
+
+use crate::{ClientError, Peer};
+
+/// The most headers that can be requested from a full node at once.
+const MAX_HEADER_BATCH: u32 = 128;
+
+/// How far back to look for a common ancestor before falling back to a weight proof.
+const MAX_BACKTRACK: u32 = 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum HeaderChainError {
+    #[error("Header at height {0} doesn't follow the previous header")]
+    NonContiguous(u32),
+
+    #[error("Header at height {0} doesn't increase the weight")]
+    NonIncreasingWeight(u32),
+
+    #[error("Header at height {0} doesn't connect to a known header")]
+    Unlinked(u32),
+
+    #[error("Weight proof has no recent chain data")]
+    EmptyWeightProof,
+
+    #[error("Headers don't end at the announced peak")]
+    PeakMismatch,
+}
+
+/// The subset of a block header needed to track the chain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct HeaderInfo {
+    pub header_hash: Bytes32,
+    pub prev_header_hash: Bytes32,
+    pub height: u32,
+    pub weight: u128,
+}
+
+impl From<&HeaderBlock> for HeaderInfo {
+    fn from(header_block: &HeaderBlock) -> Self {
+        Self {
+            header_hash: header_block.header_hash(),
+            prev_header_hash: header_block.prev_header_hash(),
+            height: header_block.height(),
+            weight: header_block.weight(),
+        }
+    }
+}
+
+/// The result of switching to a new peak.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChainUpdate {
+    pub peak: HeaderInfo,
+    /// The height of the last block shared with the previous main chain, if any blocks were
+    /// rolled back. Coin states confirmed above this height need to be refetched.
+    pub fork_height: Option<u32>,
+    /// Whether the peak was taken from an unverified weight proof, rather than from headers
+    /// which connect it to the known chain.
+    pub unverified: bool,
+}
+
+/// Tracks the heaviest known chain of block headers, so that peaks announced by peers can be
+/// checked before they're trusted, and reorgs can be detected.
+///
+/// This is a header linkage check, rather than full validation. Headers are checked for linkage
+/// and increasing weight, and must end at the announced peak. Weight proofs are only used as a
+/// source of recent headers, and their proofs of space and VDFs are not validated. This protects
+/// against peers which are inconsistent, but not against peers which fabricate an entire chain.
+#[derive(Debug, Clone)]
+pub struct HeaderChain {
+    headers: HashMap<Bytes32, HeaderInfo>,
+    main_chain: BTreeMap<u32, Bytes32>,
+    peak: Option<HeaderInfo>,
+    retention: u32,
+}
+
+impl Default for HeaderChain {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HeaderChain {
+    /// Creates an empty header chain, which keeps headers for roughly a day of blocks.
+    pub fn new() -> Self {
+        Self::with_retention(4608)
+    }
+
+    /// Creates an empty header chain, which keeps headers up to `retention` blocks below the peak.
+    pub fn with_retention(retention: u32) -> Self {
+        Self {
+            headers: HashMap::new(),
+            main_chain: BTreeMap::new(),
+            peak: None,
+            retention,
+        }
+    }
+
+    /// The heaviest known header.
+    pub fn peak(&self) -> Option<HeaderInfo> {
+        self.peak
+    }
+
+    /// Whether the header is part of the heaviest known chain.
+    pub fn is_in_main_chain(&self, header_hash: Bytes32) -> bool {
+        self.headers.get(&header_hash).map_or(false, |header| {
+            self.main_chain.get(&header.height) == Some(&header_hash)
+        })
+    }
+
+    /// The header hash of the main chain at the given height, if it's being tracked.
+    pub fn header_hash_at(&self, height: u32) -> Option<Bytes32> {
+        self.main_chain.get(&height).copied()
+    }
+
+    /// Adds a contiguous run of headers, which must connect to a known header unless the
+    /// chain is empty. If the run ends in a heavier peak, the main chain is switched to it.
+    pub fn add_headers(
+        &mut self,
+        headers: &[HeaderInfo],
+    ) -> Result<Option<ChainUpdate>, HeaderChainError> {
+        let Some(tip) = headers.last() else {
+            return Ok(None);
+        };
+
+        verify_headers(headers)?;
+
+        // Headers which are already known don't need to be linked again.
+        let known = headers
+            .iter()
+            .take_while(|header| self.headers.contains_key(&header.header_hash))
+            .count();
+        let Some(first) = headers.get(known) else {
+            return Ok(None);
+        };
+
+        if self.peak.is_some() {
+            let parent = self
+                .headers
+                .get(&first.prev_header_hash)
+                .ok_or(HeaderChainError::Unlinked(first.height))?;
+
+            if parent.height + 1 != first.height {
+                return Err(HeaderChainError::NonContiguous(first.height));
+            }
+
+            if parent.weight >= first.weight {
+                return Err(HeaderChainError::NonIncreasingWeight(first.height));
+            }
+        }
+
+        for header in &headers[known..] {
+            self.headers.insert(header.header_hash, *header);
+        }
+
+        if self.peak.map_or(false, |peak| peak.weight >= tip.weight) {
+            return Ok(None);
+        }
+
+        Ok(Some(self.set_peak(*tip)))
+    }
+
+    /// Adds the recent chain from a weight proof, which must end at the announced peak.
+    ///
+    /// The weight proof is not verified. Only the linkage of the recent chain is checked, and the
+    /// sub-epoch samples, proofs of space and VDFs are not validated. Any resulting update is
+    /// marked as [`unverified`](ChainUpdate::unverified).
+    ///
+    /// If the recent chain doesn't connect to any known header, the chain is only replaced if the
+    /// announced peak is heavier than the current one, and the fork height is conservatively
+    /// reported as zero.
+    pub fn add_unverified_recent_chain(
+        &mut self,
+        weight_proof: &WeightProof,
+        peak: &NewPeakWallet,
+    ) -> Result<Option<ChainUpdate>, HeaderChainError> {
+        let headers: Vec<HeaderInfo> = weight_proof
+            .recent_chain_data
+            .iter()
+            .map(HeaderInfo::from)
+            .collect();
+
+        self.add_recent_headers(&headers, peak)
+    }
+
+    fn add_recent_headers(
+        &mut self,
+        headers: &[HeaderInfo],
+        peak: &NewPeakWallet,
+    ) -> Result<Option<ChainUpdate>, HeaderChainError> {
+        let tip = headers.last().ok_or(HeaderChainError::EmptyWeightProof)?;
+
+        if tip.header_hash != peak.header_hash
+            || tip.height != peak.height
+            || tip.weight != peak.weight
+        {
+            return Err(HeaderChainError::PeakMismatch);
+        }
+
+        let update = match self.add_headers(headers) {
+            Err(HeaderChainError::Unlinked(..)) => {
+                verify_headers(headers)?;
+
+                if self
+                    .peak
+                    .map_or(false, |current| current.weight >= tip.weight)
+                {
+                    return Ok(None);
+                }
+
+                let had_peak = self.peak.is_some();
+                *self = Self::with_retention(self.retention);
+
+                let mut update = self.add_headers(headers)?;
+
+                if let Some(update) = &mut update {
+                    update.fork_height = had_peak.then_some(0);
+                }
+
+                update
+            }
+            result => result?,
+        };
+
+        Ok(update.map(|update| ChainUpdate {
+            unverified: true,
+            ..update
+        }))
+    }
+
+    /// Requests a weight proof for the peak from the peer, and adds its recent chain if the peak
+    /// is heavier than the current one. See [`HeaderChain::add_unverified_recent_chain`] for what
+    /// is and isn't checked.
+    pub async fn sync_unverified_recent_chain(
+        &mut self,
+        peer: &Peer,
+        peak: &NewPeakWallet,
+    ) -> Result<Option<ChainUpdate>, ClientError> {
+        if self
+            .peak
+            .map_or(false, |current| current.weight >= peak.weight)
+        {
+            return Ok(None);
+        }
+
+        let response = peer
+            .request_proof_of_weight(peak.height + 1, peak.header_hash)
+            .await?;
+
+        if response.tip != peak.header_hash {
+            return Err(HeaderChainError::PeakMismatch.into());
+        }
+
+        Ok(self.add_unverified_recent_chain(&response.wp, peak)?)
+    }
+
+    /// Handles a new peak announced by the peer, by requesting the headers which connect it to
+    /// the known chain. If no common ancestor is found nearby, an unverified weight proof is used
+    /// instead, and the update is marked as [`unverified`](ChainUpdate::unverified).
+    pub async fn handle_new_peak(
+        &mut self,
+        peer: &Peer,
+        peak: &NewPeakWallet,
+    ) -> Result<Option<ChainUpdate>, ClientError> {
+        if self.headers.contains_key(&peak.header_hash) {
+            return Ok(None);
+        }
+
+        let Some(current) = self.peak else {
+            return self.sync_unverified_recent_chain(peer, peak).await;
+        };
+
+        if peak.weight <= current.weight {
+            return Ok(None);
+        }
+
+        let update = self
+            .backtrack(peak, current, |start_height, end_height| {
+                fetch_headers(peer, start_height, end_height)
+            })
+            .await?;
+
+        match update {
+            Some(update) => Ok(update),
+            None => self.sync_unverified_recent_chain(peer, peak).await,
+        }
+    }
+
+    /// Fetches headers from further and further back until they connect to the known chain.
+    /// Returns [`None`] if no common ancestor is found within the headers being tracked.
+    async fn backtrack<F, Fut>(
+        &mut self,
+        peak: &NewPeakWallet,
+        current: HeaderInfo,
+        mut fetch: F,
+    ) -> Result<Option<Option<ChainUpdate>>, ClientError>
+    where
+        F: FnMut(u32, u32) -> Fut,
+        Fut: Future<Output = Result<Vec<HeaderInfo>, ClientError>>,
+    {
+        let lowest_height = self.main_chain.keys().next().copied().unwrap_or(0);
+        let mut start_height = peak.height.min(current.height + 1);
+        let mut step = 1;
+
+        while peak.height - start_height < MAX_BACKTRACK && start_height >= lowest_height {
+            let headers = fetch(start_height, peak.height).await?;
+
+            if headers.last().map(|header| header.header_hash) != Some(peak.header_hash) {
+                return Err(HeaderChainError::PeakMismatch.into());
+            }
+
+            match self.add_headers(&headers) {
+                Err(HeaderChainError::Unlinked(..)) if start_height > lowest_height => {
+                    start_height = start_height.saturating_sub(step).max(lowest_height);
+                    step *= 2;
+                }
+                Err(HeaderChainError::Unlinked(..)) => break,
+                result => return Ok(Some(result?)),
+            }
+        }
+
+        Ok(None)
+    }
+
+    fn set_peak(&mut self, peak: HeaderInfo) -> ChainUpdate {
+        let mut new_blocks = Vec::new();
+        let mut cursor = Some(peak);
+
+        // Walk back until the new chain meets the current main chain.
+        while let Some(header) = cursor {
+            if self.main_chain.get(&header.height) == Some(&header.header_hash) {
+                break;
+            }
+            new_blocks.push(header);
+            cursor = self.headers.get(&header.prev_header_hash).copied();
+        }
+
+        let fork_height = cursor.map(|header| header.height);
+        let old_peak_height = self.peak.map(|peak| peak.height);
+
+        let rolled_back = match (fork_height, old_peak_height) {
+            (Some(fork_height), Some(old_peak_height)) => fork_height < old_peak_height,
+            (None, Some(_)) => true,
+            (_, None) => false,
+        };
+
+        let first_new_height = fork_height.map_or(0, |height| height + 1);
+        self.main_chain.split_off(&first_new_height);
+
+        for header in new_blocks {
+            self.main_chain.insert(header.height, header.header_hash);
+        }
+
+        self.peak = Some(peak);
+        self.prune();
+
+        ChainUpdate {
+            peak,
+            fork_height: rolled_back.then(|| fork_height.unwrap_or(0)),
+            unverified: false,
+        }
+    }
+
+    fn prune(&mut self) {
+        let Some(peak) = self.peak else {
+            return;
+        };
+
+        let min_height = peak.height.saturating_sub(self.retention);
+
+        self.headers.retain(|_, header| header.height >= min_height);
+        self.main_chain = self.main_chain.split_off(&min_height);
+    }
+}
+
+fn verify_headers(headers: &[HeaderInfo]) -> Result<(), HeaderChainError> {
+    for pair in headers.windows(2) {
+        let (prev, next) = (pair[0], pair[1]);
+
+        if next.height != prev.height + 1 || next.prev_header_hash != prev.header_hash {
+            return Err(HeaderChainError::NonContiguous(next.height));
+        }
+
+        if next.weight <= prev.weight {
+            return Err(HeaderChainError::NonIncreasingWeight(next.height));
+        }
+    }
+
+    Ok(())
+}
+
+async fn fetch_headers(
+    peer: &Peer,
+    start_height: u32,
+    end_height: u32,
+) -> Result<Vec<HeaderInfo>, ClientError> {
+    let mut headers = Vec::new();
+    let mut height = start_height;
+
+    while height <= end_height {
+        let batch_end = end_height.min(height + MAX_HEADER_BATCH - 1);
+
+        let response = peer
+            .request_block_headers(height, batch_end, false)
+            .await?
+            .map_err(|_| ClientError::RejectedHeaders(height, batch_end))?;
+
+        headers.extend(response.header_blocks.iter().map(HeaderInfo::from));
+        height = batch_end + 1;
+    }
+
+    Ok(headers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header(height: u32, fork: u8, prev: Option<HeaderInfo>, weight: u128) -> HeaderInfo {
+        let mut hash = [fork; 32];
+        hash[..4].copy_from_slice(&height.to_be_bytes());
+        hash[31] = 1;
+
+        HeaderInfo {
+            header_hash: hash.into(),
+            prev_header_hash: prev.map_or(Bytes32::default(), |prev| prev.header_hash),
+            height,
+            weight,
+        }
+    }
+
+    fn extend(from: HeaderInfo, count: u32, fork: u8, weight_step: u128) -> Vec<HeaderInfo> {
+        let mut headers = Vec::new();
+        let mut prev = from;
+
+        for _ in 0..count {
+            let next = header(prev.height + 1, fork, Some(prev), prev.weight + weight_step);
+            headers.push(next);
+            prev = next;
+        }
+
+        headers
+    }
+
+    #[test]
+    fn test_extend_main_chain() -> anyhow::Result<()> {
+        let mut chain = HeaderChain::new();
+
+        let genesis = header(0, 0, None, 1);
+        let update = chain.add_headers(&[genesis])?.expect("missing update");
+        assert_eq!(update.fork_height, None);
+
+        let blocks = extend(genesis, 10, 0, 10);
+        let update = chain.add_headers(&blocks)?.expect("missing update");
+        assert_eq!(update.peak, blocks[9]);
+        assert_eq!(update.fork_height, None);
+
+        assert!(chain.is_in_main_chain(genesis.header_hash));
+        assert!(chain.is_in_main_chain(blocks[5].header_hash));
+        assert_eq!(chain.header_hash_at(3), Some(blocks[2].header_hash));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_reorg() -> anyhow::Result<()> {
+        let mut chain = HeaderChain::new();
+
+        let genesis = header(0, 0, None, 1);
+        let main = extend(genesis, 10, 0, 10);
+        chain.add_headers(&[genesis])?;
+        chain.add_headers(&main)?;
+
+        // A fork from height 5 which is lighter is stored, but doesn't become the peak.
+        let light = extend(main[4], 3, 1, 1);
+        assert_eq!(chain.add_headers(&light)?, None);
+        assert!(!chain.is_in_main_chain(light[0].header_hash));
+
+        // A heavier fork from height 5 replaces the blocks above it.
+        let heavy = extend(main[4], 10, 2, 10);
+        let update = chain.add_headers(&heavy)?.expect("missing update");
+        assert_eq!(update.peak, heavy[9]);
+        assert_eq!(update.fork_height, Some(5));
+
+        assert!(chain.is_in_main_chain(main[4].header_hash));
+        assert!(!chain.is_in_main_chain(main[5].header_hash));
+        assert!(chain.is_in_main_chain(heavy[0].header_hash));
+        assert_eq!(chain.peak(), Some(heavy[9]));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_headers() {
+        let mut chain = HeaderChain::new();
+
+        let genesis = header(0, 0, None, 1);
+        chain.add_headers(&[genesis]).unwrap();
+
+        let unknown = header(5, 3, Some(header(4, 3, None, 5)), 10);
+        assert_eq!(
+            chain.add_headers(&[unknown]),
+            Err(HeaderChainError::Unlinked(5))
+        );
+
+        let mut blocks = extend(genesis, 3, 0, 10);
+        blocks[2].weight = blocks[1].weight;
+        assert_eq!(
+            chain.add_headers(&blocks),
+            Err(HeaderChainError::NonIncreasingWeight(3))
+        );
+
+        let mut blocks = extend(genesis, 3, 0, 10);
+        blocks.swap(1, 2);
+        assert!(matches!(
+            chain.add_headers(&blocks),
+            Err(HeaderChainError::NonContiguous(_))
+        ));
+    }
+
+    #[test]
+    fn test_unlinked_recent_chain() -> anyhow::Result<()> {
+        let mut chain = HeaderChain::new();
+
+        let genesis = header(0, 0, None, 1);
+        let blocks = extend(genesis, 10, 0, 10);
+        chain.add_headers(&[genesis])?;
+        chain.add_headers(&blocks)?;
+        let current = chain.peak().expect("missing peak");
+
+        // An unlinked recent chain which isn't heavier doesn't replace the current chain.
+        let light = extend(header(50, 1, None, 1), 5, 1, 1);
+        let tip = light[4];
+        let peak = NewPeakWallet::new(tip.header_hash, tip.height, tip.weight, 0);
+        assert_eq!(chain.add_recent_headers(&light, &peak)?, None);
+        assert_eq!(chain.peak(), Some(current));
+
+        let equal = extend(header(50, 2, None, 1), 4, 2, 25);
+        let tip = equal[3];
+        assert_eq!(tip.weight, current.weight);
+        let peak = NewPeakWallet::new(tip.header_hash, tip.height, tip.weight, 0);
+        assert_eq!(chain.add_recent_headers(&equal, &peak)?, None);
+        assert_eq!(chain.peak(), Some(current));
+
+        // A heavier one does, but the update is marked as unverified.
+        let heavy = extend(header(50, 3, None, 1), 5, 3, 100);
+        let tip = heavy[4];
+        let peak = NewPeakWallet::new(tip.header_hash, tip.height, tip.weight, 0);
+        let update = chain
+            .add_recent_headers(&heavy, &peak)?
+            .expect("missing update");
+        assert_eq!(update.peak, tip);
+        assert_eq!(update.fork_height, Some(0));
+        assert!(update.unverified);
+        assert!(!chain.is_in_main_chain(blocks[9].header_hash));
+
+        // Headers which extend the known chain are verified again.
+        let more = extend(tip, 1, 3, 10);
+        let update = chain.add_headers(&more)?.expect("missing update");
+        assert!(!update.unverified);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_backtrack_to_lowest_height() -> anyhow::Result<()> {
+        let mut chain = HeaderChain::with_retention(5);
+
+        let genesis = header(0, 0, None, 1);
+        let blocks = extend(genesis, 20, 0, 10);
+        chain.add_headers(&[genesis])?;
+        chain.add_headers(&blocks)?;
+
+        let current = chain.peak().expect("missing peak");
+        assert_eq!(chain.main_chain.keys().next(), Some(&15));
+
+        // A heavier fork from the lowest height that's still being tracked.
+        let fork = extend(blocks[14], 10, 1, 10);
+        let tip = fork[9];
+        let peak = NewPeakWallet::new(tip.header_hash, tip.height, tip.weight, 15);
+
+        let remote: Vec<HeaderInfo> = [genesis]
+            .into_iter()
+            .chain(blocks[..15].iter().copied())
+            .chain(fork.iter().copied())
+            .collect();
+
+        let update = chain
+            .backtrack(&peak, current, |start_height, end_height| {
+                let headers = remote[start_height as usize..=end_height as usize].to_vec();
+                async move { Ok(headers) }
+            })
+            .await?
+            .expect("fork should be found")
+            .expect("missing update");
+
+        assert_eq!(update.peak, tip);
+        assert_eq!(update.fork_height, Some(15));
+        assert!(chain.is_in_main_chain(fork[4].header_hash));
+
+        // A fork below the lowest tracked height can't be found by backtracking.
+        let current = chain.peak().expect("missing peak");
+        let fork = extend(blocks[9], 30, 2, 10);
+        let tip = fork[29];
+        let peak = NewPeakWallet::new(tip.header_hash, tip.height, tip.weight, 10);
+
+        let remote: Vec<HeaderInfo> = [genesis]
+            .into_iter()
+            .chain(blocks[..10].iter().copied())
+            .chain(fork.iter().copied())
+            .collect();
+
+        let update = chain
+            .backtrack(&peak, current, |start_height, end_height| {
+                let headers = remote[start_height as usize..=end_height as usize].to_vec();
+                async move { Ok(headers) }
+            })
+            .await?;
+        assert_eq!(update, None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_prune() -> anyhow::Result<()> {
+        let mut chain = HeaderChain::with_retention(5);
+
+        let genesis = header(0, 0, None, 1);
+        let blocks = extend(genesis, 20, 0, 10);
+        chain.add_headers(&[genesis])?;
+        chain.add_headers(&blocks)?;
+
+        assert!(!chain.is_in_main_chain(genesis.header_hash));
+        assert_eq!(chain.header_hash_at(14), None);
+        assert_eq!(chain.header_hash_at(15), Some(blocks[14].header_hash));
+
+        let more = extend(blocks[19], 1, 0, 10);
+        chain.add_headers(&more)?;
+        assert_eq!(chain.peak(), Some(more[0]));
+
+        Ok(())
+    }
+}
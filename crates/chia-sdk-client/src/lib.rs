@@ -1,4 +1,6 @@
+mod chain;
 mod error;
+mod header_chain;
 mod network;
 mod peer;
 mod rate_limiter;
@@ -7,6 +9,7 @@ mod request_map;
 mod tls;
 
 pub use error::*;
+pub use header_chain::*;
 pub use network::*;
 pub use peer::*;
 pub use rate_limiter::*;
@@ -17,11 +20,15 @@ pub use tls::*;
 mod client;
 #[cfg(any(feature = "native-tls", feature = "rustls"))]
 mod connect;
+#[cfg(any(feature = "native-tls", feature = "rustls"))]
+mod rpc;
 
 #[cfg(any(feature = "native-tls", feature = "rustls"))]
 pub use client::*;
 #[cfg(any(feature = "native-tls", feature = "rustls"))]
 pub use connect::*;
+#[cfg(any(feature = "native-tls", feature = "rustls"))]
+pub use rpc::*;
 
 #[cfg(any(feature = "native-tls", feature = "rustls"))]
 pub use tokio_tungstenite::Connector;
@@ -1,14 +1,16 @@
-use std::{net::SocketAddr, sync::Arc, time::Duration};
+use std::{future::Future, net::SocketAddr, sync::Arc, time::Duration};
 
 use chia_protocol::{
-    Bytes32, ChiaProtocolMessage, CoinStateFilters, Message, PuzzleSolutionResponse,
-    RegisterForCoinUpdates, RegisterForPhUpdates, RejectCoinState, RejectPuzzleSolution,
-    RejectPuzzleState, RequestChildren, RequestCoinState, RequestPeers, RequestPuzzleSolution,
-    RequestPuzzleState, RequestRemoveCoinSubscriptions, RequestRemovePuzzleSubscriptions,
-    RequestTransaction, RespondChildren, RespondCoinState, RespondPeers, RespondPuzzleSolution,
-    RespondPuzzleState, RespondRemoveCoinSubscriptions, RespondRemovePuzzleSubscriptions,
-    RespondToCoinUpdates, RespondToPhUpdates, RespondTransaction, SendTransaction, SpendBundle,
-    TransactionAck,
+    Bytes32, ChiaProtocolMessage, CoinStateFilters, CoinStateUpdate, Handshake, Message,
+    NewPeakWallet, ProtocolMessageTypes, PuzzleSolutionResponse, RegisterForCoinUpdates,
+    RegisterForPhUpdates, RejectBlockHeaders, RejectCoinState, RejectPuzzleSolution,
+    RejectPuzzleState, RequestBlockHeaders, RequestChildren, RequestCoinState, RequestPeers,
+    RequestProofOfWeight, RequestPuzzleSolution, RequestPuzzleState,
+    RequestRemoveCoinSubscriptions, RequestRemovePuzzleSubscriptions, RequestTransaction,
+    RespondBlockHeaders, RespondChildren, RespondCoinState, RespondPeers, RespondProofOfWeight,
+    RespondPuzzleSolution, RespondPuzzleState, RespondRemoveCoinSubscriptions,
+    RespondRemovePuzzleSubscriptions, RespondToCoinUpdates, RespondToPhUpdates, RespondTransaction,
+    SendTransaction, SpendBundle, TransactionAck,
 };
 use chia_traits::Streamable;
 use futures_util::{
@@ -17,7 +19,7 @@ use futures_util::{
 };
 use tokio::{
     net::TcpStream,
-    sync::{mpsc, oneshot, Mutex},
+    sync::{mpsc, oneshot, watch, Mutex},
     task::JoinHandle,
 };
 use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
@@ -27,6 +29,8 @@ use crate::{request_map::RequestMap, ClientError, RateLimiter, V2_RATE_LIMITS};
 
 #[cfg(any(feature = "native-tls", feature = "rustls"))]
 use tokio_tungstenite::Connector;
+#[cfg(any(feature = "native-tls", feature = "rustls"))]
+use tungstenite::protocol::WebSocketConfig;
 
 type WebSocket = WebSocketStream<MaybeTlsStream<TcpStream>>;
 type Sink = SplitSink<WebSocket, tungstenite::Message>;
@@ -35,27 +39,63 @@ type Response<T, E> = std::result::Result<T, E>;
 
 #[derive(Debug, Clone, Copy)]
 pub struct PeerOptions {
+    /// The fraction of the rate limits which will be used, for both outbound and inbound messages.
     pub rate_limit_factor: f64,
+    /// The largest message that will be accepted from the peer, in bytes.
+    pub max_message_size: usize,
+    /// What to do when the peer sends a message which violates the rate limits or is malformed.
+    pub inbound_action: InboundAction,
+    /// The genesis challenge of the network, which is required to request the full coin state.
+    pub genesis_challenge: Option<Bytes32>,
 }
 
 impl Default for PeerOptions {
     fn default() -> Self {
         Self {
             rate_limit_factor: 0.6,
+            max_message_size: 50 * 1024 * 1024,
+            inbound_action: InboundAction::Disconnect,
+            genesis_challenge: None,
         }
     }
 }
 
+/// The action taken when an inbound message from the peer is rejected.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum InboundAction {
+    /// Discard the message and keep the connection open.
+    Drop,
+    /// Close the connection.
+    #[default]
+    Disconnect,
+    /// Close the connection, and ban the peer if it's managed by a [`Client`](crate::Client).
+    Ban,
+}
+
+/// The reason an inbound message from the peer was rejected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InboundViolation {
+    /// The peer exceeded the inbound rate limit for this message type.
+    RateLimited(ProtocolMessageTypes),
+    /// The message exceeded the maximum size, in bytes.
+    MessageTooLarge(usize),
+    /// The message could not be strictly decoded, or isn't a type the peer may send without a request.
+    InvalidMessage(Option<ProtocolMessageTypes>),
+}
+
 #[derive(Debug, Clone)]
 pub struct Peer(Arc<PeerInner>);
 
 #[derive(Debug)]
 struct PeerInner {
-    sink: Mutex<Sink>,
+    sink: Arc<Mutex<Sink>>,
     inbound_handle: JoinHandle<()>,
     requests: Arc<RequestMap>,
     socket_addr: SocketAddr,
     outbound_rate_limiter: Mutex<RateLimiter>,
+    violation: watch::Receiver<Option<InboundViolation>>,
+    peak: watch::Receiver<Option<NewPeakWallet>>,
+    genesis_challenge: Option<Bytes32>,
 }
 
 impl Peer {
@@ -77,9 +117,18 @@ impl Peer {
         connector: Connector,
         options: PeerOptions,
     ) -> Result<(Self, mpsc::Receiver<Message>), ClientError> {
-        let (ws, _) =
-            tokio_tungstenite::connect_async_tls_with_config(uri, None, false, Some(connector))
-                .await?;
+        let config = WebSocketConfig {
+            max_message_size: Some(options.max_message_size),
+            max_frame_size: Some(options.max_message_size),
+            ..Default::default()
+        };
+        let (ws, _) = tokio_tungstenite::connect_async_tls_with_config(
+            uri,
+            Some(config),
+            false,
+            Some(connector),
+        )
+        .await?;
         Self::from_websocket(ws, options)
     }
 
@@ -106,22 +155,48 @@ impl Peer {
         };
 
         let (sink, stream) = ws.split();
+        let sink = Arc::new(Mutex::new(sink));
         let (sender, receiver) = mpsc::channel(32);
+        let (violation_sender, violation) = watch::channel(None);
+        let (peak_sender, peak) = watch::channel(None);
 
         let requests = Arc::new(RequestMap::new());
-        let requests_clone = requests.clone();
+
+        let inbound = InboundHandler {
+            sink: sink.clone(),
+            sender,
+            requests: requests.clone(),
+            peak: peak_sender,
+            rate_limiter: RateLimiter::new(
+                true,
+                60,
+                options.rate_limit_factor,
+                V2_RATE_LIMITS.clone(),
+            ),
+            options,
+        };
 
         let inbound_handle = tokio::spawn(async move {
-            if let Err(error) = handle_inbound_messages(stream, sender, requests_clone).await {
-                debug!("Error handling message: {error}");
+            match inbound.handle_messages(stream).await {
+                Ok(()) => {}
+                Err(ClientError::InboundViolation(reason)) => {
+                    warn!("Closing connection due to inbound violation: {reason:?}");
+                    violation_sender.send(Some(reason)).ok();
+                }
+                Err(error) => {
+                    debug!("Error handling message: {error}");
+                }
             }
         });
 
         let peer = Self(Arc::new(PeerInner {
-            sink: Mutex::new(sink),
+            sink,
             inbound_handle,
             requests,
             socket_addr,
+            violation,
+            peak,
+            genesis_challenge: options.genesis_challenge,
             outbound_rate_limiter: Mutex::new(RateLimiter::new(
                 false,
                 60,
@@ -138,6 +213,32 @@ impl Peer {
         self.0.socket_addr
     }
 
+    /// The most recent peak announced by the peer, if any.
+    pub fn peak(&self) -> Option<NewPeakWallet> {
+        self.0.peak.borrow().clone()
+    }
+
+    /// The genesis challenge of the network, as configured in [`PeerOptions`].
+    pub fn genesis_challenge(&self) -> Result<Bytes32, ClientError> {
+        self.0
+            .genesis_challenge
+            .ok_or(ClientError::MissingGenesisChallenge)
+    }
+
+    /// Resolves with the reason the connection was closed, if it was closed due to an inbound violation.
+    /// If the connection closes for any other reason, this resolves to [`None`].
+    pub fn violation(&self) -> impl Future<Output = Option<InboundViolation>> + Send + 'static {
+        let mut violation = self.0.violation.clone();
+
+        async move {
+            violation
+                .wait_for(Option::is_some)
+                .await
+                .ok()
+                .and_then(|violation| *violation)
+        }
+    }
+
     pub async fn send_transaction(
         &self,
         spend_bundle: SpendBundle,
@@ -246,6 +347,29 @@ impl Peer {
         self.request_infallible(RequestPeers::new()).await
     }
 
+    pub async fn request_block_headers(
+        &self,
+        start_height: u32,
+        end_height: u32,
+        return_filter: bool,
+    ) -> Result<Response<RespondBlockHeaders, RejectBlockHeaders>, ClientError> {
+        self.request_fallible(RequestBlockHeaders::new(
+            start_height,
+            end_height,
+            return_filter,
+        ))
+        .await
+    }
+
+    pub async fn request_proof_of_weight(
+        &self,
+        total_number_of_blocks: u32,
+        tip: Bytes32,
+    ) -> Result<RespondProofOfWeight, ClientError> {
+        self.request_infallible(RequestProofOfWeight::new(total_number_of_blocks, tip))
+            .await
+    }
+
     /// Sends a message to the peer, but does not expect any response.
     pub async fn send<T>(&self, body: T) -> Result<(), ClientError>
     where
@@ -351,42 +475,342 @@ impl Drop for PeerInner {
     }
 }
 
-async fn handle_inbound_messages(
-    mut stream: Stream,
+struct InboundHandler {
+    sink: Arc<Mutex<Sink>>,
     sender: mpsc::Sender<Message>,
     requests: Arc<RequestMap>,
-) -> Result<(), ClientError> {
-    use tungstenite::Message::{Binary, Close, Frame, Ping, Pong, Text};
-
-    while let Some(message) = stream.next().await {
-        let message = message?;
-
-        match message {
-            Frame(..) => unreachable!(),
-            Close(..) => break,
-            Ping(..) | Pong(..) => {}
-            Text(text) => {
-                warn!("Received unexpected text message: {text}");
+    peak: watch::Sender<Option<NewPeakWallet>>,
+    rate_limiter: RateLimiter,
+    options: PeerOptions,
+}
+
+impl InboundHandler {
+    async fn handle_messages(mut self, mut stream: Stream) -> Result<(), ClientError> {
+        use tungstenite::Message::{Binary, Close, Frame, Ping, Pong, Text};
+
+        while let Some(message) = stream.next().await {
+            let message = message?;
+
+            match message {
+                Frame(..) => unreachable!(),
+                Close(..) => break,
+                Ping(..) | Pong(..) => {}
+                Text(text) => {
+                    warn!("Received unexpected text message: {text}");
+                }
+                Binary(binary) => {
+                    let message = match self.decode(&binary) {
+                        Ok(message) => message,
+                        Err(violation) => {
+                            self.reject(violation).await?;
+                            continue;
+                        }
+                    };
+
+                    let Some(id) = message.id else {
+                        self.sender.send(message).await.ok();
+                        continue;
+                    };
+
+                    let Some(request) = self.requests.remove(id).await else {
+                        warn!(
+                            "Received {:?} message with untracked id {id}",
+                            message.msg_type
+                        );
+                        return Err(ClientError::UnexpectedMessage(message.msg_type));
+                    };
+
+                    request.send(message);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Decodes and validates a message received from the peer.
+    ///
+    /// Responses to our own requests are already bounded by the outbound rate limiter,
+    /// so only unsolicited messages are subject to the inbound rate limiter.
+    fn decode(&mut self, binary: &[u8]) -> Result<Message, InboundViolation> {
+        if binary.len() > self.options.max_message_size {
+            return Err(InboundViolation::MessageTooLarge(binary.len()));
+        }
+
+        let message =
+            Message::from_bytes(binary).map_err(|_| InboundViolation::InvalidMessage(None))?;
+
+        if message.id.is_some() {
+            return Ok(message);
+        }
+
+        let mut peak = None;
+
+        // These are the only messages the wallet protocol allows a full node to send without
+        // a request, so anything else without an id is treated as malformed.
+        let valid = match message.msg_type {
+            ProtocolMessageTypes::CoinStateUpdate => {
+                CoinStateUpdate::from_bytes(&message.data).is_ok()
             }
-            Binary(binary) => {
-                let message = Message::from_bytes(&binary)?;
-
-                let Some(id) = message.id else {
-                    sender.send(message).await.ok();
-                    continue;
-                };
-
-                let Some(request) = requests.remove(id).await else {
-                    warn!(
-                        "Received {:?} message with untracked id {id}",
-                        message.msg_type
-                    );
-                    return Err(ClientError::UnexpectedMessage(message.msg_type));
-                };
-
-                request.send(message);
+            ProtocolMessageTypes::NewPeakWallet => {
+                peak = NewPeakWallet::from_bytes(&message.data).ok();
+                peak.is_some()
+            }
+            ProtocolMessageTypes::Handshake => Handshake::from_bytes(&message.data).is_ok(),
+            _ => false,
+        };
+
+        if !valid {
+            return Err(InboundViolation::InvalidMessage(Some(message.msg_type)));
+        }
+
+        if !self.rate_limiter.handle_message(&message) {
+            return Err(InboundViolation::RateLimited(message.msg_type));
+        }
+
+        // The peak is only updated once the message has been accepted.
+        if let Some(peak) = peak {
+            self.peak.send_replace(Some(peak));
+        }
+
+        Ok(message)
+    }
+
+    async fn reject(&self, violation: InboundViolation) -> Result<(), ClientError> {
+        match self.options.inbound_action {
+            InboundAction::Drop => {
+                debug!("Dropping inbound message: {violation:?}");
+                Ok(())
+            }
+            InboundAction::Disconnect | InboundAction::Ban => {
+                self.sink.lock().await.close().await.ok();
+                Err(ClientError::InboundViolation(violation))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chia_protocol::CoinStateUpdate;
+    use tokio::net::TcpListener;
+    use tokio_tungstenite::{accept_async, connect_async, WebSocketStream};
+
+    use super::*;
+
+    /// Connects a [`Peer`] to a local websocket server, and returns the server side of the connection.
+    async fn connect(
+        options: PeerOptions,
+    ) -> anyhow::Result<(Peer, mpsc::Receiver<Message>, WebSocketStream<TcpStream>)> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await?;
+            anyhow::Ok(accept_async(stream).await?)
+        });
+
+        let (ws, _) = connect_async(format!("ws://{addr}/ws")).await?;
+        let (peer, receiver) = Peer::from_websocket(ws, options)?;
+
+        Ok((peer, receiver, server.await??))
+    }
+
+    fn message<T>(body: &T) -> anyhow::Result<tungstenite::Message>
+    where
+        T: Streamable + ChiaProtocolMessage,
+    {
+        let message = Message {
+            msg_type: T::msg_type(),
+            id: None,
+            data: body.to_bytes()?.into(),
+        };
+        Ok(message.to_bytes()?.into())
+    }
+
+    fn coin_state_update() -> CoinStateUpdate {
+        CoinStateUpdate::new(1, 1, Bytes32::default(), Vec::new())
+    }
+
+    fn new_peak(height: u32) -> NewPeakWallet {
+        NewPeakWallet::new(Bytes32::default(), height, 0, height)
+    }
+
+    /// Waits for the server side of the connection to be closed by the peer.
+    async fn closed(server: &mut WebSocketStream<TcpStream>) -> bool {
+        while let Some(message) = server.next().await {
+            match message {
+                Ok(tungstenite::Message::Close(..)) | Err(..) => return true,
+                Ok(..) => {}
             }
         }
+        true
+    }
+
+    #[tokio::test]
+    async fn test_inbound_drop() -> anyhow::Result<()> {
+        let (peer, mut receiver, mut server) = connect(PeerOptions {
+            max_message_size: 1024,
+            inbound_action: InboundAction::Drop,
+            ..Default::default()
+        })
+        .await?;
+
+        // Oversized and undecodable messages are discarded, but the connection stays open.
+        server
+            .send(tungstenite::Message::Binary(vec![0; 2048]))
+            .await?;
+        server
+            .send(tungstenite::Message::Binary(vec![1, 2, 3]))
+            .await?;
+        server.send(message(&coin_state_update())?).await?;
+
+        let update = receiver.recv().await.expect("connection closed");
+        assert_eq!(update.msg_type, ProtocolMessageTypes::CoinStateUpdate);
+        assert_eq!(
+            CoinStateUpdate::from_bytes(&update.data)?,
+            coin_state_update()
+        );
+
+        drop(server);
+        assert_eq!(peer.violation().await, None);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_inbound_disconnect() -> anyhow::Result<()> {
+        let (peer, _receiver, mut server) = connect(PeerOptions {
+            inbound_action: InboundAction::Disconnect,
+            ..Default::default()
+        })
+        .await?;
+
+        server
+            .send(tungstenite::Message::Binary(vec![1, 2, 3]))
+            .await?;
+
+        assert_eq!(
+            peer.violation().await,
+            Some(InboundViolation::InvalidMessage(None))
+        );
+        assert!(closed(&mut server).await);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_inbound_ban() -> anyhow::Result<()> {
+        let (peer, _receiver, mut server) = connect(PeerOptions {
+            max_message_size: 1024,
+            inbound_action: InboundAction::Ban,
+            ..Default::default()
+        })
+        .await?;
+
+        server
+            .send(tungstenite::Message::Binary(vec![0; 2048]))
+            .await?;
+
+        assert_eq!(
+            peer.violation().await,
+            Some(InboundViolation::MessageTooLarge(2048))
+        );
+        assert!(closed(&mut server).await);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_inbound_invalid_body() -> anyhow::Result<()> {
+        let (peer, _receiver, mut server) = connect(PeerOptions::default()).await?;
+
+        let message = Message {
+            msg_type: ProtocolMessageTypes::NewPeakWallet,
+            id: None,
+            data: vec![1, 2, 3].into(),
+        };
+        server
+            .send(tungstenite::Message::Binary(message.to_bytes()?))
+            .await?;
+
+        assert_eq!(
+            peer.violation().await,
+            Some(InboundViolation::InvalidMessage(Some(
+                ProtocolMessageTypes::NewPeakWallet
+            )))
+        );
+        assert_eq!(peer.peak(), None);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_inbound_unexpected_type() -> anyhow::Result<()> {
+        let (peer, _receiver, mut server) = connect(PeerOptions::default()).await?;
+
+        // A valid response is only accepted if it's for one of our requests.
+        server
+            .send(message(&RespondChildren::new(Vec::new()))?)
+            .await?;
+
+        assert_eq!(
+            peer.violation().await,
+            Some(InboundViolation::InvalidMessage(Some(
+                ProtocolMessageTypes::RespondChildren
+            )))
+        );
+        assert!(closed(&mut server).await);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_inbound_peak() -> anyhow::Result<()> {
+        let (peer, mut receiver, mut server) = connect(PeerOptions::default()).await?;
+
+        server.send(message(&new_peak(5))?).await?;
+
+        let peak = receiver.recv().await.expect("connection closed");
+        assert_eq!(peak.msg_type, ProtocolMessageTypes::NewPeakWallet);
+        assert_eq!(peer.peak(), Some(new_peak(5)));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_inbound_rate_limited() -> anyhow::Result<()> {
+        let (peer, mut receiver, mut server) = connect(PeerOptions {
+            rate_limit_factor: 0.0,
+            inbound_action: InboundAction::Drop,
+            ..Default::default()
+        })
+        .await?;
+
+        // With a limit factor of zero, every unsolicited message is over the limit.
+        server.send(message(&coin_state_update())?).await?;
+        drop(server);
+
+        assert!(receiver.recv().await.is_none());
+        assert_eq!(peer.violation().await, None);
+
+        let (peer, _receiver, mut server) = connect(PeerOptions {
+            rate_limit_factor: 0.0,
+            ..Default::default()
+        })
+        .await?;
+
+        server.send(message(&new_peak(5))?).await?;
+
+        assert_eq!(
+            peer.violation().await,
+            Some(InboundViolation::RateLimited(
+                ProtocolMessageTypes::NewPeakWallet
+            ))
+        );
+
+        // Rate limited peaks are discarded, rather than being reported by the peer.
+        assert_eq!(peer.peak(), None);
+
+        Ok(())
     }
-    Ok(())
 }
@@ -41,7 +41,10 @@ impl RateLimiter {
     }
 
     pub fn handle_message(&mut self, message: &Message) -> bool {
-        let size: u32 = message.data.len().try_into().expect("Message too large");
+        // Messages this large can't pass any of the limits, so they're rejected without counting them.
+        let Ok(size) = u32::try_from(message.data.len()) else {
+            return false;
+        };
         let size = f64::from(size);
         let period = time() / self.reset_seconds;
 
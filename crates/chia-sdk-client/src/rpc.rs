@@ -0,0 +1,895 @@
+use std::collections::HashSet;
+
+use chia_protocol::{Bytes32, CoinSpend, CoinState, Program, SpendBundle, TransactionAck};
+use chia_sdk_types::{ChainReader, ChainWriter};
+use chia_ssl::ChiaCertificate;
+use json::{hex_streamable, CoinSpendJson, SpendBundleJson};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::json;
+
+use crate::ClientError;
+
+mod json;
+mod types;
+
+pub use types::*;
+
+/// A client for the full node RPC, which is an HTTPS JSON API secured with mutual TLS.
+#[derive(Debug, Clone)]
+pub struct FullNodeRpcClient {
+    base_url: String,
+    client: reqwest::Client,
+}
+
+impl FullNodeRpcClient {
+    /// Creates a client which authenticates with the given certificate.
+    /// For example, `https://localhost:8555` and the full node's `private_full_node` certificate.
+    ///
+    /// The full node's certificate is verified against its private CA, which is usually found at
+    /// `config/ssl/ca/private_ca.crt`. Certificates issued by the private CA aren't tied to a hostname,
+    /// so the hostname isn't verified.
+    pub fn new(
+        base_url: &str,
+        cert: &ChiaCertificate,
+        private_ca_cert_pem: &str,
+    ) -> Result<Self, ClientError> {
+        #[cfg(feature = "native-tls")]
+        let builder = reqwest::Client::builder().identity(reqwest::Identity::from_pkcs8_pem(
+            cert.cert_pem.as_bytes(),
+            cert.key_pem.as_bytes(),
+        )?);
+
+        #[cfg(not(feature = "native-tls"))]
+        let builder =
+            reqwest::Client::builder()
+                .use_rustls_tls()
+                .identity(reqwest::Identity::from_pem(
+                    format!("{}\n{}", cert.key_pem, cert.cert_pem).as_bytes(),
+                )?);
+
+        let client = builder
+            .tls_built_in_root_certs(false)
+            .add_root_certificate(reqwest::Certificate::from_pem(
+                private_ca_cert_pem.as_bytes(),
+            )?)
+            .danger_accept_invalid_hostnames(true)
+            .build()?;
+
+        Ok(Self::with_client(base_url, client))
+    }
+
+    /// Creates a client from an existing HTTP client, which is responsible for any authentication.
+    pub fn with_client(base_url: &str, client: reqwest::Client) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            client,
+        }
+    }
+
+    pub async fn get_blockchain_state(&self) -> Result<BlockchainState, ClientError> {
+        #[derive(Deserialize)]
+        struct Response {
+            blockchain_state: BlockchainState,
+        }
+
+        let response: Response = self.request("get_blockchain_state", json!({})).await?;
+        Ok(response.blockchain_state)
+    }
+
+    pub async fn get_coin_records_by_puzzle_hash(
+        &self,
+        puzzle_hash: Bytes32,
+        start_height: Option<u32>,
+        end_height: Option<u32>,
+        include_spent_coins: bool,
+    ) -> Result<Vec<CoinRecord>, ClientError> {
+        #[derive(Serialize)]
+        struct Request {
+            #[serde(with = "hex_streamable")]
+            puzzle_hash: Bytes32,
+            #[serde(flatten)]
+            filters: CoinRecordFilters,
+        }
+
+        let response: CoinRecordsResponse = self
+            .request(
+                "get_coin_records_by_puzzle_hash",
+                Request {
+                    puzzle_hash,
+                    filters: CoinRecordFilters::new(start_height, end_height, include_spent_coins),
+                },
+            )
+            .await?;
+
+        Ok(response.coin_records)
+    }
+
+    pub async fn get_coin_records_by_puzzle_hashes(
+        &self,
+        puzzle_hashes: Vec<Bytes32>,
+        start_height: Option<u32>,
+        end_height: Option<u32>,
+        include_spent_coins: bool,
+    ) -> Result<Vec<CoinRecord>, ClientError> {
+        #[derive(Serialize)]
+        struct Request {
+            puzzle_hashes: Vec<String>,
+            #[serde(flatten)]
+            filters: CoinRecordFilters,
+        }
+
+        let response: CoinRecordsResponse = self
+            .request(
+                "get_coin_records_by_puzzle_hashes",
+                Request {
+                    puzzle_hashes: puzzle_hashes.iter().map(hex_string).collect(),
+                    filters: CoinRecordFilters::new(start_height, end_height, include_spent_coins),
+                },
+            )
+            .await?;
+
+        Ok(response.coin_records)
+    }
+
+    pub async fn get_coin_records_by_hint(
+        &self,
+        hint: Bytes32,
+        start_height: Option<u32>,
+        end_height: Option<u32>,
+        include_spent_coins: bool,
+    ) -> Result<Vec<CoinRecord>, ClientError> {
+        #[derive(Serialize)]
+        struct Request {
+            #[serde(with = "hex_streamable")]
+            hint: Bytes32,
+            #[serde(flatten)]
+            filters: CoinRecordFilters,
+        }
+
+        let response: CoinRecordsResponse = self
+            .request(
+                "get_coin_records_by_hint",
+                Request {
+                    hint,
+                    filters: CoinRecordFilters::new(start_height, end_height, include_spent_coins),
+                },
+            )
+            .await?;
+
+        Ok(response.coin_records)
+    }
+
+    pub async fn get_coin_records_by_hints(
+        &self,
+        hints: Vec<Bytes32>,
+        start_height: Option<u32>,
+        end_height: Option<u32>,
+        include_spent_coins: bool,
+    ) -> Result<Vec<CoinRecord>, ClientError> {
+        #[derive(Serialize)]
+        struct Request {
+            hints: Vec<String>,
+            #[serde(flatten)]
+            filters: CoinRecordFilters,
+        }
+
+        let response: CoinRecordsResponse = self
+            .request(
+                "get_coin_records_by_hints",
+                Request {
+                    hints: hints.iter().map(hex_string).collect(),
+                    filters: CoinRecordFilters::new(start_height, end_height, include_spent_coins),
+                },
+            )
+            .await?;
+
+        Ok(response.coin_records)
+    }
+
+    pub async fn get_coin_records_by_names(
+        &self,
+        names: Vec<Bytes32>,
+        start_height: Option<u32>,
+        end_height: Option<u32>,
+        include_spent_coins: bool,
+    ) -> Result<Vec<CoinRecord>, ClientError> {
+        #[derive(Serialize)]
+        struct Request {
+            names: Vec<String>,
+            #[serde(flatten)]
+            filters: CoinRecordFilters,
+        }
+
+        let response: CoinRecordsResponse = self
+            .request(
+                "get_coin_records_by_names",
+                Request {
+                    names: names.iter().map(hex_string).collect(),
+                    filters: CoinRecordFilters::new(start_height, end_height, include_spent_coins),
+                },
+            )
+            .await?;
+
+        Ok(response.coin_records)
+    }
+
+    pub async fn get_coin_records_by_parent_ids(
+        &self,
+        parent_ids: Vec<Bytes32>,
+        start_height: Option<u32>,
+        end_height: Option<u32>,
+        include_spent_coins: bool,
+    ) -> Result<Vec<CoinRecord>, ClientError> {
+        #[derive(Serialize)]
+        struct Request {
+            parent_ids: Vec<String>,
+            #[serde(flatten)]
+            filters: CoinRecordFilters,
+        }
+
+        let response: CoinRecordsResponse = self
+            .request(
+                "get_coin_records_by_parent_ids",
+                Request {
+                    parent_ids: parent_ids.iter().map(hex_string).collect(),
+                    filters: CoinRecordFilters::new(start_height, end_height, include_spent_coins),
+                },
+            )
+            .await?;
+
+        Ok(response.coin_records)
+    }
+
+    pub async fn get_puzzle_and_solution(
+        &self,
+        coin_id: Bytes32,
+        height: u32,
+    ) -> Result<CoinSpend, ClientError> {
+        #[derive(Deserialize)]
+        struct Response {
+            coin_solution: CoinSpendJson,
+        }
+
+        let response: Response = self
+            .request(
+                "get_puzzle_and_solution",
+                json!({ "coin_id": hex_string(&coin_id), "height": height }),
+            )
+            .await?;
+
+        Ok(response.coin_solution.into())
+    }
+
+    /// Submits a spend bundle to the mempool.
+    ///
+    /// A rejection by the full node is returned as a [`TransactionAck`] with a failed status,
+    /// to match the behavior of [`Peer::send_transaction`](crate::Peer::send_transaction).
+    pub async fn push_tx(&self, spend_bundle: SpendBundle) -> Result<TransactionAck, ClientError> {
+        #[derive(Deserialize)]
+        struct Response {
+            status: String,
+        }
+
+        let transaction_id = spend_bundle.name();
+
+        let response: Response = match self
+            .request(
+                "push_tx",
+                json!({ "spend_bundle": SpendBundleJson::from(spend_bundle) }),
+            )
+            .await
+        {
+            Ok(response) => response,
+            Err(ClientError::Rpc(error)) => {
+                return Ok(TransactionAck::new(transaction_id, 3, Some(error)));
+            }
+            Err(error) => return Err(error),
+        };
+
+        let status = match response.status.as_str() {
+            "SUCCESS" => 1,
+            "PENDING" => 2,
+            _ => 3,
+        };
+
+        Ok(TransactionAck::new(transaction_id, status, None))
+    }
+
+    /// Estimates the fee required for a transaction with the given cost to be included
+    /// within each of the target times, in seconds.
+    pub async fn get_fee_estimate(
+        &self,
+        target_times: Vec<u64>,
+        cost: u64,
+    ) -> Result<FeeEstimates, ClientError> {
+        self.request(
+            "get_fee_estimate",
+            json!({ "target_times": target_times, "cost": cost }),
+        )
+        .await
+    }
+
+    pub async fn get_mempool_items_by_coin_name(
+        &self,
+        coin_name: Bytes32,
+    ) -> Result<Vec<MempoolItem>, ClientError> {
+        #[derive(Deserialize)]
+        struct Response {
+            mempool_items: Vec<MempoolItem>,
+        }
+
+        let response: Response = self
+            .request(
+                "get_mempool_items_by_coin_name",
+                json!({ "coin_name": hex_string(&coin_name) }),
+            )
+            .await?;
+
+        Ok(response.mempool_items)
+    }
+
+    /// Sends a request to an arbitrary RPC endpoint, and parses the response if it was successful.
+    pub async fn request<T, B>(&self, endpoint: &str, body: B) -> Result<T, ClientError>
+    where
+        T: DeserializeOwned,
+        B: Serialize,
+    {
+        #[derive(Deserialize)]
+        struct Status {
+            success: bool,
+            error: Option<String>,
+        }
+
+        // The response is parsed directly from the bytes, rather than through a `Value`,
+        // since some fields such as the netspace don't fit in a 64-bit integer.
+        let bytes = self
+            .client
+            .post(format!("{}/{endpoint}", self.base_url))
+            .json(&body)
+            .send()
+            .await?
+            .error_for_status()?
+            .bytes()
+            .await?;
+
+        let status: Status = serde_json::from_slice(&bytes)?;
+
+        if !status.success {
+            return Err(ClientError::Rpc(
+                status.error.unwrap_or_else(|| "Unknown error".to_string()),
+            ));
+        }
+
+        Ok(serde_json::from_slice(&bytes)?)
+    }
+}
+
+impl ChainReader for FullNodeRpcClient {
+    type Error = ClientError;
+
+    async fn peak_height(&self) -> Result<u32, ClientError> {
+        self.get_blockchain_state()
+            .await?
+            .peak
+            .map(|peak| peak.height)
+            .ok_or(ClientError::MissingPeak)
+    }
+
+    async fn coin_states(&self, coin_ids: Vec<Bytes32>) -> Result<Vec<CoinState>, ClientError> {
+        Ok(coin_states(
+            self.get_coin_records_by_names(coin_ids, None, None, true)
+                .await?,
+        ))
+    }
+
+    async fn coin_states_by_puzzle_hash(
+        &self,
+        puzzle_hashes: Vec<Bytes32>,
+        include_hinted: bool,
+    ) -> Result<Vec<CoinState>, ClientError> {
+        let mut coin_records = self
+            .get_coin_records_by_puzzle_hashes(puzzle_hashes.clone(), None, None, true)
+            .await?;
+
+        if include_hinted {
+            coin_records.extend(
+                self.get_coin_records_by_hints(puzzle_hashes, None, None, true)
+                    .await?,
+            );
+        }
+
+        Ok(coin_states(coin_records))
+    }
+
+    async fn children(&self, coin_id: Bytes32) -> Result<Vec<CoinState>, ClientError> {
+        Ok(coin_states(
+            self.get_coin_records_by_parent_ids(vec![coin_id], None, None, true)
+                .await?,
+        ))
+    }
+
+    async fn puzzle_and_solution(
+        &self,
+        coin_id: Bytes32,
+        spent_height: u32,
+    ) -> Result<Option<(Program, Program)>, ClientError> {
+        // The full node returns an error if the coin wasn't spent at the given height,
+        // so the coin record is checked first to tell that apart from other errors.
+        let coin_records = self
+            .get_coin_records_by_names(vec![coin_id], None, None, true)
+            .await?;
+
+        let is_spent_at_height = coin_records.iter().any(|record| {
+            record.coin.coin_id() == coin_id
+                && record.spent
+                && record.spent_block_index == spent_height
+        });
+
+        if !is_spent_at_height {
+            return Ok(None);
+        }
+
+        let coin_spend = self.get_puzzle_and_solution(coin_id, spent_height).await?;
+
+        Ok(Some((coin_spend.puzzle_reveal, coin_spend.solution)))
+    }
+}
+
+impl ChainWriter for FullNodeRpcClient {
+    async fn submit_transaction(
+        &mut self,
+        spend_bundle: SpendBundle,
+    ) -> Result<TransactionAck, ClientError> {
+        self.push_tx(spend_bundle).await
+    }
+}
+
+/// Converts coin records into coin states, skipping duplicates.
+fn coin_states(coin_records: Vec<CoinRecord>) -> Vec<CoinState> {
+    let mut coin_ids = HashSet::new();
+
+    coin_records
+        .into_iter()
+        .filter(|record| coin_ids.insert(record.coin.coin_id()))
+        .map(|record| record.coin_state())
+        .collect()
+}
+
+#[derive(Serialize)]
+struct CoinRecordFilters {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    start_height: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    end_height: Option<u32>,
+    include_spent_coins: bool,
+}
+
+impl CoinRecordFilters {
+    fn new(start_height: Option<u32>, end_height: Option<u32>, include_spent_coins: bool) -> Self {
+        Self {
+            start_height,
+            end_height,
+            include_spent_coins,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct CoinRecordsResponse {
+    coin_records: Vec<CoinRecord>,
+}
+
+fn hex_string(hash: &Bytes32) -> String {
+    format!("0x{}", hex::encode(hash))
+}
+
+#[cfg(test)]
+mod tests {
+    use chia_bls::Signature;
+    use chia_protocol::{Coin, CoinState, Program};
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+        task::JoinHandle,
+    };
+
+    use serde_json::Value;
+
+    use super::*;
+
+    /// Serves a single canned JSON response, and returns the request path and body it received.
+    async fn mock_server(
+        response: String,
+    ) -> anyhow::Result<(String, JoinHandle<(String, Value)>)> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let url = format!("http://{}", listener.local_addr()?);
+        let handle = tokio::spawn(async move { serve(&listener, response).await });
+        Ok((url, handle))
+    }
+
+    /// Serves each canned JSON response in turn, one per request, and returns the request paths and bodies it received.
+    async fn mock_server_sequence(
+        responses: Vec<String>,
+    ) -> anyhow::Result<(String, JoinHandle<Vec<(String, Value)>>)> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let url = format!("http://{}", listener.local_addr()?);
+
+        let handle = tokio::spawn(async move {
+            let mut requests = Vec::new();
+            for response in responses {
+                requests.push(serve(&listener, response).await);
+            }
+            requests
+        });
+
+        Ok((url, handle))
+    }
+
+    async fn serve(listener: &TcpListener, response: String) -> (String, Value) {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut buffer = Vec::new();
+
+        let (head, body) = loop {
+            let mut chunk = [0; 4096];
+            let len = stream.read(&mut chunk).await.unwrap();
+            buffer.extend_from_slice(&chunk[..len]);
+
+            let text = String::from_utf8_lossy(&buffer).to_string();
+            let Some((head, body)) = text.split_once("\r\n\r\n") else {
+                continue;
+            };
+
+            let content_length: usize = head
+                .lines()
+                .find_map(|line| {
+                    line.to_lowercase()
+                        .strip_prefix("content-length: ")
+                        .map(str::to_string)
+                })
+                .and_then(|len| len.parse().ok())
+                .unwrap_or(0);
+
+            if body.len() >= content_length {
+                break (head.to_string(), body.to_string());
+            }
+        };
+
+        let payload = response;
+        let reply = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{payload}",
+            payload.len()
+        );
+        stream.write_all(reply.as_bytes()).await.unwrap();
+
+        let path = head.split_whitespace().nth(1).unwrap().to_string();
+        (path, serde_json::from_str(&body).unwrap())
+    }
+
+    fn coin_json(coin: Coin) -> Value {
+        json!({
+            "parent_coin_info": hex_string(&coin.parent_coin_info),
+            "puzzle_hash": hex_string(&coin.puzzle_hash),
+            "amount": coin.amount,
+        })
+    }
+
+    #[tokio::test]
+    async fn test_get_coin_records_by_puzzle_hash() -> anyhow::Result<()> {
+        let coin = Coin::new(Bytes32::new([1; 32]), Bytes32::new([2; 32]), 1000);
+
+        let (url, handle) = mock_server(
+            json!({
+                "coin_records": [{
+                    "coin": coin_json(coin),
+                    "confirmed_block_index": 10,
+                    "spent_block_index": 15,
+                    "spent": true,
+                    "coinbase": false,
+                    "timestamp": 1_700_000_000,
+                }],
+                "success": true,
+            })
+            .to_string(),
+        )
+        .await?;
+
+        let client = FullNodeRpcClient::with_client(&url, reqwest::Client::new());
+        let records = client
+            .get_coin_records_by_puzzle_hash(coin.puzzle_hash, None, Some(20), true)
+            .await?;
+
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].coin, coin);
+        assert_eq!(
+            records[0].coin_state(),
+            CoinState::new(coin, Some(15), Some(10))
+        );
+
+        let (path, body) = handle.await?;
+        assert_eq!(path, "/get_coin_records_by_puzzle_hash");
+        assert_eq!(
+            body,
+            json!({
+                "puzzle_hash": hex_string(&coin.puzzle_hash),
+                "end_height": 20,
+                "include_spent_coins": true,
+            })
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_get_coin_records_by_hints() -> anyhow::Result<()> {
+        let hints = vec![Bytes32::new([5; 32]), Bytes32::new([6; 32])];
+
+        let (url, handle) =
+            mock_server(json!({ "coin_records": [], "success": true }).to_string()).await?;
+
+        let client = FullNodeRpcClient::with_client(&url, reqwest::Client::new());
+        let records = client
+            .get_coin_records_by_hints(hints.clone(), Some(5), None, false)
+            .await?;
+        assert!(records.is_empty());
+
+        // All of the hints are looked up in a single request.
+        let (path, body) = handle.await?;
+        assert_eq!(path, "/get_coin_records_by_hints");
+        assert_eq!(
+            body,
+            json!({
+                "hints": [hex_string(&hints[0]), hex_string(&hints[1])],
+                "start_height": 5,
+                "include_spent_coins": false,
+            })
+        );
+
+        Ok(())
+    }
+
+    fn coin_record_json(coin: Coin, spent_block_index: u32) -> Value {
+        json!({
+            "coin": coin_json(coin),
+            "confirmed_block_index": 5,
+            "spent_block_index": spent_block_index,
+            "spent": spent_block_index > 0,
+            "coinbase": false,
+            "timestamp": 1_700_000_000,
+        })
+    }
+
+    #[tokio::test]
+    async fn test_puzzle_and_solution() -> anyhow::Result<()> {
+        let coin = Coin::new(Bytes32::new([1; 32]), Bytes32::new([2; 32]), 1000);
+        let coin_id = coin.coin_id();
+
+        let (url, handle) = mock_server_sequence(vec![
+            json!({ "coin_records": [coin_record_json(coin, 10)], "success": true }).to_string(),
+            json!({
+                "coin_solution": {
+                    "coin": coin_json(coin),
+                    "puzzle_reveal": "0x01",
+                    "solution": "0x80",
+                },
+                "success": true,
+            })
+            .to_string(),
+        ])
+        .await?;
+
+        let client = FullNodeRpcClient::with_client(&url, reqwest::Client::new());
+        assert_eq!(
+            client.puzzle_and_solution(coin_id, 10).await?,
+            Some((Program::from(vec![1]), Program::from(vec![0x80])))
+        );
+
+        let requests = handle.await?;
+        assert_eq!(requests[0].0, "/get_coin_records_by_names");
+        assert_eq!(requests[1].0, "/get_puzzle_and_solution");
+        assert_eq!(
+            requests[1].1,
+            json!({ "coin_id": hex_string(&coin_id), "height": 10 })
+        );
+
+        // Coins which are unspent, unknown, or spent at a different height are missing,
+        // without asking the full node for the puzzle and solution.
+        for coin_records in [
+            json!([coin_record_json(coin, 0)]),
+            json!([]),
+            json!([coin_record_json(coin, 15)]),
+        ] {
+            let (url, _handle) =
+                mock_server(json!({ "coin_records": coin_records, "success": true }).to_string())
+                    .await?;
+
+            let client = FullNodeRpcClient::with_client(&url, reqwest::Client::new());
+            assert_eq!(client.puzzle_and_solution(coin_id, 10).await?, None);
+        }
+
+        // If the coin was spent at the given height, errors from the full node are returned.
+        let (url, _handle) = mock_server_sequence(vec![
+            json!({ "coin_records": [coin_record_json(coin, 10)], "success": true }).to_string(),
+            json!({ "error": "Invalid block or block generator", "success": false }).to_string(),
+        ])
+        .await?;
+
+        let client = FullNodeRpcClient::with_client(&url, reqwest::Client::new());
+        assert!(matches!(
+            client.puzzle_and_solution(coin_id, 10).await,
+            Err(ClientError::Rpc(error)) if error == "Invalid block or block generator"
+        ));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_push_tx() -> anyhow::Result<()> {
+        let coin = Coin::new(Bytes32::new([1; 32]), Bytes32::new([2; 32]), 1000);
+        let spend_bundle = SpendBundle::new(
+            vec![CoinSpend::new(
+                coin,
+                Program::from(vec![1]),
+                Program::from(vec![0x80]),
+            )],
+            Signature::default(),
+        );
+
+        let (url, handle) =
+            mock_server(json!({ "status": "SUCCESS", "success": true }).to_string()).await?;
+
+        let client = FullNodeRpcClient::with_client(&url, reqwest::Client::new());
+        let ack = client.push_tx(spend_bundle.clone()).await?;
+        assert_eq!(ack, TransactionAck::new(spend_bundle.name(), 1, None));
+
+        let (path, body) = handle.await?;
+        assert_eq!(path, "/push_tx");
+        assert_eq!(
+            body["spend_bundle"]["coin_spends"][0],
+            json!({
+                "coin": coin_json(coin),
+                "puzzle_reveal": "0x01",
+                "solution": "0x80",
+            })
+        );
+
+        let (url, _handle) =
+            mock_server(json!({ "error": "DOUBLE_SPEND", "success": false }).to_string()).await?;
+
+        let client = FullNodeRpcClient::with_client(&url, reqwest::Client::new());
+        let ack = client.push_tx(spend_bundle.clone()).await?;
+        assert_eq!(
+            ack,
+            TransactionAck::new(spend_bundle.name(), 3, Some("DOUBLE_SPEND".to_string()))
+        );
+
+        Ok(())
+    }
+
+    /// Serves a single request over TLS with a certificate signed by the Chia CA.
+    #[cfg(feature = "native-tls")]
+    fn tls_server(response: String) -> anyhow::Result<(String, std::thread::JoinHandle<()>)> {
+        use std::io::{Read, Write};
+
+        let cert = ChiaCertificate::generate()?;
+        let identity =
+            native_tls::Identity::from_pkcs8(cert.cert_pem.as_bytes(), cert.key_pem.as_bytes())?;
+        let acceptor = native_tls::TlsAcceptor::new(identity)?;
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0")?;
+        let url = format!("https://{}", listener.local_addr()?);
+
+        let handle = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+
+            // The handshake fails if the client doesn't trust the certificate.
+            let Ok(mut stream) = acceptor.accept(stream) else {
+                return;
+            };
+
+            // Reads the request headers, and then the JSON body.
+            let mut buffer = Vec::new();
+            let mut chunk = [0; 4096];
+
+            loop {
+                let len = stream.read(&mut chunk).unwrap();
+                buffer.extend_from_slice(&chunk[..len]);
+
+                let text = String::from_utf8_lossy(&buffer);
+                if len == 0
+                    || text
+                        .split_once("\r\n\r\n")
+                        .is_some_and(|(_, body)| body.ends_with('}'))
+                {
+                    break;
+                }
+            }
+
+            let reply = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{response}",
+                response.len()
+            );
+            stream.write_all(reply.as_bytes()).unwrap();
+        });
+
+        Ok((url, handle))
+    }
+
+    #[cfg(feature = "native-tls")]
+    #[tokio::test]
+    async fn test_pinned_private_ca() -> anyhow::Result<()> {
+        use chia_ssl::CHIA_CA_CRT;
+
+        let cert = ChiaCertificate::generate()?;
+        let response = json!({ "mempool_items": [], "success": true }).to_string();
+
+        let (url, handle) = tls_server(response.clone())?;
+        let client = FullNodeRpcClient::new(&url, &cert, CHIA_CA_CRT)?;
+        assert!(client
+            .get_mempool_items_by_coin_name(Bytes32::default())
+            .await?
+            .is_empty());
+        handle.join().unwrap();
+
+        // A server certificate which wasn't issued by the pinned CA is rejected.
+        let (url, handle) = tls_server(response)?;
+        let client = FullNodeRpcClient::new(&url, &cert, &cert.cert_pem)?;
+        assert!(matches!(
+            client
+                .get_mempool_items_by_coin_name(Bytes32::default())
+                .await,
+            Err(ClientError::Http(..))
+        ));
+        handle.join().unwrap();
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_get_blockchain_state() -> anyhow::Result<()> {
+        // The netspace is larger than `u64::MAX`, so it can't be built with the `json!` macro.
+        let response = format!(
+            r#"{{
+                "blockchain_state": {{
+                    "peak": {{
+                        "header_hash": "{}",
+                        "height": 42,
+                        "weight": 1000,
+                        "timestamp": null
+                    }},
+                    "sync": {{
+                        "synced": true,
+                        "sync_mode": false,
+                        "sync_progress_height": 0,
+                        "sync_tip_height": 0
+                    }},
+                    "difficulty": 1024,
+                    "sub_slot_iters": 134217728,
+                    "space": 30000000000000000000,
+                    "mempool_size": 3,
+                    "mempool_cost": 1000,
+                    "mempool_fees": 50,
+                    "mempool_max_total_cost": 550000000000,
+                    "block_max_cost": 11000000000,
+                    "node_id": "{}"
+                }},
+                "success": true
+            }}"#,
+            hex_string(&Bytes32::new([3; 32])),
+            hex_string(&Bytes32::new([4; 32])),
+        );
+
+        let (url, _handle) = mock_server(response).await?;
+
+        let client = FullNodeRpcClient::with_client(&url, reqwest::Client::new());
+        let state = client.get_blockchain_state().await?;
+
+        let peak = state.peak.expect("missing peak");
+        assert_eq!(peak.height, 42);
+        assert_eq!(peak.header_hash, Bytes32::new([3; 32]));
+        assert!(state.sync.synced);
+        assert_eq!(state.space, 30_000_000_000_000_000_000);
+        assert_eq!(state.node_id, Bytes32::new([4; 32]));
+
+        Ok(())
+    }
+}
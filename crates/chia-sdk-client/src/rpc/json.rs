@@ -0,0 +1,131 @@
+use chia_bls::Signature;
+use chia_protocol::{Bytes32, Coin, CoinSpend, Program, SpendBundle};
+use serde::{Deserialize, Deserializer, Serialize};
+
+/// Serializes streamable values as `0x` prefixed hex strings, which is how the RPC represents
+/// hashes, programs and signatures.
+pub(crate) mod hex_streamable {
+    use chia_traits::Streamable;
+    use serde::{de, ser, Deserialize, Deserializer, Serializer};
+
+    pub(crate) fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: Streamable,
+        S: Serializer,
+    {
+        let bytes = value.to_bytes().map_err(ser::Error::custom)?;
+        serializer.serialize_str(&format!("0x{}", hex::encode(bytes)))
+    }
+
+    pub(crate) fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: Streamable,
+        D: Deserializer<'de>,
+    {
+        let text = String::deserialize(deserializer)?;
+        let bytes =
+            hex::decode(text.strip_prefix("0x").unwrap_or(&text)).map_err(de::Error::custom)?;
+        T::from_bytes(&bytes).map_err(de::Error::custom)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub(crate) struct CoinJson {
+    #[serde(with = "hex_streamable")]
+    parent_coin_info: Bytes32,
+    #[serde(with = "hex_streamable")]
+    puzzle_hash: Bytes32,
+    amount: u64,
+}
+
+impl From<Coin> for CoinJson {
+    fn from(coin: Coin) -> Self {
+        Self {
+            parent_coin_info: coin.parent_coin_info,
+            puzzle_hash: coin.puzzle_hash,
+            amount: coin.amount,
+        }
+    }
+}
+
+impl From<CoinJson> for Coin {
+    fn from(coin: CoinJson) -> Self {
+        Coin::new(coin.parent_coin_info, coin.puzzle_hash, coin.amount)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct CoinSpendJson {
+    coin: CoinJson,
+    #[serde(with = "hex_streamable")]
+    puzzle_reveal: Program,
+    #[serde(with = "hex_streamable")]
+    solution: Program,
+}
+
+impl From<CoinSpend> for CoinSpendJson {
+    fn from(coin_spend: CoinSpend) -> Self {
+        Self {
+            coin: coin_spend.coin.into(),
+            puzzle_reveal: coin_spend.puzzle_reveal,
+            solution: coin_spend.solution,
+        }
+    }
+}
+
+impl From<CoinSpendJson> for CoinSpend {
+    fn from(coin_spend: CoinSpendJson) -> Self {
+        CoinSpend::new(
+            coin_spend.coin.into(),
+            coin_spend.puzzle_reveal,
+            coin_spend.solution,
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct SpendBundleJson {
+    coin_spends: Vec<CoinSpendJson>,
+    #[serde(with = "hex_streamable")]
+    aggregated_signature: Signature,
+}
+
+impl From<SpendBundle> for SpendBundleJson {
+    fn from(spend_bundle: SpendBundle) -> Self {
+        Self {
+            coin_spends: spend_bundle
+                .coin_spends
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+            aggregated_signature: spend_bundle.aggregated_signature,
+        }
+    }
+}
+
+impl From<SpendBundleJson> for SpendBundle {
+    fn from(spend_bundle: SpendBundleJson) -> Self {
+        SpendBundle::new(
+            spend_bundle
+                .coin_spends
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+            spend_bundle.aggregated_signature,
+        )
+    }
+}
+
+pub(crate) fn deserialize_coin<'de, D>(deserializer: D) -> Result<Coin, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    CoinJson::deserialize(deserializer).map(Into::into)
+}
+
+pub(crate) fn deserialize_spend_bundle<'de, D>(deserializer: D) -> Result<SpendBundle, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    SpendBundleJson::deserialize(deserializer).map(Into::into)
+}
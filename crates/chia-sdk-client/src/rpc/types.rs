@@ -0,0 +1,87 @@
+use chia_protocol::{Bytes32, Coin, CoinState, SpendBundle};
+use serde::Deserialize;
+
+use super::json::{deserialize_coin, deserialize_spend_bundle, hex_streamable};
+
+/// A coin record, as returned by the full node RPC.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub struct CoinRecord {
+    #[serde(deserialize_with = "deserialize_coin")]
+    pub coin: Coin,
+    pub confirmed_block_index: u32,
+    pub spent_block_index: u32,
+    pub spent: bool,
+    pub coinbase: bool,
+    pub timestamp: u64,
+}
+
+impl CoinRecord {
+    /// Converts the coin record into the [`CoinState`] the wallet protocol would return for it.
+    pub fn coin_state(&self) -> CoinState {
+        CoinState::new(
+            self.coin,
+            self.spent.then_some(self.spent_block_index),
+            Some(self.confirmed_block_index),
+        )
+    }
+}
+
+/// The state of the blockchain, as seen by the full node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub struct BlockchainState {
+    pub peak: Option<BlockchainPeak>,
+    pub sync: SyncState,
+    pub difficulty: u64,
+    pub sub_slot_iters: u64,
+    pub space: u128,
+    pub mempool_size: u32,
+    pub mempool_cost: u64,
+    pub mempool_fees: u64,
+    pub mempool_max_total_cost: u64,
+    pub block_max_cost: u64,
+    #[serde(with = "hex_streamable")]
+    pub node_id: Bytes32,
+}
+
+/// The subset of the peak block record that's relevant to wallets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub struct BlockchainPeak {
+    #[serde(with = "hex_streamable")]
+    pub header_hash: Bytes32,
+    pub height: u32,
+    pub weight: u128,
+    pub timestamp: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub struct SyncState {
+    pub synced: bool,
+    pub sync_mode: bool,
+    pub sync_progress_height: u32,
+    pub sync_tip_height: u32,
+}
+
+/// Fee estimates for each of the requested target times, in mojos.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct FeeEstimates {
+    pub estimates: Vec<u64>,
+    pub target_times: Vec<u64>,
+    pub current_fee_rate: f64,
+    pub mempool_size: u64,
+    pub mempool_fees: u64,
+    pub mempool_max_size: u64,
+    pub full_node_synced: bool,
+    pub peak_height: u32,
+    pub last_peak_timestamp: u64,
+}
+
+/// A spend bundle that is currently in the mempool.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct MempoolItem {
+    #[serde(deserialize_with = "deserialize_spend_bundle")]
+    pub spend_bundle: SpendBundle,
+    #[serde(with = "hex_streamable")]
+    pub spend_bundle_name: Bytes32,
+    pub fee: u64,
+    pub cost: u64,
+}
@@ -1,5 +1,9 @@
 use std::num::TryFromIntError;
 
+use chia_consensus::gen::validation_error::ValidationErr;
+use chia_protocol::Bytes32;
+use chia_sdk_signer::SignerError;
+use chia_sdk_utils::CoinSelectionError;
 use clvm_traits::{FromClvmError, ToClvmError};
 use clvmr::reduction::EvalErr;
 use thiserror::Error;
@@ -21,6 +25,15 @@ pub enum DriverError {
     #[error("clvm eval error: {0}")]
     Eval(#[from] EvalErr),
 
+    #[error("coin selection error: {0}")]
+    CoinSelection(#[from] CoinSelectionError),
+
+    #[error("signer error: {0}")]
+    Signer(#[from] SignerError),
+
+    #[error("validation error: {0}")]
+    Validation(#[from] ValidationErr),
+
     #[error("invalid mod hash")]
     InvalidModHash,
 
@@ -45,6 +58,42 @@ pub enum DriverError {
     #[error("expected even oracle fee, but it was odd")]
     OddOracleFee,
 
+    #[error("unknown metadata key {0}")]
+    UnknownMetadataKey(String),
+
+    #[error("missing content hash for the uris with metadata key {0}")]
+    MissingContentHash(&'static str),
+
+    #[error("content hash with metadata key {0} has already been set")]
+    ContentHashAlreadySet(&'static str),
+
+    #[error("not enough funds to pay for the launchers and fee of every bundle")]
+    InsufficientMintFunds,
+
+    #[error("minting a single nft exceeds the maximum bundle cost")]
+    MintExceedsMaxCost,
+
+    #[error("selected coin {0} is not spendable")]
+    UnspendableCoin(Bytes32),
+
+    #[error("royalty amount is too large")]
+    RoyaltyOverflow,
+
+    #[error("recovery list does not match the recovery list hash")]
+    InvalidRecoveryList,
+
+    #[error("not enough attestations to recover the did")]
+    InsufficientAttestations,
+
+    #[error("recovery did {0} has not attested, but every recovery did must attest")]
+    MissingAttestation(Bytes32),
+
+    #[error("attestation does not match the recovery message")]
+    AttestationMismatch,
+
+    #[error("public key does not match the p2 puzzle hash")]
+    InvalidOwnerKey,
+
     #[error("custom driver error: {0}")]
     Custom(String),
 }
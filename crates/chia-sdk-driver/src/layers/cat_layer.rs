@@ -4,7 +4,7 @@ use clvm_traits::FromClvm;
 use clvm_utils::{CurriedProgram, ToTreeHash, TreeHash};
 use clvmr::{Allocator, NodePtr};
 
-use crate::{DriverError, Layer, Puzzle, SpendContext};
+use crate::{DriverError, Layer, Puzzle, RecognizableLayer, SpendContext};
 
 /// The CAT [`Layer`] enforces restrictions on the supply of a token.
 /// Specifically, unless the TAIL program is run, the supply cannot change.
@@ -103,6 +103,15 @@ where
     }
 }
 
+impl RecognizableLayer for CatLayer<Puzzle> {
+    const MOD_HASH: TreeHash = CAT_PUZZLE_HASH;
+    const NAME: &'static str = "Cat";
+
+    fn inner_puzzles(&self) -> Vec<Puzzle> {
+        vec![self.inner_puzzle]
+    }
+}
+
 impl<I> ToTreeHash for CatLayer<I>
 where
     I: ToTreeHash,
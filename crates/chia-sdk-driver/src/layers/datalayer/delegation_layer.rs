@@ -4,7 +4,7 @@ use clvm_utils::{CurriedProgram, ToTreeHash, TreeHash};
 use clvmr::{Allocator, NodePtr};
 use hex_literal::hex;
 
-use crate::{DriverError, Layer, Puzzle, SpendContext};
+use crate::{DriverError, Layer, Puzzle, RecognizableLayer, SpendContext};
 
 #[allow(clippy::doc_markdown)]
 /// The Delegation [`Layer`] is used to enable DataLayer delegation capabilities
@@ -80,6 +80,11 @@ impl Layer for DelegationLayer {
     }
 }
 
+impl RecognizableLayer for DelegationLayer {
+    const MOD_HASH: TreeHash = DELEGATION_LAYER_PUZZLE_HASH;
+    const NAME: &'static str = "Delegation";
+}
+
 pub const DELEGATION_LAYER_PUZZLE: [u8; 1027] = hex!(
     "
     ff02ffff01ff02ff12ffff04ff02ffff04ff05ffff04ff0bffff04ff17ffff04ff2fffff04ff5fff
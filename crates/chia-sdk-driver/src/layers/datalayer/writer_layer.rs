@@ -5,7 +5,7 @@ use clvm_utils::{CurriedProgram, ToTreeHash, TreeHash};
 use clvmr::{Allocator, NodePtr};
 use hex_literal::hex;
 
-use crate::{DriverError, Layer, Puzzle, Spend, SpendContext, StandardLayer};
+use crate::{DriverError, Layer, Puzzle, RecognizableLayer, Spend, SpendContext, StandardLayer};
 
 /// The Writer [`Layer`] removes an authorized puzzle's ability to change the list of authorized puzzles.
 /// It's typically used with [`DelegationLayer`](crate::DelegationLayer).
@@ -76,6 +76,15 @@ where
     }
 }
 
+impl RecognizableLayer for WriterLayer<Puzzle> {
+    const MOD_HASH: TreeHash = WRITER_FILTER_PUZZLE_HASH;
+    const NAME: &'static str = "Writer";
+
+    fn inner_puzzles(&self) -> Vec<Puzzle> {
+        vec![self.inner_puzzle]
+    }
+}
+
 impl<I> ToTreeHash for WriterLayer<I>
 where
     I: ToTreeHash,
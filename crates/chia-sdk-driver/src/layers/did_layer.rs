@@ -7,7 +7,7 @@ use clvm_traits::{FromClvm, ToClvm};
 use clvm_utils::{CurriedProgram, ToTreeHash, TreeHash};
 use clvmr::{Allocator, NodePtr};
 
-use crate::{DriverError, Layer, Puzzle, SpendContext};
+use crate::{DriverError, HashedPtr, Layer, Puzzle, RecognizableLayer, SpendContext};
 
 /// The DID [`Layer`] keeps track of metadata and handles recovery capabilities.
 /// It's typically an inner layer of the [`SingletonLayer`](crate::SingletonLayer).
@@ -136,6 +136,15 @@ where
     }
 }
 
+impl RecognizableLayer for DidLayer<HashedPtr, Puzzle> {
+    const MOD_HASH: TreeHash = DID_INNER_PUZZLE_HASH;
+    const NAME: &'static str = "Did";
+
+    fn inner_puzzles(&self) -> Vec<Puzzle> {
+        vec![self.inner_puzzle]
+    }
+}
+
 impl<M, I> ToTreeHash for DidLayer<M, I>
 where
     M: ToTreeHash,
@@ -6,7 +6,7 @@ use clvm_traits::FromClvm;
 use clvm_utils::{CurriedProgram, ToTreeHash, TreeHash};
 use clvmr::{Allocator, NodePtr};
 
-use crate::{DriverError, Layer, Puzzle, SpendContext};
+use crate::{DriverError, Layer, Puzzle, RecognizableLayer, SpendContext};
 
 /// The NFT ownership [`Layer`] keeps track of the current DID that owns the NFT.
 /// It also contains a transfer layer, which is used to transfer ownership of the NFT.
@@ -106,6 +106,15 @@ where
     }
 }
 
+impl RecognizableLayer for NftOwnershipLayer<Puzzle, Puzzle> {
+    const MOD_HASH: TreeHash = NFT_OWNERSHIP_LAYER_PUZZLE_HASH;
+    const NAME: &'static str = "NftOwnership";
+
+    fn inner_puzzles(&self) -> Vec<Puzzle> {
+        vec![self.transfer_layer, self.inner_puzzle]
+    }
+}
+
 impl<T, I> ToTreeHash for NftOwnershipLayer<T, I>
 where
     T: ToTreeHash,
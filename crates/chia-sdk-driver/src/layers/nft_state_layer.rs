@@ -5,7 +5,9 @@ use clvm_traits::{FromClvm, ToClvm};
 use clvm_utils::{CurriedProgram, ToTreeHash, TreeHash};
 use clvmr::{Allocator, NodePtr};
 
-use crate::{DriverError, Layer, Puzzle, SpendContext};
+use crate::{
+    DriverError, HashedPtr, Layer, MetadataUpdater, Puzzle, RecognizableLayer, SpendContext,
+};
 
 /// The NFT state [`Layer`] keeps track of the current metadata of the NFT and how to change it.
 /// It's typically an inner layer of the [`SingletonLayer`](crate::SingletonLayer).
@@ -108,6 +110,15 @@ where
     }
 }
 
+impl RecognizableLayer for NftStateLayer<HashedPtr, Puzzle> {
+    const MOD_HASH: TreeHash = NFT_STATE_LAYER_PUZZLE_HASH;
+    const NAME: &'static str = "NftState";
+
+    fn inner_puzzles(&self) -> Vec<Puzzle> {
+        vec![self.inner_puzzle]
+    }
+}
+
 impl<M, I> ToTreeHash for NftStateLayer<M, I>
 where
     M: ToTreeHash,
@@ -156,4 +167,37 @@ impl<M, I> NftStateLayer<M, I> {
 
         Ok(parsed.metadata_info.new_metadata)
     }
+
+    /// Parses the metadata updater and its solution from an [`UpdateNftMetadata`] condition,
+    /// if the revealed puzzle matches the given [`MetadataUpdater`].
+    pub fn parse_metadata_update<U>(
+        allocator: &Allocator,
+        condition: UpdateNftMetadata<NodePtr, NodePtr>,
+    ) -> Result<Option<(U, U::Solution)>, DriverError>
+    where
+        U: MetadataUpdater,
+    {
+        let puzzle = Puzzle::parse(allocator, condition.updater_puzzle_reveal);
+
+        let Some(updater) = U::parse_puzzle(allocator, puzzle)? else {
+            return Ok(None);
+        };
+
+        let solution = U::parse_solution(allocator, condition.updater_solution)?;
+
+        Ok(Some((updater, solution)))
+    }
+
+    /// Constructs an [`UpdateNftMetadata`] condition, which updates the metadata with the given [`MetadataUpdater`].
+    pub fn update_metadata_condition<U>(
+        ctx: &mut SpendContext,
+        updater: &U,
+        solution: U::Solution,
+    ) -> Result<UpdateNftMetadata<NodePtr, NodePtr>, DriverError>
+    where
+        U: MetadataUpdater,
+    {
+        let spend = updater.spend(ctx, solution)?;
+        Ok(UpdateNftMetadata::new(spend.puzzle, spend.solution))
+    }
 }
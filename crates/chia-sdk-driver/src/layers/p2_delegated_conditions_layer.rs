@@ -5,7 +5,7 @@ use clvm_utils::{CurriedProgram, TreeHash};
 use clvmr::{Allocator, NodePtr};
 use hex_literal::hex;
 
-use crate::{DriverError, Layer, Puzzle, SpendContext};
+use crate::{DriverError, Layer, Puzzle, RecognizableLayer, SpendContext};
 
 /// The p2 delegated conditions [`Layer`] allows a certain key to spend the coin.
 /// To do so, a list of additional conditions is signed and passed in the solution.
@@ -61,6 +61,11 @@ impl Layer for P2DelegatedConditionsLayer {
     }
 }
 
+impl RecognizableLayer for P2DelegatedConditionsLayer {
+    const MOD_HASH: TreeHash = P2_DELEGATED_CONDITIONS_PUZZLE_HASH;
+    const NAME: &'static str = "P2DelegatedConditions";
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, ToClvm, FromClvm)]
 #[clvm(curry)]
 pub struct P2DelegatedConditionsArgs {
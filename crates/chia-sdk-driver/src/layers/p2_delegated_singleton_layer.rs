@@ -5,7 +5,7 @@ use clvm_utils::{CurriedProgram, ToTreeHash, TreeHash};
 use clvmr::{Allocator, NodePtr};
 use hex_literal::hex;
 
-use crate::{DriverError, Layer, Puzzle, Spend, SpendContext};
+use crate::{DriverError, Layer, Puzzle, RecognizableLayer, Spend, SpendContext};
 
 /// The p2 delegated singleton [`Layer`] allows for requiring that a singleton
 /// be spent alongside this coin to authorize it, while also outputting conditions.
@@ -112,6 +112,11 @@ impl Layer for P2DelegatedSingletonLayer {
     }
 }
 
+impl RecognizableLayer for P2DelegatedSingletonLayer {
+    const MOD_HASH: TreeHash = P2_DELEGATED_SINGLETON_PUZZLE_HASH;
+    const NAME: &'static str = "P2DelegatedSingleton";
+}
+
 impl ToTreeHash for P2DelegatedSingletonLayer {
     fn tree_hash(&self) -> TreeHash {
         P2DelegatedSingletonArgs::curry_tree_hash(self.launcher_id)
@@ -4,7 +4,7 @@ use clvm_utils::{CurriedProgram, TreeHash};
 use clvmr::{Allocator, NodePtr};
 use hex_literal::hex;
 
-use crate::{DriverError, Layer, Puzzle, SpendContext};
+use crate::{DriverError, Layer, Puzzle, RecognizableLayer, SpendContext};
 
 /// The p2 1 of n [`Layer`] allows for picking from several delegated puzzles at runtime without revealing up front.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -60,6 +60,11 @@ impl Layer for P2OneOfMany {
     }
 }
 
+impl RecognizableLayer for P2OneOfMany {
+    const MOD_HASH: TreeHash = P2_ONE_OF_MANY_PUZZLE_HASH;
+    const NAME: &'static str = "P2OneOfMany";
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, ToClvm, FromClvm)]
 #[clvm(curry)]
 pub struct P2OneOfManyArgs {
@@ -5,7 +5,7 @@ use clvm_utils::{CurriedProgram, ToTreeHash, TreeHash};
 use clvmr::{Allocator, NodePtr};
 use hex_literal::hex;
 
-use crate::{DriverError, Layer, Puzzle, Spend, SpendContext};
+use crate::{DriverError, Layer, Puzzle, RecognizableLayer, Spend, SpendContext};
 
 /// The p2 singleton [`Layer`] allows for requiring that a
 /// singleton be spent alongside this coin to authorize it.
@@ -104,6 +104,11 @@ impl Layer for P2Singleton {
     }
 }
 
+impl RecognizableLayer for P2Singleton {
+    const MOD_HASH: TreeHash = P2_SINGLETON_PUZZLE_HASH;
+    const NAME: &'static str = "P2Singleton";
+}
+
 impl ToTreeHash for P2Singleton {
     fn tree_hash(&self) -> TreeHash {
         P2SingletonArgs::curry_tree_hash(self.launcher_id)
@@ -9,7 +9,7 @@ use clvm_traits::FromClvm;
 use clvm_utils::{CurriedProgram, ToTreeHash, TreeHash};
 use clvmr::{Allocator, NodePtr};
 
-use crate::{DriverError, Layer, Puzzle, SpendContext};
+use crate::{DriverError, Layer, Puzzle, RecognizableLayer, SpendContext};
 
 /// The royalty transfer [`Layer`] is used to transfer NFTs with royalties.
 /// When an NFT is transferred, a percentage of the transfer amount is paid to an address.
@@ -94,6 +94,11 @@ impl Layer for RoyaltyTransferLayer {
     }
 }
 
+impl RecognizableLayer for RoyaltyTransferLayer {
+    const MOD_HASH: TreeHash = NFT_ROYALTY_TRANSFER_PUZZLE_HASH;
+    const NAME: &'static str = "RoyaltyTransfer";
+}
+
 impl ToTreeHash for RoyaltyTransferLayer {
     fn tree_hash(&self) -> TreeHash {
         NftRoyaltyTransferPuzzleArgs::curry_tree_hash(
@@ -1,8 +1,9 @@
 use chia_puzzles::offer::{SettlementPaymentsSolution, SETTLEMENT_PAYMENTS_PUZZLE_HASH};
 use clvm_traits::FromClvm;
+use clvm_utils::TreeHash;
 use clvmr::{Allocator, NodePtr};
 
-use crate::{DriverError, Layer, Puzzle, SpendContext};
+use crate::{DriverError, Layer, Puzzle, RecognizableLayer, SpendContext};
 
 /// The settlement [`Layer`] is used to spend coins that are part of an offer.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -37,3 +38,8 @@ impl Layer for SettlementLayer {
         Ok(FromClvm::from_clvm(allocator, solution)?)
     }
 }
+
+impl RecognizableLayer for SettlementLayer {
+    const MOD_HASH: TreeHash = SETTLEMENT_PAYMENTS_PUZZLE_HASH;
+    const NAME: &'static str = "Settlement";
+}
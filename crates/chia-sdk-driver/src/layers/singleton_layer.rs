@@ -10,7 +10,7 @@ use clvm_traits::FromClvm;
 use clvm_utils::{CurriedProgram, ToTreeHash, TreeHash};
 use clvmr::{Allocator, NodePtr};
 
-use crate::{DriverError, Layer, Puzzle, SpendContext};
+use crate::{DriverError, Layer, Puzzle, RecognizableLayer, SpendContext};
 
 /// The singleton [`Layer`] enforces uniqueness on a coin, which is identified by the launcher id.
 /// It contains an inner puzzle layer, which determines the actual behavior of the coin.
@@ -113,6 +113,15 @@ where
     }
 }
 
+impl RecognizableLayer for SingletonLayer<Puzzle> {
+    const MOD_HASH: TreeHash = SINGLETON_TOP_LAYER_PUZZLE_HASH;
+    const NAME: &'static str = "Singleton";
+
+    fn inner_puzzles(&self) -> Vec<Puzzle> {
+        vec![self.inner_puzzle]
+    }
+}
+
 impl<I> ToTreeHash for SingletonLayer<I>
 where
     I: ToTreeHash,
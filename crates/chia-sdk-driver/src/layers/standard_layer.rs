@@ -6,7 +6,9 @@ use clvm_traits::{clvm_quote, FromClvm};
 use clvm_utils::{CurriedProgram, ToTreeHash, TreeHash};
 use clvmr::{Allocator, NodePtr};
 
-use crate::{DriverError, Layer, Puzzle, Spend, SpendContext, SpendWithConditions};
+use crate::{
+    DriverError, Layer, Puzzle, RecognizableLayer, Spend, SpendContext, SpendWithConditions,
+};
 
 /// This is the actual puzzle name for the [`StandardLayer`].
 pub type P2DelegatedOrHiddenLayer = StandardLayer;
@@ -98,6 +100,11 @@ impl Layer for StandardLayer {
     }
 }
 
+impl RecognizableLayer for StandardLayer {
+    const MOD_HASH: TreeHash = STANDARD_PUZZLE_HASH;
+    const NAME: &'static str = "Standard";
+}
+
 impl SpendWithConditions for StandardLayer {
     fn spend_with_conditions(
         &self,
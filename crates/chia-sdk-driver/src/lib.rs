@@ -7,9 +7,12 @@ mod layers;
 mod merkle_tree;
 mod primitives;
 mod puzzle;
+mod puzzle_registry;
 mod spend;
 mod spend_context;
 mod spend_with_conditions;
+mod sync_error;
+mod transaction_builder;
 
 pub use driver_error::*;
 pub use hashed_ptr::*;
@@ -18,6 +21,9 @@ pub use layers::*;
 pub use merkle_tree::*;
 pub use primitives::*;
 pub use puzzle::*;
+pub use puzzle_registry::*;
 pub use spend::*;
 pub use spend_context::*;
 pub use spend_with_conditions::*;
+pub use sync_error::*;
+pub use transaction_builder::*;
@@ -7,7 +7,7 @@ use chia_puzzles::{
     EveProof, LineageProof, Proof,
 };
 use chia_sdk_types::{run_puzzle, CreateCoin, NewMetadataInfo, NewMetadataOutput};
-use chia_sdk_types::{Condition, UpdateNftMetadata};
+use chia_sdk_types::{Condition, Conditions, UpdateNftMetadata};
 use clvm_traits::{FromClvm, FromClvmError, ToClvm};
 use clvm_utils::{tree_hash, CurriedProgram, ToTreeHash, TreeHash};
 use clvmr::{Allocator, NodePtr};
@@ -15,7 +15,7 @@ use num_bigint::BigInt;
 
 use crate::{
     DelegationLayerArgs, DelegationLayerSolution, DriverError, Layer, NftStateLayer, Puzzle,
-    SingletonLayer, Spend, SpendContext, DELEGATION_LAYER_PUZZLE_HASH,
+    SingletonLayer, Spend, SpendContext, SpendWithConditions, DELEGATION_LAYER_PUZZLE_HASH,
     DL_METADATA_UPDATER_PUZZLE_HASH,
 };
 
@@ -98,6 +98,33 @@ where
         Ok(CoinSpend::new(self.coin, puzzle, solution))
     }
 
+    /// Melts this [`DataStore`], which permanently destroys the singleton.
+    /// Only the owner can melt the store, since delegated puzzles are prevented from doing so.
+    ///
+    /// The melt condition takes the place of the singleton's odd output, so all but one mojo of the
+    /// amount is reclaimed to the given puzzle hash. The remaining mojo is left over as part of the fee.
+    pub fn melt<I>(
+        self,
+        ctx: &mut SpendContext,
+        owner: &I,
+        puzzle_hash: Bytes32,
+        extra_conditions: Conditions,
+    ) -> Result<CoinSpend, DriverError>
+    where
+        M: Clone,
+        I: SpendWithConditions,
+    {
+        let mut conditions = extra_conditions.melt_singleton();
+
+        if self.coin.amount > 1 {
+            conditions =
+                conditions.create_coin(puzzle_hash, self.coin.amount - 1, vec![puzzle_hash.into()]);
+        }
+
+        let inner_spend = owner.spend_with_conditions(ctx, conditions)?;
+        self.spend(ctx, inner_spend)
+    }
+
     /// Returns the lineage proof that would be used by the child.
     pub fn child_lineage_proof(&self, ctx: &mut SpendContext) -> Result<LineageProof, DriverError> {
         Ok(LineageProof {
@@ -1703,6 +1730,52 @@ pub mod tests {
         Ok(())
     }
 
+    #[rstest]
+    fn test_melt_reclaims_amount(#[values(1, 3)] amount: u64) -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let ctx = &mut SpendContext::new();
+
+        let (sk, pk, puzzle_hash, coin) = sim.new_p2(amount)?;
+        let p2 = StandardLayer::new(pk);
+
+        let (launch_singleton, datastore) = Launcher::new(coin.coin_id(), amount).mint_datastore(
+            ctx,
+            DataStoreMetadata::default(),
+            puzzle_hash.into(),
+            vec![],
+        )?;
+        p2.spend(ctx, coin, launch_singleton)?;
+
+        let datastore_coin = datastore.coin;
+        let coin_spend = datastore.melt(ctx, &p2, puzzle_hash, Conditions::new())?;
+        ctx.insert(coin_spend);
+
+        sim.spend_coins(ctx.take(), &[sk])?;
+
+        assert!(sim
+            .coin_state(datastore_coin.coin_id())
+            .and_then(|coin_state| coin_state.spent_height)
+            .is_some());
+
+        // All but the melted mojo is reclaimed, and there's nothing left to reclaim from a single mojo store.
+        let reclaimed: Vec<Coin> = sim
+            .children(datastore_coin.coin_id())
+            .into_iter()
+            .map(|coin_state| coin_state.coin)
+            .collect();
+
+        if amount > 1 {
+            assert_eq!(
+                reclaimed,
+                [Coin::new(datastore_coin.coin_id(), puzzle_hash, amount - 1)]
+            );
+        } else {
+            assert!(reclaimed.is_empty());
+        }
+
+        Ok(())
+    }
+
     enum AttackerPuzzle {
         Admin,
         Writer,
@@ -15,8 +15,12 @@ use crate::{
 
 mod did_info;
 mod did_launcher;
+mod did_message;
+mod did_recovery;
 
 pub use did_info::*;
+pub use did_message::*;
+pub use did_recovery::*;
 
 #[must_use]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -116,6 +120,30 @@ where
         self.spend(ctx, inner_spend)
     }
 
+    /// Melts this DID, which permanently destroys the singleton.
+    ///
+    /// The melt condition takes the place of the singleton's odd output, so all but one mojo of the
+    /// amount is reclaimed to the given puzzle hash. The remaining mojo is left over as part of the fee.
+    pub fn melt<I>(
+        self,
+        ctx: &mut SpendContext,
+        inner: &I,
+        puzzle_hash: Bytes32,
+        extra_conditions: Conditions,
+    ) -> Result<(), DriverError>
+    where
+        I: SpendWithConditions,
+    {
+        let mut conditions = extra_conditions.melt_singleton();
+
+        if self.coin.amount > 1 {
+            conditions =
+                conditions.create_coin(puzzle_hash, self.coin.amount - 1, vec![puzzle_hash.into()]);
+        }
+
+        self.spend_with(ctx, inner, conditions)
+    }
+
     /// Transfers this DID to a new p2 puzzle hash.
     ///
     /// Note: This does not update the metadata. You need to do an update spend to change the metadata.
@@ -373,6 +401,46 @@ mod tests {
         Ok(())
     }
 
+    #[rstest]
+    fn test_melt_did(#[values(1, 3)] amount: u64) -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let ctx = &mut SpendContext::new();
+        let (sk, pk, puzzle_hash, coin) = sim.new_p2(amount)?;
+        let p2 = StandardLayer::new(pk);
+
+        let (create_did, did) =
+            Launcher::new(coin.coin_id(), amount).create_simple_did(ctx, &p2)?;
+        p2.spend(ctx, coin, create_did)?;
+
+        let did_coin = did.coin;
+        did.melt(ctx, &p2, puzzle_hash, Conditions::new())?;
+
+        sim.spend_coins(ctx.take(), &[sk])?;
+
+        assert!(sim
+            .coin_state(did_coin.coin_id())
+            .and_then(|coin_state| coin_state.spent_height)
+            .is_some());
+
+        // All but the melted mojo is reclaimed, and there's nothing left to reclaim from a single mojo DID.
+        let reclaimed: Vec<Coin> = sim
+            .children(did_coin.coin_id())
+            .into_iter()
+            .map(|coin_state| coin_state.coin)
+            .collect();
+
+        if amount > 1 {
+            assert_eq!(
+                reclaimed,
+                [Coin::new(did_coin.coin_id(), puzzle_hash, amount - 1)]
+            );
+        } else {
+            assert!(reclaimed.is_empty());
+        }
+
+        Ok(())
+    }
+
     #[test]
     fn test_update_did_metadata() -> anyhow::Result<()> {
         let mut sim = Simulator::new();
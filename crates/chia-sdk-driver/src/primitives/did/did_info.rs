@@ -99,6 +99,20 @@ impl<M> DidInfo<M> {
         }
     }
 
+    pub fn with_recovery_list(
+        self,
+        recovery_list_hash: Option<Bytes32>,
+        num_verifications_required: u64,
+    ) -> Self {
+        Self {
+            launcher_id: self.launcher_id,
+            recovery_list_hash,
+            num_verifications_required,
+            metadata: self.metadata,
+            p2_puzzle_hash: self.p2_puzzle_hash,
+        }
+    }
+
     pub fn inner_puzzle_hash(&self) -> TreeHash
     where
         M: ToTreeHash,
@@ -0,0 +1,213 @@
+use chia_bls::{sign, verify, PublicKey, SecretKey, Signature};
+use chia_protocol::{Bytes, Bytes32, CoinState};
+use chia_sdk_types::ChainReader;
+use clvm_traits::{clvm_tuple, FromClvm, ToClvm};
+use clvm_utils::{tree_hash, ToTreeHash};
+use clvmr::Allocator;
+
+use crate::{DriverError, HashedPtr, Puzzle, StandardLayer, SyncError};
+
+use super::Did;
+
+/// The prefix that is prepended to messages signed according to CHIP-0002.
+pub const CHIP_0002_SIGN_MESSAGE_PREFIX: &str = "Chia Signed Message";
+
+/// Calculates the hash that is signed for a message according to CHIP-0002.
+/// This is the tree hash of `("Chia Signed Message" . message)`.
+pub fn chip_0002_message_hash(message: &[u8]) -> Bytes32 {
+    let mut allocator = Allocator::new();
+    let ptr = clvm_tuple!(CHIP_0002_SIGN_MESSAGE_PREFIX, Bytes::from(message))
+        .to_clvm(&mut allocator)
+        .expect("message should serialize");
+    tree_hash(&allocator, ptr).into()
+}
+
+/// A message signed by the owner of a DID, in the same format as the reference wallet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignedDidMessage {
+    /// The launcher id of the DID that signed the message.
+    pub launcher_id: Bytes32,
+    /// The synthetic public key of the DID's standard p2 puzzle.
+    pub public_key: PublicKey,
+    /// The message that was signed.
+    pub message: Bytes,
+    /// The signature of the [`chip_0002_message_hash`] of the message.
+    pub signature: Signature,
+}
+
+impl SignedDidMessage {
+    /// Checks the signature against the public key, without checking that the key owns the DID.
+    pub fn verify_signature(&self) -> bool {
+        verify(
+            &self.signature,
+            &self.public_key,
+            chip_0002_message_hash(&self.message),
+        )
+    }
+
+    /// Checks the signature, and that the public key owns the latest coin of the DID on chain.
+    pub async fn verify<C>(&self, chain: &C) -> Result<bool, SyncError<C::Error>>
+    where
+        C: ChainReader,
+    {
+        if !self.verify_signature() {
+            return Ok(false);
+        }
+
+        let mut allocator = Allocator::new();
+
+        let Some(did) =
+            fetch_latest_did::<C, HashedPtr>(chain, &mut allocator, self.launcher_id).await?
+        else {
+            return Ok(false);
+        };
+
+        Ok(did.info.p2_puzzle_hash == StandardLayer::new(self.public_key).tree_hash().into())
+    }
+}
+
+impl<M> Did<M> {
+    /// Signs a message on behalf of the DID, according to CHIP-0002.
+    ///
+    /// The secret key must be the synthetic key of the [`StandardLayer`] that owns the DID.
+    pub fn sign_message(
+        &self,
+        secret_key: &SecretKey,
+        message: &[u8],
+    ) -> Result<SignedDidMessage, DriverError> {
+        let public_key = secret_key.public_key();
+
+        if self.info.p2_puzzle_hash != StandardLayer::new(public_key).tree_hash().into() {
+            return Err(DriverError::InvalidOwnerKey);
+        }
+
+        Ok(SignedDidMessage {
+            launcher_id: self.info.launcher_id,
+            public_key,
+            message: message.to_vec().into(),
+            signature: sign(secret_key, chip_0002_message_hash(message)),
+        })
+    }
+}
+
+/// Follows the DID from its launcher to its latest unspent coin.
+///
+/// Returns [`None`] if the DID doesn't exist, has been melted, or its eve coin hasn't been spent
+/// yet, since there's no way to know the owner until then.
+async fn fetch_latest_did<C, M>(
+    chain: &C,
+    allocator: &mut Allocator,
+    launcher_id: Bytes32,
+) -> Result<Option<Did<M>>, SyncError<C::Error>>
+where
+    C: ChainReader,
+    M: ToClvm<Allocator> + FromClvm<Allocator> + Clone,
+{
+    let Some(mut coin_state) = singleton_child(chain, launcher_id).await? else {
+        return Ok(None);
+    };
+
+    let mut did = None;
+
+    while let Some(spent_height) = coin_state.spent_height {
+        let coin = coin_state.coin;
+
+        let Some((puzzle, solution)) = chain
+            .puzzle_and_solution(coin.coin_id(), spent_height)
+            .await
+            .map_err(SyncError::Chain)?
+        else {
+            return Ok(None);
+        };
+
+        let Some(child_state) = singleton_child(chain, coin.coin_id()).await? else {
+            return Ok(None);
+        };
+
+        let puzzle = puzzle.to_clvm(allocator).map_err(DriverError::from)?;
+        let solution = solution.to_clvm(allocator).map_err(DriverError::from)?;
+        let puzzle = Puzzle::parse(allocator, puzzle);
+
+        let Some(child) =
+            Did::<M>::parse_child(allocator, coin, puzzle, solution, child_state.coin)?
+        else {
+            return Ok(None);
+        };
+
+        did = Some(child);
+        coin_state = child_state;
+    }
+
+    Ok(did)
+}
+
+/// Looks up the odd amount child of a singleton coin.
+async fn singleton_child<C>(
+    chain: &C,
+    coin_id: Bytes32,
+) -> Result<Option<CoinState>, SyncError<C::Error>>
+where
+    C: ChainReader,
+{
+    Ok(chain
+        .children(coin_id)
+        .await
+        .map_err(SyncError::Chain)?
+        .into_iter()
+        .find(|child| child.coin.amount % 2 == 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use chia_sdk_test::Simulator;
+    use chia_sdk_types::Conditions;
+
+    use crate::{Launcher, SpendContext};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_sign_and_verify_did_message() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let ctx = &mut SpendContext::new();
+
+        let (alice_secret_key, alice_public_key, _, coin) = sim.child_p2(1, 0)?;
+        let alice = StandardLayer::new(alice_public_key);
+
+        let (bob_secret_key, _bob_public_key, bob_puzzle_hash, _) = sim.child_p2(0, 1)?;
+
+        let (create_did, did) = Launcher::new(coin.coin_id(), 1).create_simple_did(ctx, &alice)?;
+        alice.spend(ctx, coin, create_did)?;
+
+        sim.spend_coins(ctx.take(), &[alice_secret_key.clone()])?;
+
+        let message = b"Log in to example.com";
+        let signed = did.sign_message(&alice_secret_key, message)?;
+
+        assert!(signed.verify_signature());
+        assert!(signed.verify(&sim).await?);
+
+        assert!(matches!(
+            did.sign_message(&bob_secret_key, message),
+            Err(DriverError::InvalidOwnerKey)
+        ));
+
+        // Tampering with the message invalidates the signature.
+        let mut tampered = signed.clone();
+        tampered.message = b"Log in to example.org".to_vec().into();
+        assert!(!tampered.verify(&sim).await?);
+
+        // Once the DID is transferred, the previous owner's signatures are no longer valid.
+        let did = did.transfer(ctx, &alice, bob_puzzle_hash, Conditions::new())?;
+        sim.spend_coins(ctx.take(), &[alice_secret_key])?;
+
+        assert!(!signed.verify(&sim).await?);
+        assert!(
+            did.sign_message(&bob_secret_key, message)?
+                .verify(&sim)
+                .await?
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,506 @@
+use chia_bls::PublicKey;
+use chia_protocol::{Bytes32, Coin};
+use chia_puzzles::{
+    singleton::{SingletonArgs, SingletonSolution},
+    CoinProof, Proof,
+};
+use chia_sdk_types::Conditions;
+use clvm_traits::{clvm_quote, FromClvm, ToClvm};
+use clvm_utils::{ToTreeHash, TreeHash};
+use clvmr::{Allocator, NodePtr};
+
+use crate::{DriverError, Layer, Puzzle, Spend, SpendContext, SpendWithConditions};
+
+use super::Did;
+
+/// Calculates the recovery list hash that is curried into a DID, given the launcher ids of the recovery DIDs.
+pub fn did_recovery_list_hash(recovery_list: &[Bytes32]) -> Bytes32 {
+    recovery_list.to_vec().tree_hash().into()
+}
+
+/// The message that a recovery DID attests to, which approves moving the recovering DID coin to a new inner puzzle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DidRecoveryMessage {
+    /// The coin id of the DID that is being recovered.
+    pub recovering_coin_id: Bytes32,
+    /// The inner puzzle hash (inside of the singleton layer) of the recovered DID.
+    pub new_inner_puzzle_hash: Bytes32,
+    /// The public key that must sign the new inner puzzle hash to complete the recovery.
+    pub public_key: PublicKey,
+}
+
+impl DidRecoveryMessage {
+    pub fn new(
+        recovering_coin_id: Bytes32,
+        new_inner_puzzle_hash: Bytes32,
+        public_key: PublicKey,
+    ) -> Self {
+        Self {
+            recovering_coin_id,
+            new_inner_puzzle_hash,
+            public_key,
+        }
+    }
+
+    /// The conditions output by the message coin when it's spent.
+    pub fn conditions(&self) -> Conditions {
+        Conditions::new()
+            .create_coin_announcement(self.recovering_coin_id.into())
+            .agg_sig_unsafe(self.public_key, self.new_inner_puzzle_hash.into())
+    }
+
+    /// Allocates the message puzzle, which simply returns the [`DidRecoveryMessage::conditions`].
+    pub fn puzzle(&self, ctx: &mut SpendContext) -> Result<NodePtr, DriverError> {
+        ctx.alloc(&clvm_quote!(self.conditions()))
+    }
+
+    pub fn puzzle_hash(&self) -> TreeHash {
+        let mut allocator = Allocator::new();
+        let ptr = clvm_quote!(self.conditions())
+            .to_clvm(&mut allocator)
+            .expect("message puzzle should serialize");
+        clvm_utils::tree_hash(&allocator, ptr)
+    }
+}
+
+/// An attestation created by a recovery DID, which can be used towards recovering another DID.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DidAttestation {
+    /// The launcher id of the recovery DID that created this attestation.
+    pub launcher_id: Bytes32,
+    /// The proof of the recovery DID coin that created the message coin.
+    pub coin_proof: CoinProof,
+    /// The zero amount coin that holds the message puzzle.
+    pub message_coin: Coin,
+    /// The message that was attested to.
+    pub message: DidRecoveryMessage,
+}
+
+impl DidAttestation {
+    /// Spends the message coin, which announces the approval and requires a signature
+    /// from the public key in the message.
+    pub fn spend_message(&self, ctx: &mut SpendContext) -> Result<(), DriverError> {
+        let puzzle = self.message.puzzle(ctx)?;
+        ctx.spend(self.message_coin, Spend::new(puzzle, NodePtr::NIL))
+    }
+}
+
+#[derive(Debug, Clone, ToClvm)]
+#[clvm(list)]
+struct DidRecoverySpend {
+    mode: u8,
+    amount: u64,
+    new_inner_puzzle_hash: Bytes32,
+    recovery_coins: Vec<Option<CoinProof>>,
+    public_key: PublicKey,
+    recovery_list_reveal: Vec<Bytes32>,
+    my_id: Bytes32,
+}
+
+impl<M> Did<M>
+where
+    M: ToClvm<Allocator> + FromClvm<Allocator> + ToTreeHash + Clone,
+{
+    /// Attests to the recovery of another DID coin, using this DID as one of its recovery DIDs.
+    ///
+    /// This recreates the DID and creates a message coin, which must be spent alongside the recovery.
+    pub fn attest_recovery<I>(
+        self,
+        ctx: &mut SpendContext,
+        inner: &I,
+        message: DidRecoveryMessage,
+        extra_conditions: Conditions,
+    ) -> Result<(Did<M>, DidAttestation), DriverError>
+    where
+        I: SpendWithConditions,
+    {
+        let message_puzzle_hash = message.puzzle_hash().into();
+
+        let attestation = DidAttestation {
+            launcher_id: self.info.launcher_id,
+            coin_proof: CoinProof {
+                parent_coin_info: self.coin.parent_coin_info,
+                inner_puzzle_hash: self.info.inner_puzzle_hash().into(),
+                amount: self.coin.amount,
+            },
+            message_coin: Coin::new(self.coin.coin_id(), message_puzzle_hash, 0),
+            message,
+        };
+
+        let did = self.update(
+            ctx,
+            inner,
+            extra_conditions.create_coin(message_puzzle_hash, 0, Vec::new()),
+        )?;
+
+        Ok((did, attestation))
+    }
+
+    /// Recovers this DID to a new p2 puzzle hash, using attestations from its recovery DIDs.
+    ///
+    /// The current p2 puzzle must be revealed, but it's not run. The recovery must be signed by
+    /// the public key in the [`Did::recovery_message`], and the message coins are spent as well.
+    ///
+    /// Note: The DID1 puzzle fails to skip recovery DIDs that haven't attested (the arguments to its
+    /// recursive call are misaligned), so every DID in the recovery list must provide an attestation,
+    /// regardless of the number of verifications required. If one hasn't, [`DriverError::MissingAttestation`]
+    /// is returned.
+    pub fn recover(
+        self,
+        ctx: &mut SpendContext,
+        p2_puzzle: NodePtr,
+        new_p2_puzzle_hash: Bytes32,
+        public_key: PublicKey,
+        recovery_list: Vec<Bytes32>,
+        attestations: &[DidAttestation],
+    ) -> Result<Did<M>, DriverError> {
+        if self.info.recovery_list_hash != Some(did_recovery_list_hash(&recovery_list)) {
+            return Err(DriverError::InvalidRecoveryList);
+        }
+
+        let message = self.recovery_message(new_p2_puzzle_hash, public_key);
+
+        if self.info.num_verifications_required == 0
+            || self.info.num_verifications_required > recovery_list.len() as u64
+        {
+            return Err(DriverError::InsufficientAttestations);
+        }
+
+        let mut recovery_coins = Vec::with_capacity(recovery_list.len());
+        let mut used_attestations = Vec::with_capacity(recovery_list.len());
+
+        for launcher_id in &recovery_list {
+            let Some(attestation) = attestations
+                .iter()
+                .find(|attestation| attestation.launcher_id == *launcher_id)
+            else {
+                return Err(DriverError::MissingAttestation(*launcher_id));
+            };
+
+            if attestation.message != message {
+                return Err(DriverError::AttestationMismatch);
+            }
+
+            recovery_coins.push(Some(attestation.coin_proof));
+            used_attestations.push(attestation);
+        }
+
+        self.spend_recovery(ctx, p2_puzzle, message, recovery_list, recovery_coins)?;
+
+        for attestation in used_attestations {
+            attestation.spend_message(ctx)?;
+        }
+
+        let metadata = self.info.metadata.clone();
+
+        Ok(self.wrapped_child(new_p2_puzzle_hash, metadata))
+    }
+
+    /// Spends the DID in recovery mode. Recovery DIDs without a coin proof are meant to be skipped,
+    /// but that doesn't work with the DID1 puzzle.
+    fn spend_recovery(
+        &self,
+        ctx: &mut SpendContext,
+        p2_puzzle: NodePtr,
+        message: DidRecoveryMessage,
+        recovery_list: Vec<Bytes32>,
+        recovery_coins: Vec<Option<CoinProof>>,
+    ) -> Result<(), DriverError> {
+        let layers = self
+            .info
+            .clone()
+            .into_layers(Puzzle::parse(&ctx.allocator, p2_puzzle));
+
+        let puzzle = layers.construct_puzzle(ctx)?;
+        let inner_solution = ctx.alloc(&DidRecoverySpend {
+            mode: 0,
+            amount: self.coin.amount,
+            new_inner_puzzle_hash: message.new_inner_puzzle_hash,
+            recovery_coins,
+            public_key: message.public_key,
+            recovery_list_reveal: recovery_list,
+            my_id: self.coin.coin_id(),
+        })?;
+        let solution = ctx.alloc(&SingletonSolution {
+            lineage_proof: self.proof,
+            amount: self.coin.amount,
+            inner_solution,
+        })?;
+
+        ctx.spend(self.coin, Spend::new(puzzle, solution))
+    }
+
+    /// Creates the message that recovery DIDs attest to in order to recover this DID to a new p2 puzzle hash.
+    ///
+    /// The recovered DID keeps its metadata and recovery list.
+    pub fn recovery_message(
+        &self,
+        new_p2_puzzle_hash: Bytes32,
+        public_key: PublicKey,
+    ) -> DidRecoveryMessage {
+        DidRecoveryMessage::new(
+            self.coin.coin_id(),
+            self.info
+                .clone()
+                .with_p2_puzzle_hash(new_p2_puzzle_hash)
+                .inner_puzzle_hash()
+                .into(),
+            public_key,
+        )
+    }
+
+    /// Updates the recovery list hash and number of verifications required for recovery.
+    pub fn update_recovery_list<I>(
+        self,
+        ctx: &mut SpendContext,
+        inner: &I,
+        recovery_list_hash: Option<Bytes32>,
+        num_verifications_required: u64,
+        extra_conditions: Conditions,
+    ) -> Result<Did<M>, DriverError>
+    where
+        I: SpendWithConditions,
+    {
+        let info = self
+            .info
+            .clone()
+            .with_recovery_list(recovery_list_hash, num_verifications_required);
+
+        self.spend_with(
+            ctx,
+            inner,
+            extra_conditions.create_coin(
+                info.inner_puzzle_hash().into(),
+                self.coin.amount,
+                vec![info.p2_puzzle_hash.into()],
+            ),
+        )?;
+
+        Ok(Did {
+            coin: Coin::new(
+                self.coin.coin_id(),
+                SingletonArgs::curry_tree_hash(info.launcher_id, info.inner_puzzle_hash()).into(),
+                self.coin.amount,
+            ),
+            proof: Proof::Lineage(self.child_lineage_proof()),
+            info,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chia_bls::Signature;
+    use chia_consensus::gen::validation_error::ErrorCode;
+    use chia_protocol::SpendBundle;
+    use chia_sdk_test::Simulator;
+
+    use crate::{Launcher, StandardLayer};
+
+    use super::*;
+
+    #[test]
+    fn test_recover_did() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let ctx = &mut SpendContext::new();
+
+        let (sk, pk, puzzle_hash, coin) = sim.child_p2(1, 0)?;
+        let p2 = StandardLayer::new(pk);
+
+        let (new_secret_key, new_public_key, new_puzzle_hash, _) = sim.child_p2(0, 1)?;
+        let new_p2 = StandardLayer::new(new_public_key);
+
+        let mut recovery_dids = Vec::new();
+
+        for _ in 0..3 {
+            let coin = sim.new_coin(puzzle_hash, 1);
+            let (create_did, did) = Launcher::new(coin.coin_id(), 1).create_simple_did(ctx, &p2)?;
+            p2.spend(ctx, coin, create_did)?;
+            recovery_dids.push(did);
+        }
+
+        let recovery_list: Vec<Bytes32> = recovery_dids
+            .iter()
+            .map(|did| did.info.launcher_id)
+            .collect();
+
+        let (create_did, did) = Launcher::new(coin.coin_id(), 1).create_did(
+            ctx,
+            Some(did_recovery_list_hash(&recovery_list)),
+            2,
+            (),
+            &p2,
+        )?;
+        p2.spend(ctx, coin, create_did)?;
+
+        sim.spend_coins(ctx.take(), &[sk.clone()])?;
+
+        // Each of the recovery DIDs attests to the recovery.
+        let message = did.recovery_message(new_puzzle_hash, new_public_key);
+        let mut attestations = Vec::new();
+
+        for recovery_did in recovery_dids {
+            let (_recovery_did, attestation) =
+                recovery_did.attest_recovery(ctx, &p2, message, Conditions::new())?;
+            attestations.push(attestation);
+        }
+
+        sim.spend_coins(ctx.take(), &[sk])?;
+
+        let p2_puzzle = p2.construct_puzzle(ctx)?;
+
+        assert!(matches!(
+            did.recover(
+                ctx,
+                p2_puzzle,
+                new_puzzle_hash,
+                new_public_key,
+                recovery_list.clone(),
+                &attestations[..2],
+            ),
+            Err(DriverError::MissingAttestation(launcher_id)) if launcher_id == recovery_list[2]
+        ));
+
+        assert!(matches!(
+            did.recover(
+                ctx,
+                p2_puzzle,
+                new_puzzle_hash,
+                new_public_key,
+                recovery_list[..2].to_vec(),
+                &attestations,
+            ),
+            Err(DriverError::InvalidRecoveryList)
+        ));
+
+        assert!(matches!(
+            did.recover(
+                ctx,
+                p2_puzzle,
+                puzzle_hash,
+                new_public_key,
+                recovery_list.clone(),
+                &attestations,
+            ),
+            Err(DriverError::AttestationMismatch)
+        ));
+
+        let did = did.recover(
+            ctx,
+            p2_puzzle,
+            new_puzzle_hash,
+            new_public_key,
+            recovery_list,
+            &attestations,
+        )?;
+        assert_eq!(did.info.p2_puzzle_hash, new_puzzle_hash);
+
+        // The new owner can spend the recovered DID.
+        let did = did.update(ctx, &new_p2, Conditions::new())?;
+
+        sim.spend_coins(ctx.take(), &[new_secret_key])?;
+
+        assert!(sim.coin_state(did.coin.coin_id()).is_some());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_threshold_recovery_fails() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let ctx = &mut SpendContext::new();
+
+        let (sk, pk, puzzle_hash, coin) = sim.child_p2(1, 0)?;
+        let p2 = StandardLayer::new(pk);
+
+        let (_new_secret_key, new_public_key, new_puzzle_hash, _) = sim.child_p2(0, 1)?;
+
+        let mut recovery_dids = Vec::new();
+
+        for _ in 0..3 {
+            let coin = sim.new_coin(puzzle_hash, 1);
+            let (create_did, did) = Launcher::new(coin.coin_id(), 1).create_simple_did(ctx, &p2)?;
+            p2.spend(ctx, coin, create_did)?;
+            recovery_dids.push(did);
+        }
+
+        let recovery_list: Vec<Bytes32> = recovery_dids
+            .iter()
+            .map(|did| did.info.launcher_id)
+            .collect();
+
+        let (create_did, did) = Launcher::new(coin.coin_id(), 1).create_did(
+            ctx,
+            Some(did_recovery_list_hash(&recovery_list)),
+            2,
+            (),
+            &p2,
+        )?;
+        p2.spend(ctx, coin, create_did)?;
+
+        sim.spend_coins(ctx.take(), &[sk.clone()])?;
+
+        // Only 2 of the 3 recovery DIDs attest, which meets the number of verifications required.
+        let message = did.recovery_message(new_puzzle_hash, new_public_key);
+        let mut attestations = Vec::new();
+
+        for recovery_did in recovery_dids.into_iter().take(2) {
+            let (_recovery_did, attestation) =
+                recovery_did.attest_recovery(ctx, &p2, message, Conditions::new())?;
+            attestations.push(attestation);
+        }
+
+        sim.spend_coins(ctx.take(), &[sk.clone()])?;
+
+        // The DID1 puzzle should skip the recovery DID without a coin proof, but it fails instead.
+        let p2_puzzle = p2.construct_puzzle(ctx)?;
+        let recovery_coins = attestations
+            .iter()
+            .map(|attestation| Some(attestation.coin_proof))
+            .chain([None])
+            .collect();
+
+        did.spend_recovery(ctx, p2_puzzle, message, recovery_list, recovery_coins)?;
+
+        for attestation in &attestations {
+            attestation.spend_message(ctx)?;
+        }
+
+        // The spend fails before the signature is checked, so it doesn't need to be signed.
+        assert_eq!(
+            sim.new_transaction(SpendBundle::new(ctx.take(), Signature::default()))
+                .unwrap_err()
+                .error_code(),
+            Some(ErrorCode::GeneratorRuntimeError)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_update_recovery_list() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let ctx = &mut SpendContext::new();
+
+        let (sk, pk, _puzzle_hash, coin) = sim.new_p2(1)?;
+        let p2 = StandardLayer::new(pk);
+
+        let (create_did, did) = Launcher::new(coin.coin_id(), 1).create_simple_did(ctx, &p2)?;
+        p2.spend(ctx, coin, create_did)?;
+
+        let recovery_list_hash = did_recovery_list_hash(&[Bytes32::new([42; 32])]);
+        let did =
+            did.update_recovery_list(ctx, &p2, Some(recovery_list_hash), 1, Conditions::new())?;
+
+        assert_eq!(did.info.recovery_list_hash, Some(recovery_list_hash));
+        assert_eq!(did.info.num_verifications_required, 1);
+
+        let did = did.update(ctx, &p2, Conditions::new())?;
+
+        sim.spend_coins(ctx.take(), &[sk])?;
+
+        assert!(sim.coin_state(did.coin.coin_id()).is_some());
+
+        Ok(())
+    }
+}
@@ -12,22 +12,35 @@ use chia_sdk_types::{
 use clvm_traits::{clvm_list, FromClvm, ToClvm};
 use clvm_utils::{tree_hash, ToTreeHash};
 use clvmr::{sha2::Sha256, Allocator, NodePtr};
+use hex_literal::hex;
 
 use crate::{
     DriverError, Layer, NftOwnershipLayer, NftStateLayer, Puzzle, RoyaltyTransferLayer,
     SettlementLayer, SingletonLayer, Spend, SpendContext, SpendWithConditions,
 };
 
+mod bulk_mint;
 mod did_owner;
 mod metadata_update;
+mod metadata_updater;
 mod nft_info;
 mod nft_launcher;
 mod nft_mint;
+mod royalties;
 
+pub use bulk_mint::*;
 pub use did_owner::*;
 pub use metadata_update::*;
+pub use metadata_updater::*;
 pub use nft_info::*;
 pub use nft_mint::*;
+pub use royalties::*;
+
+/// The puzzle hash that NFTs are conventionally sent to when they are burned.
+/// Nobody knows a puzzle that hashes to it, so the NFT can never be spent again.
+pub const NFT_BURN_PUZZLE_HASH: Bytes32 = Bytes32::new(hex!(
+    "000000000000000000000000000000000000000000000000000000000000dead"
+));
 
 /// Everything that is required to spend an NFT coin.
 #[must_use]
@@ -141,6 +154,29 @@ where
         self.spend(ctx, inner_spend)
     }
 
+    /// Burns this NFT by transferring it to the [`NFT_BURN_PUZZLE_HASH`].
+    ///
+    /// Unlike other singletons, NFTs can't be melted, since the ownership layer fails to run when the
+    /// melt condition is output. Creating another odd output gets past the ownership layer, but then the
+    /// singleton layer rejects the spend, since it only allows one odd output.
+    pub fn burn<I>(
+        self,
+        ctx: &mut SpendContext,
+        inner: &I,
+        extra_conditions: Conditions,
+    ) -> Result<Nft<M>, DriverError>
+    where
+        M: ToTreeHash,
+        I: SpendWithConditions,
+    {
+        self.transfer(ctx, inner, NFT_BURN_PUZZLE_HASH, extra_conditions)
+    }
+
+    /// Whether this NFT is owned by the [`NFT_BURN_PUZZLE_HASH`].
+    pub fn is_burned(&self) -> bool {
+        self.info.p2_puzzle_hash == NFT_BURN_PUZZLE_HASH
+    }
+
     /// Transfers this NFT to a new p2 puzzle hash, with new metadata.
     pub fn transfer_with_metadata<I, N>(
         self,
@@ -505,6 +541,9 @@ mod tests {
 
     use super::*;
 
+    use chia_bls::Signature;
+    use chia_consensus::gen::validation_error::ErrorCode;
+    use chia_protocol::SpendBundle;
     use chia_puzzles::nft::NftMetadata;
     use chia_sdk_test::Simulator;
 
@@ -537,6 +576,59 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_burn_nft() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let ctx = &mut SpendContext::new();
+
+        let (sk, pk, puzzle_hash, coin) = sim.new_p2(1)?;
+        let p2 = StandardLayer::new(pk);
+
+        let (mint_nft, nft) = Launcher::new(coin.coin_id(), 1).mint_nft(
+            ctx,
+            NftMint::new(NftMetadata::default(), puzzle_hash, 300, None),
+        )?;
+        p2.spend(ctx, coin, mint_nft)?;
+
+        assert!(!nft.is_burned());
+        let nft = nft.burn(ctx, &p2, Conditions::new())?;
+        assert!(nft.is_burned());
+
+        sim.spend_coins(ctx.take(), &[sk])?;
+
+        assert!(sim.coin_state(nft.coin.coin_id()).is_some());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_melt_nft_fails() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let ctx = &mut SpendContext::new();
+
+        let (sk, pk, puzzle_hash, coin) = sim.new_p2(1)?;
+        let p2 = StandardLayer::new(pk);
+
+        let (mint_nft, nft) = Launcher::new(coin.coin_id(), 1).mint_nft(
+            ctx,
+            NftMint::new(NftMetadata::default(), puzzle_hash, 300, None),
+        )?;
+        p2.spend(ctx, coin, mint_nft)?;
+        sim.spend_coins(ctx.take(), &[sk])?;
+
+        nft.spend_with(ctx, &p2, Conditions::new().melt_singleton())?;
+
+        // The spend fails before the signature is checked, so it doesn't need to be signed.
+        assert_eq!(
+            sim.new_transaction(SpendBundle::new(ctx.take(), Signature::default()))
+                .unwrap_err()
+                .error_code(),
+            Some(ErrorCode::GeneratorRuntimeError)
+        );
+
+        Ok(())
+    }
+
     #[test]
     fn test_nft_lineage() -> anyhow::Result<()> {
         let mut sim = Simulator::new();
@@ -0,0 +1,727 @@
+use std::collections::VecDeque;
+
+use chia_bls::Signature;
+use chia_consensus::{
+    consensus_constants::ConsensusConstants,
+    gen::{
+        opcodes::{AGG_SIG_COST, CREATE_COIN_COST},
+        solution_generator::calculate_generator_length,
+    },
+    spendbundle_conditions::get_conditions_from_spendbundle,
+};
+use chia_protocol::{Bytes32, Coin, CoinSpend, SpendBundle};
+use chia_puzzles::nft::{NftMetadata, NFT_METADATA_UPDATER_PUZZLE_HASH};
+use chia_sdk_signer::{AggSigConstants, RequiredSignature};
+use chia_sdk_types::{announcement_id, Conditions};
+use clvm_traits::{FromClvm, ToClvm};
+use clvm_utils::ToTreeHash;
+use clvmr::{
+    reduction::Reduction, run_program, serde::node_to_bytes, Allocator, ChiaDialect, NodePtr,
+};
+
+use crate::{Did, DidOwner, DriverError, IntermediateLauncher, SpendContext, StandardLayer};
+
+use super::{Nft, NftMint};
+
+/// Mints a collection of NFTs from a DID, split into as many spend bundles as needed to stay under a cost limit.
+///
+/// Each NFT is minted through its own [`IntermediateLauncher`], created by the DID, and is assigned to the DID
+/// with a [`TransferNft`](chia_sdk_types::TransferNft) condition that the DID asserts the announcement for.
+/// Each launcher needs 1 mojo, which along with the fee is paid for by a funding coin spent in the same bundle.
+///
+/// The bundles are planned up front, with each one spending the DID and funding coin created by the previous one,
+/// so they must be submitted in order, waiting for each to be confirmed before submitting the next.
+#[derive(Debug, Clone)]
+#[must_use]
+pub struct BulkMint {
+    items: Vec<(NftMetadata, Bytes32)>,
+    royalty_puzzle_hash: Bytes32,
+    royalty_ten_thousandths: u16,
+    metadata_updater_puzzle_hash: Bytes32,
+    editions: bool,
+    max_bundle_cost: Option<u64>,
+    fee_per_bundle: u64,
+}
+
+impl BulkMint {
+    /// Creates an empty collection, where every NFT pays the same royalty to the given puzzle hash.
+    pub fn new(royalty_puzzle_hash: Bytes32, royalty_ten_thousandths: u16) -> Self {
+        Self {
+            items: Vec::new(),
+            royalty_puzzle_hash,
+            royalty_ten_thousandths,
+            metadata_updater_puzzle_hash: NFT_METADATA_UPDATER_PUZZLE_HASH.into(),
+            editions: false,
+            max_bundle_cost: None,
+            fee_per_bundle: 0,
+        }
+    }
+
+    /// Adds an NFT to the collection, which will be sent to the given p2 puzzle hash once minted.
+    pub fn mint(mut self, metadata: NftMetadata, p2_puzzle_hash: Bytes32) -> Self {
+        self.items.push((metadata, p2_puzzle_hash));
+        self
+    }
+
+    /// Adds several NFTs to the collection, all of which will be sent to the given p2 puzzle hash once minted.
+    pub fn mint_all(
+        mut self,
+        metadata: impl IntoIterator<Item = NftMetadata>,
+        p2_puzzle_hash: Bytes32,
+    ) -> Self {
+        self.items.extend(
+            metadata
+                .into_iter()
+                .map(|metadata| (metadata, p2_puzzle_hash)),
+        );
+        self
+    }
+
+    /// Numbers the NFTs as editions of the collection, in the order they were added.
+    /// This overrides the edition number and total of each NFT's metadata.
+    pub fn with_editions(mut self) -> Self {
+        self.editions = true;
+        self
+    }
+
+    pub fn with_custom_metadata_updater(mut self, metadata_updater_puzzle_hash: Bytes32) -> Self {
+        self.metadata_updater_puzzle_hash = metadata_updater_puzzle_hash;
+        self
+    }
+
+    /// Sets the maximum cost of each bundle, which defaults to half of the maximum block cost,
+    /// since that's the most that a single spend bundle can cost to be accepted into the mempool.
+    pub fn max_bundle_cost(mut self, max_bundle_cost: u64) -> Self {
+        self.max_bundle_cost = Some(max_bundle_cost);
+        self
+    }
+
+    /// Sets the fee paid by each bundle, which defaults to 0.
+    pub fn fee_per_bundle(mut self, fee_per_bundle: u64) -> Self {
+        self.fee_per_bundle = fee_per_bundle;
+        self
+    }
+
+    /// Plans the bundles needed to mint the collection from the DID. The DID and the funding coin must both be spendable
+    /// with the given [`StandardLayer`]. Each bundle sends the change of the funding coin back to the same puzzle hash,
+    /// so that it can be spent by the next bundle.
+    ///
+    /// The coin spends are returned rather than being added to the [`SpendContext`].
+    pub fn plan<M>(
+        self,
+        ctx: &mut SpendContext,
+        did: Did<M>,
+        funding_coin: Coin,
+        p2: &StandardLayer,
+        constants: &ConsensusConstants,
+    ) -> Result<BulkMintPlan<M>, DriverError>
+    where
+        M: ToClvm<Allocator> + FromClvm<Allocator> + ToTreeHash + Clone,
+    {
+        // Any spends which were already in the context are set aside, so they aren't included.
+        let existing_spends = ctx.take();
+        let result = self.plan_bundles(ctx, did, funding_coin, p2, constants);
+        ctx.take();
+
+        for coin_spend in existing_spends {
+            ctx.insert(coin_spend);
+        }
+
+        result
+    }
+
+    fn plan_bundles<M>(
+        self,
+        ctx: &mut SpendContext,
+        did: Did<M>,
+        funding_coin: Coin,
+        p2: &StandardLayer,
+        constants: &ConsensusConstants,
+    ) -> Result<BulkMintPlan<M>, DriverError>
+    where
+        M: ToClvm<Allocator> + FromClvm<Allocator> + ToTreeHash + Clone,
+    {
+        let mut state = BundleState {
+            did,
+            funding_coin: Some(funding_coin),
+        };
+        let mint_total = self.items.len();
+        let max_bundle_cost = self
+            .max_bundle_cost
+            .unwrap_or(constants.max_block_cost_clvm / 2);
+
+        // The DID and funding coin are spent in every bundle, so their cost is accounted for up front.
+        let _ = self.finish_bundle(ctx, state.clone(), p2, Conditions::new(), 0)?;
+        let overhead = estimate_cost(&ctx.take(), constants)?;
+
+        let mut queue: VecDeque<(usize, NftMint<NftMetadata>)> = self
+            .items
+            .clone()
+            .into_iter()
+            .enumerate()
+            .map(|(index, (mut metadata, p2_puzzle_hash))| {
+                if self.editions {
+                    metadata.edition_number = index as u64 + 1;
+                    metadata.edition_total = mint_total as u64;
+                }
+
+                let mint = NftMint::new(
+                    metadata,
+                    p2_puzzle_hash,
+                    self.royalty_ten_thousandths,
+                    Some(DidOwner::from_did_info(&state.did.info)),
+                )
+                .with_royalty_puzzle_hash(self.royalty_puzzle_hash)
+                .with_custom_metadata_updater(self.metadata_updater_puzzle_hash);
+
+                (index, mint)
+            })
+            .collect();
+
+        let mut bundles = Vec::new();
+        let mut pending = PendingBundle::new(overhead);
+
+        loop {
+            let Some((index, mint)) = queue.pop_front() else {
+                if pending.nfts.is_empty() {
+                    break;
+                }
+
+                let (bundle, leftover);
+                (bundle, state, leftover) =
+                    self.finish_pending(ctx, &state, p2, pending, constants, max_bundle_cost)?;
+
+                bundles.push(bundle);
+                pending = PendingBundle::new(overhead);
+                queue.extend(leftover);
+                continue;
+            };
+
+            let minted =
+                mint_from_did(ctx, &state.did, index, mint_total, mint.clone(), constants)?;
+
+            if pending.cost + minted.cost > max_bundle_cost && !pending.nfts.is_empty() {
+                // The NFT doesn't fit, so the bundle is finished and the NFT is minted from the next DID coin instead.
+                let (bundle, leftover);
+                (bundle, state, leftover) =
+                    self.finish_pending(ctx, &state, p2, pending, constants, max_bundle_cost)?;
+
+                bundles.push(bundle);
+                pending = PendingBundle::new(overhead);
+
+                queue.push_front((index, mint));
+                for item in leftover.into_iter().rev() {
+                    queue.push_front(item);
+                }
+                continue;
+            }
+
+            if pending.cost + minted.cost > max_bundle_cost {
+                return Err(DriverError::MintExceedsMaxCost);
+            }
+
+            pending.cost += minted.cost;
+            pending.nfts.push(PendingNft {
+                index,
+                mint,
+                minted,
+            });
+        }
+
+        Ok(BulkMintPlan {
+            bundles,
+            did: state.did,
+            funding_coin: state.funding_coin,
+        })
+    }
+
+    /// Finishes the pending bundle. The estimated cost of each NFT can be lower than what it actually adds,
+    /// so if the bundle exceeds the maximum cost, NFTs are removed from the end until it fits. The removed
+    /// NFTs are returned, so that they can be minted in the next bundle instead.
+    #[allow(clippy::type_complexity)]
+    fn finish_pending<M>(
+        &self,
+        ctx: &mut SpendContext,
+        state: &BundleState<M>,
+        p2: &StandardLayer,
+        mut pending: PendingBundle,
+        constants: &ConsensusConstants,
+        max_bundle_cost: u64,
+    ) -> Result<
+        (
+            MintBundle,
+            BundleState<M>,
+            Vec<(usize, NftMint<NftMetadata>)>,
+        ),
+        DriverError,
+    >
+    where
+        M: ToClvm<Allocator> + FromClvm<Allocator> + ToTreeHash + Clone,
+    {
+        let mut leftover = Vec::new();
+
+        let (coin_spends, cost, state) = loop {
+            let mut conditions = Conditions::new();
+            for nft in &pending.nfts {
+                conditions = conditions.extend(nft.minted.did_conditions.clone());
+            }
+
+            let nft_count = pending.nfts.len() as u64;
+            let next_state = self.finish_bundle(ctx, state.clone(), p2, conditions, nft_count)?;
+
+            let mut coin_spends = ctx.take();
+            for nft in &pending.nfts {
+                coin_spends.extend(nft.minted.coin_spends.iter().cloned());
+            }
+
+            let cost = spend_bundle_cost(&coin_spends, constants)?;
+
+            if cost <= max_bundle_cost {
+                break (coin_spends, cost, next_state);
+            }
+
+            match pending.nfts.pop() {
+                Some(nft) if !pending.nfts.is_empty() => leftover.insert(0, (nft.index, nft.mint)),
+                _ => return Err(DriverError::MintExceedsMaxCost),
+            }
+        };
+
+        let required_signatures = RequiredSignature::from_coin_spends(
+            &mut ctx.allocator,
+            &coin_spends,
+            &AggSigConstants::from(constants),
+        )?;
+
+        let bundle = MintBundle {
+            coin_spends,
+            required_signatures,
+            nfts: pending.nfts.into_iter().map(|nft| nft.minted.nft).collect(),
+            fee: self.fee_per_bundle,
+            cost,
+        };
+
+        Ok((bundle, state, leftover))
+    }
+
+    /// Spends the DID with the conditions needed to mint the bundle's NFTs, along with the funding coin.
+    fn finish_bundle<M>(
+        &self,
+        ctx: &mut SpendContext,
+        BundleState { did, funding_coin }: BundleState<M>,
+        p2: &StandardLayer,
+        did_conditions: Conditions,
+        nft_count: u64,
+    ) -> Result<BundleState<M>, DriverError>
+    where
+        M: ToClvm<Allocator> + FromClvm<Allocator> + ToTreeHash + Clone,
+    {
+        let required = u128::from(self.fee_per_bundle) + u128::from(nft_count);
+
+        let Some(coin) = funding_coin.filter(|coin| u128::from(coin.amount) >= required) else {
+            return Err(DriverError::InsufficientMintFunds);
+        };
+
+        let change_amount = u64::try_from(u128::from(coin.amount) - required)?;
+
+        // The funding coin and DID are linked, so that neither can be spent without the other.
+        let mut conditions = Conditions::new()
+            .create_coin_announcement(b"$".to_vec().into())
+            .assert_coin_announcement(announcement_id(did.coin.coin_id(), "$"));
+
+        if self.fee_per_bundle > 0 {
+            conditions = conditions.reserve_fee(self.fee_per_bundle);
+        }
+
+        let mut change = None;
+
+        if change_amount > 0 {
+            conditions = conditions.create_coin(coin.puzzle_hash, change_amount, Vec::new());
+            change = Some(Coin::new(coin.coin_id(), coin.puzzle_hash, change_amount));
+        }
+
+        p2.spend(ctx, coin, conditions)?;
+
+        let did = did.update(
+            ctx,
+            p2,
+            did_conditions
+                .create_coin_announcement(b"$".to_vec().into())
+                .assert_coin_announcement(announcement_id(coin.coin_id(), "$")),
+        )?;
+
+        Ok(BundleState {
+            did,
+            funding_coin: change,
+        })
+    }
+}
+
+/// The bundles which mint a collection with [`BulkMint`], in the order they must be submitted.
+#[derive(Debug, Clone)]
+pub struct BulkMintPlan<M> {
+    pub bundles: Vec<MintBundle>,
+    /// The DID after every bundle has been confirmed.
+    pub did: Did<M>,
+    /// The change from the funding coin after every bundle has been confirmed.
+    pub funding_coin: Option<Coin>,
+}
+
+/// A single spend bundle which mints part of a collection.
+#[derive(Debug, Clone)]
+pub struct MintBundle {
+    pub coin_spends: Vec<CoinSpend>,
+    /// The signatures which must be aggregated to form the spend bundle's signature.
+    pub required_signatures: Vec<RequiredSignature>,
+    /// The NFTs minted by this bundle, in the order they were added to the collection.
+    pub nfts: Vec<Nft<NftMetadata>>,
+    pub fee: u64,
+    /// The cost of the spend bundle, as calculated by the mempool.
+    pub cost: u64,
+}
+
+/// The coins that the next bundle will spend.
+#[derive(Debug, Clone)]
+struct BundleState<M> {
+    did: Did<M>,
+    funding_coin: Option<Coin>,
+}
+
+#[derive(Debug, Clone)]
+struct PendingBundle {
+    nfts: Vec<PendingNft>,
+    cost: u64,
+}
+
+impl PendingBundle {
+    fn new(overhead: u64) -> Self {
+        Self {
+            nfts: Vec::new(),
+            cost: overhead,
+        }
+    }
+}
+
+/// An NFT in the pending bundle, along with what's needed to mint it in the next bundle instead.
+#[derive(Debug, Clone)]
+struct PendingNft {
+    index: usize,
+    mint: NftMint<NftMetadata>,
+    minted: MintedNft,
+}
+
+#[derive(Debug, Clone)]
+struct MintedNft {
+    did_conditions: Conditions,
+    coin_spends: Vec<CoinSpend>,
+    nft: Nft<NftMetadata>,
+    cost: u64,
+}
+
+fn mint_from_did<M>(
+    ctx: &mut SpendContext,
+    did: &Did<M>,
+    mint_number: usize,
+    mint_total: usize,
+    mint: NftMint<NftMetadata>,
+    constants: &ConsensusConstants,
+) -> Result<MintedNft, DriverError> {
+    let (did_conditions, nft) =
+        IntermediateLauncher::new(did.coin.coin_id(), mint_number, mint_total)
+            .create(ctx)?
+            .mint_nft(ctx, mint)?;
+
+    let coin_spends = ctx.take();
+
+    // The conditions are added to the DID's delegated puzzle, and one of them creates the intermediate coin.
+    let conditions = ctx.alloc(&did_conditions)?;
+    let condition_bytes = node_to_bytes(&ctx.allocator, conditions)?.len() as u64;
+    let cost = estimate_cost(&coin_spends, constants)?
+        + condition_bytes * constants.cost_per_byte
+        + CREATE_COIN_COST;
+
+    Ok(MintedNft {
+        did_conditions,
+        coin_spends,
+        nft,
+        cost,
+    })
+}
+
+/// Estimates the cost that the coin spends add to a spend bundle, by running each puzzle
+/// and adding the cost of their size and the conditions they output.
+///
+/// This is only used to decide which bundle each NFT fits in, since the cost of a whole bundle is
+/// calculated with [`spend_bundle_cost`] once it's finished.
+fn estimate_cost(
+    coin_spends: &[CoinSpend],
+    constants: &ConsensusConstants,
+) -> Result<u64, DriverError> {
+    let mut allocator = Allocator::new();
+    let mut cost = calculate_generator_length(coin_spends) as u64 * constants.cost_per_byte;
+
+    for coin_spend in coin_spends {
+        let puzzle = coin_spend.puzzle_reveal.to_clvm(&mut allocator)?;
+        let solution = coin_spend.solution.to_clvm(&mut allocator)?;
+
+        let Reduction(run_cost, output) = run_program(
+            &mut allocator,
+            &ChiaDialect::new(0),
+            puzzle,
+            solution,
+            constants.max_block_cost_clvm,
+        )?;
+        cost += run_cost;
+
+        for condition in Vec::<NodePtr>::from_clvm(&allocator, output)? {
+            let Some((opcode, _)) = allocator.next(condition) else {
+                continue;
+            };
+
+            match allocator.small_number(opcode) {
+                Some(51) => cost += CREATE_COIN_COST,
+                Some(43..=50) => cost += AGG_SIG_COST,
+                _ => {}
+            }
+        }
+    }
+
+    Ok(cost)
+}
+
+/// Calculates the cost of the spend bundle the same way as the mempool, under the current consensus rules.
+/// The signature isn't needed to calculate the cost, so it isn't validated.
+fn spend_bundle_cost(
+    coin_spends: &[CoinSpend],
+    constants: &ConsensusConstants,
+) -> Result<u64, DriverError> {
+    let spend_bundle = SpendBundle::new(coin_spends.to_vec(), Signature::default());
+
+    let conditions = get_conditions_from_spendbundle(
+        &mut Allocator::new(),
+        &spend_bundle,
+        constants.max_block_cost_clvm,
+        constants.hard_fork_height,
+        constants,
+    )?;
+
+    Ok(conditions.cost)
+}
+
+#[cfg(test)]
+mod tests {
+    use chia_consensus::spendbundle_conditions::get_conditions_from_spendbundle;
+    use chia_protocol::SpendBundle;
+    use chia_sdk_test::{sign_transaction, Simulator};
+
+    use crate::Launcher;
+
+    use super::*;
+
+    #[test]
+    fn test_bulk_mint_plan() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let constants = sim.config().constants.clone();
+        let ctx = &mut SpendContext::new();
+
+        let (sk, pk, puzzle_hash, coin) = sim.new_p2(1)?;
+        let funding_coin = sim.new_coin(puzzle_hash, 1000);
+        let p2 = StandardLayer::new(pk);
+
+        let (create_did, did) = Launcher::new(coin.coin_id(), 1).create_simple_did(ctx, &p2)?;
+        p2.spend(ctx, coin, create_did)?;
+        sim.spend_coins(ctx.take(), &[sk.clone()])?;
+
+        let royalty_puzzle_hash = Bytes32::new([1; 32]);
+        let max_bundle_cost = 1_000_000_000;
+
+        let plan = BulkMint::new(royalty_puzzle_hash, 300)
+            .mint_all(
+                (0..25).map(|i| NftMetadata {
+                    data_uris: vec![format!("https://example.com/{i}.png")],
+                    data_hash: Some(Bytes32::new([i; 32])),
+                    ..Default::default()
+                }),
+                puzzle_hash,
+            )
+            .with_editions()
+            .max_bundle_cost(max_bundle_cost)
+            .fee_per_bundle(100)
+            .plan(ctx, did, funding_coin, &p2, &constants)?;
+
+        let bundle_count = plan.bundles.len();
+        assert!(bundle_count > 1);
+        assert_eq!(
+            plan.bundles
+                .iter()
+                .map(|bundle| bundle.nfts.len())
+                .sum::<usize>(),
+            25
+        );
+
+        for bundle in plan.bundles {
+            assert!(bundle.cost <= max_bundle_cost);
+            assert_eq!(bundle.fee, 100);
+
+            let signature = sign_transaction(&bundle.coin_spends, &[sk.clone()])?;
+            let spend_bundle = SpendBundle::new(bundle.coin_spends.clone(), signature);
+            let conds = get_conditions_from_spendbundle(
+                &mut ctx.allocator,
+                &spend_bundle,
+                u64::MAX,
+                100_000_000,
+                &constants,
+            )?;
+            assert_eq!(conds.cost, bundle.cost);
+
+            sim.spend_coins(bundle.coin_spends, &[sk.clone()])?;
+
+            for nft in bundle.nfts {
+                assert!(sim.coin_state(nft.coin.coin_id()).is_some());
+                assert_eq!(nft.info.current_owner, Some(did.info.launcher_id));
+                assert_eq!(nft.info.royalty_puzzle_hash, royalty_puzzle_hash);
+                assert_eq!(nft.info.royalty_ten_thousandths, 300);
+                assert_eq!(nft.info.metadata.edition_total, 25);
+            }
+        }
+
+        assert!(sim.coin_state(plan.did.coin.coin_id()).is_some());
+
+        let funding_coin = plan.funding_coin.expect("missing funding coin");
+        assert_eq!(funding_coin.amount, 1000 - 25 - 100 * bundle_count as u64);
+        assert!(sim.coin_state(funding_coin.coin_id()).is_some());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_bulk_mint_fills_to_max_cost() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let constants = sim.config().constants.clone();
+        let ctx = &mut SpendContext::new();
+
+        let (sk, pk, puzzle_hash, coin) = sim.new_p2(1)?;
+        let funding_coin = sim.new_coin(puzzle_hash, 100);
+        let p2 = StandardLayer::new(pk);
+
+        let (create_did, did) = Launcher::new(coin.coin_id(), 1).create_simple_did(ctx, &p2)?;
+        p2.spend(ctx, coin, create_did)?;
+        sim.spend_coins(ctx.take(), &[sk.clone()])?;
+
+        let collection =
+            BulkMint::new(puzzle_hash, 300).mint_all(vec![NftMetadata::default(); 5], puzzle_hash);
+
+        let plan = collection.clone().max_bundle_cost(u64::MAX).plan(
+            ctx,
+            did,
+            funding_coin,
+            &p2,
+            &constants,
+        )?;
+        assert_eq!(plan.bundles.len(), 1);
+        let full_cost = plan.bundles[0].cost;
+
+        let mut pending = PendingBundle::new(0);
+
+        for index in 0..5 {
+            let mint = NftMint::new(
+                NftMetadata::default(),
+                puzzle_hash,
+                300,
+                Some(DidOwner::from_did_info(&did.info)),
+            );
+            let minted = mint_from_did(ctx, &did, index, 5, mint.clone(), &constants)?;
+            pending.nfts.push(PendingNft {
+                index,
+                mint,
+                minted,
+            });
+        }
+
+        let state = BundleState {
+            did,
+            funding_coin: Some(funding_coin),
+        };
+
+        // A bundle which costs exactly the maximum is kept whole.
+        let (bundle, _, leftover) =
+            collection.finish_pending(ctx, &state, &p2, pending.clone(), &constants, full_cost)?;
+        assert_eq!(bundle.cost, full_cost);
+        assert_eq!(bundle.nfts.len(), 5);
+        assert!(leftover.is_empty());
+
+        // If the estimates were too low, the last NFTs are moved to the next bundle.
+        let (bundle, _, leftover) =
+            collection.finish_pending(ctx, &state, &p2, pending, &constants, full_cost - 1)?;
+        assert!(bundle.cost < full_cost);
+        assert_eq!(bundle.nfts.len(), 4);
+        assert_eq!(
+            leftover.iter().map(|(index, _)| *index).collect::<Vec<_>>(),
+            [4]
+        );
+
+        // Every planned bundle stays within the maximum cost.
+        let plan = collection.max_bundle_cost(full_cost - 1).plan(
+            ctx,
+            did,
+            funding_coin,
+            &p2,
+            &constants,
+        )?;
+        assert_eq!(plan.bundles.len(), 2);
+
+        for bundle in plan.bundles {
+            assert!(bundle.cost < full_cost);
+            sim.spend_coins(bundle.coin_spends, &[sk.clone()])?;
+
+            for nft in bundle.nfts {
+                assert!(sim.coin_state(nft.coin.coin_id()).is_some());
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_bulk_mint_errors() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let constants = sim.config().constants.clone();
+        let ctx = &mut SpendContext::new();
+
+        let (sk, pk, puzzle_hash, coin) = sim.new_p2(1)?;
+        let funding_coin = sim.new_coin(puzzle_hash, 10);
+        let p2 = StandardLayer::new(pk);
+
+        let (create_did, did) = Launcher::new(coin.coin_id(), 1).create_simple_did(ctx, &p2)?;
+        p2.spend(ctx, coin, create_did)?;
+        sim.spend_coins(ctx.take(), &[sk])?;
+
+        let collection = BulkMint::new(puzzle_hash, 300)
+            .mint_all(vec![NftMetadata::default(); 5], puzzle_hash)
+            .max_bundle_cost(300_000_000);
+
+        assert!(matches!(
+            collection.clone().max_bundle_cost(100_000_000).plan(
+                ctx,
+                did,
+                funding_coin,
+                &p2,
+                &constants
+            ),
+            Err(DriverError::MintExceedsMaxCost)
+        ));
+
+        assert!(matches!(
+            collection
+                .clone()
+                .fee_per_bundle(3)
+                .plan(ctx, did, funding_coin, &p2, &constants),
+            Err(DriverError::InsufficientMintFunds)
+        ));
+
+        let plan = collection.plan(ctx, did, funding_coin, &p2, &constants)?;
+        assert!(plan.bundles.len() > 1);
+        assert!(ctx.take().is_empty());
+
+        Ok(())
+    }
+}
@@ -1,3 +1,6 @@
+use chia_protocol::Bytes32;
+use chia_puzzles::nft::NftMetadata;
+
 use crate::{DriverError, Spend, SpendContext};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -9,11 +12,132 @@ pub enum MetadataUpdate {
 
 impl MetadataUpdate {
     pub fn spend(&self, ctx: &mut SpendContext) -> Result<Spend, DriverError> {
-        let solution = ctx.alloc(&match self {
-            Self::NewDataUri(uri) => ("u", uri),
-            Self::NewMetadataUri(uri) => ("mu", uri),
-            Self::NewLicenseUri(uri) => ("lu", uri),
-        })?;
+        let solution = ctx.alloc(&(self.key(), self.uri()))?;
         Ok(Spend::new(ctx.nft_metadata_updater()?, solution))
     }
+
+    /// The metadata key of the list of URIs that this update prepends to.
+    pub fn key(&self) -> &'static str {
+        match self {
+            Self::NewDataUri(..) => "u",
+            Self::NewMetadataUri(..) => "mu",
+            Self::NewLicenseUri(..) => "lu",
+        }
+    }
+
+    pub fn uri(&self) -> &str {
+        match self {
+            Self::NewDataUri(uri) | Self::NewMetadataUri(uri) | Self::NewLicenseUri(uri) => uri,
+        }
+    }
+
+    /// Checks that the metadata has a content hash for the URI being added,
+    /// so that the content it points to can be verified.
+    pub fn validate(&self, metadata: &NftMetadata) -> Result<(), DriverError> {
+        let content_hash = match self {
+            Self::NewDataUri(..) => metadata.data_hash,
+            Self::NewMetadataUri(..) => metadata.metadata_hash,
+            Self::NewLicenseUri(..) => metadata.license_hash,
+        };
+
+        if content_hash.is_none() {
+            return Err(DriverError::MissingContentHash(self.key()));
+        }
+
+        Ok(())
+    }
+}
+
+/// Multiple changes to NFT metadata, which can be applied in a single spend with the
+/// [`BatchMetadataUpdater`](crate::BatchMetadataUpdater).
+///
+/// The content hashes are set before the URIs are added. Each URI is prepended to its list in order,
+/// so the last URI of each kind ends up first.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct MetadataUpdates {
+    pub data_hash: Option<Bytes32>,
+    pub metadata_hash: Option<Bytes32>,
+    pub license_hash: Option<Bytes32>,
+    pub uris: Vec<MetadataUpdate>,
+}
+
+impl MetadataUpdates {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Checks that the updates don't change a content hash that has already been set,
+    /// and that every URI being added has a content hash, either already or from these updates.
+    ///
+    /// The [`BatchMetadataUpdater`](crate::BatchMetadataUpdater) puzzle enforces this as well,
+    /// so checking it before creating the spend avoids a spend that would fail.
+    pub fn validate(&self, metadata: &NftMetadata) -> Result<(), DriverError> {
+        let data_hash = merge_hash("h", metadata.data_hash, self.data_hash)?;
+        let metadata_hash = merge_hash("mh", metadata.metadata_hash, self.metadata_hash)?;
+        let license_hash = merge_hash("lh", metadata.license_hash, self.license_hash)?;
+
+        let metadata = NftMetadata {
+            data_hash,
+            metadata_hash,
+            license_hash,
+            ..metadata.clone()
+        };
+
+        for update in &self.uris {
+            update.validate(&metadata)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn merge_hash(
+    key: &'static str,
+    current: Option<Bytes32>,
+    new: Option<Bytes32>,
+) -> Result<Option<Bytes32>, DriverError> {
+    match (current, new) {
+        (Some(current), Some(new)) if current != new => {
+            Err(DriverError::ContentHashAlreadySet(key))
+        }
+        _ => Ok(new.or(current)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_metadata_updates() {
+        let metadata = NftMetadata {
+            data_hash: Some(Bytes32::new([1; 32])),
+            ..Default::default()
+        };
+
+        let data_uri = MetadataUpdate::NewDataUri("example.com".to_string());
+        let metadata_uri = MetadataUpdate::NewMetadataUri("metadata.com".to_string());
+
+        assert!(data_uri.validate(&metadata).is_ok());
+        assert!(matches!(
+            metadata_uri.validate(&metadata),
+            Err(DriverError::MissingContentHash("mu"))
+        ));
+
+        let updates = MetadataUpdates {
+            metadata_hash: Some(Bytes32::new([2; 32])),
+            uris: vec![data_uri, metadata_uri],
+            ..Default::default()
+        };
+        assert!(updates.validate(&metadata).is_ok());
+
+        let updates = MetadataUpdates {
+            data_hash: Some(Bytes32::new([3; 32])),
+            ..Default::default()
+        };
+        assert!(matches!(
+            updates.validate(&metadata),
+            Err(DriverError::ContentHashAlreadySet("h"))
+        ));
+    }
 }
@@ -0,0 +1,589 @@
+use chia_protocol::Bytes32;
+use chia_puzzles::nft::NFT_METADATA_UPDATER_PUZZLE_HASH;
+use clvm_traits::{FromClvm, ToClvm};
+use clvm_utils::TreeHash;
+use clvmr::{Allocator, NodePtr};
+use hex_literal::hex;
+
+use crate::{DriverError, Puzzle, Spend, SpendContext};
+
+use super::{MetadataUpdate, MetadataUpdates};
+
+/// A metadata updater is the puzzle revealed in the [`UpdateNftMetadata`](chia_sdk_types::UpdateNftMetadata)
+/// condition, which is run by the [`NftStateLayer`](crate::NftStateLayer) to calculate the new metadata.
+///
+/// Its puzzle hash is curried into the NFT state layer when the NFT is minted, so an NFT can only be updated
+/// with the metadata updater it was minted with (unless the updater outputs a new puzzle hash).
+pub trait MetadataUpdater {
+    /// Most metadata updaters have a specific solution type, which describes the update.
+    type Solution;
+
+    /// Parses the metadata updater from a puzzle, if it matches.
+    fn parse_puzzle(allocator: &Allocator, puzzle: Puzzle) -> Result<Option<Self>, DriverError>
+    where
+        Self: Sized;
+
+    /// Parses the solution of the metadata updater.
+    fn parse_solution(
+        allocator: &Allocator,
+        solution: NodePtr,
+    ) -> Result<Self::Solution, DriverError>;
+
+    /// Constructs the puzzle of the metadata updater.
+    fn construct_puzzle(&self, ctx: &mut SpendContext) -> Result<NodePtr, DriverError>;
+
+    /// Constructs the solution of the metadata updater, from the update.
+    fn construct_solution(
+        &self,
+        ctx: &mut SpendContext,
+        solution: Self::Solution,
+    ) -> Result<NodePtr, DriverError>;
+
+    /// Creates a spend of the metadata updater, which can be revealed in the [`UpdateNftMetadata`](chia_sdk_types::UpdateNftMetadata) condition.
+    fn spend(
+        &self,
+        ctx: &mut SpendContext,
+        solution: Self::Solution,
+    ) -> Result<Spend, DriverError> {
+        let puzzle = self.construct_puzzle(ctx)?;
+        let solution = self.construct_solution(ctx, solution)?;
+        Ok(Spend::new(puzzle, solution))
+    }
+}
+
+/// The default metadata updater, which can prepend a single data, metadata, or license URI per spend.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DefaultMetadataUpdater;
+
+impl MetadataUpdater for DefaultMetadataUpdater {
+    type Solution = MetadataUpdate;
+
+    fn parse_puzzle(_allocator: &Allocator, puzzle: Puzzle) -> Result<Option<Self>, DriverError> {
+        if puzzle.curried_puzzle_hash() != NFT_METADATA_UPDATER_PUZZLE_HASH {
+            return Ok(None);
+        }
+
+        Ok(Some(Self))
+    }
+
+    fn parse_solution(
+        allocator: &Allocator,
+        solution: NodePtr,
+    ) -> Result<Self::Solution, DriverError> {
+        let (key, uri) = <(String, String)>::from_clvm(allocator, solution)?;
+        parse_uri_update(key, uri)
+    }
+
+    fn construct_puzzle(&self, ctx: &mut SpendContext) -> Result<NodePtr, DriverError> {
+        ctx.nft_metadata_updater()
+    }
+
+    fn construct_solution(
+        &self,
+        ctx: &mut SpendContext,
+        solution: Self::Solution,
+    ) -> Result<NodePtr, DriverError> {
+        ctx.alloc(&(solution.key(), solution.uri()))
+    }
+}
+
+/// A metadata updater which applies [`MetadataUpdates`] in a single spend.
+/// Unlike the [`DefaultMetadataUpdater`], it can also set content hashes, and add keys which aren't in the metadata yet.
+///
+/// The solution is a list of `(key . value)` pairs. The values of URI keys (`u`, `mu`, and `lu`) are prepended to the
+/// existing list, and the values of other keys replace the existing value. Keys which don't exist are inserted in the same
+/// order that [`NftMetadata`](chia_puzzles::nft::NftMetadata) is serialized in, so the metadata can be parsed and
+/// serialized again without changing its tree hash.
+///
+/// The puzzle only allows the URI and content hash keys to be updated, and enforces the same rules as [`MetadataUpdates::validate`],
+/// so content hashes must be 32 bytes and can't be changed once they are set, and URIs can't be added without a content hash.
+/// To use it, mint the NFT with [`NftMint::with_custom_metadata_updater`](crate::NftMint::with_custom_metadata_updater).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BatchMetadataUpdater;
+
+impl MetadataUpdater for BatchMetadataUpdater {
+    type Solution = MetadataUpdates;
+
+    fn parse_puzzle(_allocator: &Allocator, puzzle: Puzzle) -> Result<Option<Self>, DriverError> {
+        if puzzle.curried_puzzle_hash() != NFT_BATCH_METADATA_UPDATER_PUZZLE_HASH {
+            return Ok(None);
+        }
+
+        Ok(Some(Self))
+    }
+
+    fn parse_solution(
+        allocator: &Allocator,
+        solution: NodePtr,
+    ) -> Result<Self::Solution, DriverError> {
+        let pairs = Vec::<(String, NodePtr)>::from_clvm(allocator, solution)?;
+        let mut updates = MetadataUpdates::new();
+
+        for (key, value) in pairs {
+            match key.as_str() {
+                "h" => updates.data_hash = Some(Bytes32::from_clvm(allocator, value)?),
+                "mh" => updates.metadata_hash = Some(Bytes32::from_clvm(allocator, value)?),
+                "lh" => updates.license_hash = Some(Bytes32::from_clvm(allocator, value)?),
+                _ => {
+                    let uri = String::from_clvm(allocator, value)?;
+                    updates.uris.push(parse_uri_update(key, uri)?);
+                }
+            }
+        }
+
+        Ok(updates)
+    }
+
+    fn construct_puzzle(&self, ctx: &mut SpendContext) -> Result<NodePtr, DriverError> {
+        ctx.nft_batch_metadata_updater()
+    }
+
+    fn construct_solution(
+        &self,
+        ctx: &mut SpendContext,
+        solution: Self::Solution,
+    ) -> Result<NodePtr, DriverError> {
+        let mut pairs = Vec::new();
+
+        for (key, hash) in [
+            ("h", solution.data_hash),
+            ("mh", solution.metadata_hash),
+            ("lh", solution.license_hash),
+        ] {
+            if let Some(hash) = hash {
+                pairs.push((key, hash).to_clvm(&mut ctx.allocator)?);
+            }
+        }
+
+        for update in &solution.uris {
+            pairs.push((update.key(), update.uri()).to_clvm(&mut ctx.allocator)?);
+        }
+
+        ctx.alloc(&pairs)
+    }
+}
+
+fn parse_uri_update(key: String, uri: String) -> Result<MetadataUpdate, DriverError> {
+    Ok(match key.as_str() {
+        "u" => MetadataUpdate::NewDataUri(uri),
+        "mu" => MetadataUpdate::NewMetadataUri(uri),
+        "lu" => MetadataUpdate::NewLicenseUri(uri),
+        _ => return Err(DriverError::UnknownMetadataKey(key)),
+    })
+}
+
+/// The serialized [`BatchMetadataUpdater`] puzzle, which is assembled from `puzzles/nft_batch_metadata_updater.clvm`.
+pub const NFT_BATCH_METADATA_UPDATER_PUZZLE: [u8; 1201] = hex!(
+    "
+    ff02ffff01ff02ffff01ff02ffff03ffff02ff8202feffff04ff02ffff04ff05
+    ffff04ff17ff8080808080ffff01ff04ffff04ff05ffff04ff0bff808080ffff
+    01ff808080ffff01ff088080ff0180ffff04ff02ffff04ffff02ff8200beffff
+    04ff02ffff04ff05ffff04ff17ff8080808080ffff04ff0bffff04ff17ff8080
+    80808080ffff04ffff01ffff03ffff09ff05ffff017580ffff0101ffff03ffff
+    09ff05ffff016880ffff0102ffff03ffff09ff05ffff01826d7580ffff0103ff
+    ff03ffff09ff05ffff01826d6880ffff0104ffff03ffff09ff05ffff01826c75
+    80ffff0105ffff03ffff09ff05ffff01826c6880ffff0106ffff03ffff09ff05
+    ffff0182736e80ffff0107ffff03ffff09ff05ffff0182737480ffff0108ffff
+    01098080808080808080ffff21ffff09ff05ffff017580ffff09ff05ffff0182
+    6d7580ffff09ff05ffff01826c758080ffff03ffff09ff05ffff017580ffff01
+    68ffff03ffff09ff05ffff01826d7580ffff01826d68ffff01826c688080ffff
+    02ffff03ffff02ff0affff04ff02ffff04ff05ff80808080ffff01ff04ff0bff
+    1780ffff01ff02ffff03ffff22ffff09ffff0dff0b80ffff012080ffff21ffff
+    20ff1780ffff09ff17ff0b808080ffff010bffff01ff088080ff018080ff0180
+    ffff02ffff03ff05ffff01ff02ffff03ffff09ffff05ffff05ff058080ffff05
+    ff0b8080ffff01ff04ffff04ffff05ff0b80ffff02ff2effff04ff02ffff04ff
+    ff05ff0b80ffff04ffff06ff0b80ffff04ffff06ffff05ff058080ff80808080
+    808080ffff06ff058080ffff01ff02ffff03ffff15ffff02ff04ffff04ff02ff
+    ff04ffff05ffff05ff058080ff80808080ffff02ff04ffff04ff02ffff04ffff
+    05ff0b80ff8080808080ffff01ff04ffff04ffff05ff0b80ffff02ff2effff04
+    ff02ffff04ffff05ff0b80ffff04ffff06ff0b80ffff01ff80808080808080ff
+    0580ffff01ff04ffff05ff0580ffff02ff5effff04ff02ffff04ffff06ff0580
+    ffff04ff0bff80808080808080ff018080ff0180ffff01ff04ffff04ffff05ff
+    0b80ffff02ff2effff04ff02ffff04ffff05ff0b80ffff04ffff06ff0b80ffff
+    01ff80808080808080ff808080ff0180ffff02ffff03ff0bffff01ff02ffff03
+    ffff15ffff0107ffff02ff04ffff04ff02ffff04ffff05ffff05ff0b8080ff80
+    80808080ffff01ff02ff8200beffff04ff02ffff04ffff02ff5effff04ff02ff
+    ff04ff05ffff04ffff05ff0b80ff8080808080ffff04ffff06ff0b80ff808080
+    8080ffff01ff088080ff0180ffff010580ff0180ffff02ffff03ff05ffff01ff
+    02ffff03ffff09ffff05ffff05ff058080ff0b80ffff01ff06ffff05ff058080
+    ffff01ff02ff82017effff04ff02ffff04ffff06ff0580ffff04ff0bff808080
+    808080ff0180ff8080ff0180ffff02ffff03ff0bffff01ff02ffff03ffff21ff
+    ff20ffff02ff0affff04ff02ffff04ffff05ffff05ff0b8080ff8080808080ff
+    ff02ff82017effff04ff02ffff04ff05ffff04ffff02ff16ffff04ff02ffff04
+    ffff05ffff05ff0b8080ff80808080ff808080808080ffff01ff02ff8202feff
+    ff04ff02ffff04ff05ffff04ffff06ff0b80ff8080808080ffff01ff088080ff
+    0180ffff01ff010180ff018080ff018080
+    "
+);
+
+pub const NFT_BATCH_METADATA_UPDATER_PUZZLE_HASH: TreeHash = TreeHash::new(hex!(
+    "
+    2364d8b38b1d0efd373c4de1b9a7b7c6325dd1e545f5b17a1f7d823ad8d5a741
+    "
+));
+
+#[cfg(test)]
+mod tests {
+    use chia_protocol::Bytes;
+    use chia_puzzles::nft::NftMetadata;
+    use chia_sdk_test::Simulator;
+    use chia_sdk_types::{Conditions, UpdateNftMetadata};
+    use clvmr::serde::node_to_bytes;
+
+    use crate::{
+        assert_puzzle_hash, DidOwner, IntermediateLauncher, Launcher, Nft, NftMint, NftStateLayer,
+        StandardLayer,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_puzzle_hash() -> anyhow::Result<()> {
+        assert_puzzle_hash!(NFT_BATCH_METADATA_UPDATER_PUZZLE => NFT_BATCH_METADATA_UPDATER_PUZZLE_HASH);
+        Ok(())
+    }
+
+    #[test]
+    fn test_puzzle_source() -> anyhow::Result<()> {
+        let puzzle = include_str!("../../../puzzles/nft_batch_metadata_updater.clvm.hex");
+        assert_eq!(
+            hex::decode(puzzle.trim())?,
+            NFT_BATCH_METADATA_UPDATER_PUZZLE
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_puzzle_assembly() -> anyhow::Result<()> {
+        let mut allocator = Allocator::new();
+        let source = include_str!("../../../puzzles/nft_batch_metadata_updater.clvm");
+        let puzzle = assemble(&mut allocator, source)?;
+        assert_eq!(
+            node_to_bytes(&allocator, puzzle)?,
+            NFT_BATCH_METADATA_UPDATER_PUZZLE
+        );
+        Ok(())
+    }
+
+    /// Assembles the subset of CLVM assembly that the puzzle source is written in, which is lists,
+    /// cons pairs, operator names, strings, and non-negative integers.
+    fn assemble(allocator: &mut Allocator, source: &str) -> anyhow::Result<NodePtr> {
+        let mut tokens = Vec::new();
+        let mut chars = source.char_indices().peekable();
+
+        while let Some((start, char)) = chars.next() {
+            match char {
+                ';' => while chars.next_if(|(_, char)| *char != '\n').is_some() {},
+                '(' | ')' => tokens.push(&source[start..=start]),
+                '"' => {
+                    let (end, _) = chars
+                        .find(|(_, char)| *char == '"')
+                        .ok_or_else(|| anyhow::anyhow!("unterminated string"))?;
+                    tokens.push(&source[start..=end]);
+                }
+                char if char.is_whitespace() => {}
+                _ => {
+                    let mut end = start + char.len_utf8();
+                    while let Some((index, char)) =
+                        chars.next_if(|(_, char)| !char.is_whitespace() && !"();".contains(*char))
+                    {
+                        end = index + char.len_utf8();
+                    }
+                    tokens.push(&source[start..end]);
+                }
+            }
+        }
+
+        let mut tokens = tokens.into_iter();
+        let first = tokens.next();
+        let program = parse_token(allocator, first, &mut tokens)?;
+        anyhow::ensure!(tokens.next().is_none(), "unexpected trailing tokens");
+        Ok(program)
+    }
+
+    fn parse_token<'a>(
+        allocator: &mut Allocator,
+        token: Option<&'a str>,
+        tokens: &mut impl Iterator<Item = &'a str>,
+    ) -> anyhow::Result<NodePtr> {
+        let token = token.ok_or_else(|| anyhow::anyhow!("unexpected end of source"))?;
+
+        if token == "(" {
+            let mut items = Vec::new();
+            let mut rest = NodePtr::NIL;
+
+            loop {
+                match tokens.next() {
+                    Some(")") => break,
+                    Some(".") => {
+                        let next = tokens.next();
+                        rest = parse_token(allocator, next, tokens)?;
+                        anyhow::ensure!(tokens.next() == Some(")"), "expected `)` after cons tail");
+                        break;
+                    }
+                    token => items.push(parse_token(allocator, token, tokens)?),
+                }
+            }
+
+            for item in items.into_iter().rev() {
+                rest = allocator.new_pair(item, rest)?;
+            }
+
+            return Ok(rest);
+        }
+
+        if let Some(string) = token
+            .strip_prefix('"')
+            .and_then(|token| token.strip_suffix('"'))
+        {
+            return Ok(allocator.new_atom(string.as_bytes())?);
+        }
+
+        let value = match token {
+            "q" => 1,
+            "a" => 2,
+            "i" => 3,
+            "c" => 4,
+            "f" => 5,
+            "r" => 6,
+            "x" => 8,
+            "=" => 9,
+            "strlen" => 13,
+            ">" => 21,
+            "not" => 32,
+            "any" => 33,
+            "all" => 34,
+            _ => token.parse()?,
+        };
+
+        Ok(allocator.new_small_number(value)?)
+    }
+
+    fn run_batch_update(
+        ctx: &mut SpendContext,
+        metadata: &NftMetadata,
+        updates: &[(&str, NodePtr)],
+    ) -> Result<NftMetadata, DriverError> {
+        let puzzle = ctx.nft_batch_metadata_updater()?;
+        let solution = ctx.alloc(&updates)?;
+
+        NftStateLayer::<NftMetadata, NodePtr>::get_next_metadata(
+            &mut ctx.allocator,
+            metadata,
+            NFT_BATCH_METADATA_UPDATER_PUZZLE_HASH.into(),
+            UpdateNftMetadata::new(puzzle, solution),
+        )
+    }
+
+    #[test]
+    fn test_batch_metadata_update_rules() -> anyhow::Result<()> {
+        let ctx = &mut SpendContext::new();
+
+        let metadata = NftMetadata {
+            data_uris: vec!["example.com".to_string()],
+            data_hash: Some(Bytes32::new([1; 32])),
+            ..Default::default()
+        };
+
+        let same_hash = ctx.alloc(&Bytes32::new([1; 32]))?;
+        let other_hash = ctx.alloc(&Bytes32::new([2; 32]))?;
+        let uri = ctx.alloc(&"another.com")?;
+
+        // Setting a hash to the value it already has is allowed, but changing it isn't.
+        assert_eq!(
+            run_batch_update(ctx, &metadata, &[("h", same_hash)])?,
+            metadata
+        );
+        assert!(run_batch_update(ctx, &metadata, &[("h", other_hash)]).is_err());
+
+        // Hashes must be 32 bytes, even if nothing is set yet.
+        let short_hash = ctx.alloc(&Bytes::new(vec![3; 31]))?;
+        let long_hash = ctx.alloc(&Bytes::new(vec![3; 33]))?;
+        assert!(run_batch_update(ctx, &NftMetadata::default(), &[("mh", short_hash)]).is_err());
+        assert!(run_batch_update(ctx, &NftMetadata::default(), &[("lh", long_hash)]).is_err());
+        assert_eq!(
+            run_batch_update(ctx, &NftMetadata::default(), &[("mh", other_hash)])?,
+            NftMetadata {
+                metadata_hash: Some(Bytes32::new([2; 32])),
+                ..Default::default()
+            }
+        );
+
+        // A URI needs its content hash, which can also be set after it in the same update.
+        assert!(run_batch_update(ctx, &metadata, &[("mu", uri)]).is_err());
+        assert_eq!(
+            run_batch_update(ctx, &metadata, &[("mu", uri), ("mh", other_hash)])?,
+            NftMetadata {
+                metadata_uris: vec!["another.com".to_string()],
+                metadata_hash: Some(Bytes32::new([2; 32])),
+                ..metadata.clone()
+            }
+        );
+
+        // Other keys can't be updated.
+        assert!(run_batch_update(ctx, &metadata, &[("sn", same_hash)]).is_err());
+        assert!(run_batch_update(ctx, &metadata, &[("x", uri)]).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_batch_metadata_update() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let ctx = &mut SpendContext::new();
+
+        let (sk, pk, puzzle_hash, coin) = sim.new_p2(2)?;
+        let p2 = StandardLayer::new(pk);
+
+        let (create_did, did) = Launcher::new(coin.coin_id(), 1).create_simple_did(ctx, &p2)?;
+        p2.spend(ctx, coin, create_did)?;
+
+        let metadata = NftMetadata {
+            data_uris: vec!["example.com".to_string()],
+            data_hash: Some(Bytes32::new([1; 32])),
+            ..Default::default()
+        };
+
+        let mint = NftMint::new(
+            metadata.clone(),
+            puzzle_hash,
+            300,
+            Some(DidOwner::from_did_info(&did.info)),
+        )
+        .with_custom_metadata_updater(NFT_BATCH_METADATA_UPDATER_PUZZLE_HASH.into());
+
+        let (mint_nft, nft) = IntermediateLauncher::new(did.coin.coin_id(), 0, 1)
+            .create(ctx)?
+            .mint_nft(ctx, mint)?;
+        let _did = did.update(ctx, &p2, mint_nft)?;
+
+        let updates = MetadataUpdates {
+            metadata_hash: Some(Bytes32::new([2; 32])),
+            uris: vec![
+                MetadataUpdate::NewDataUri("another.com".to_string()),
+                MetadataUpdate::NewMetadataUri("metadata.com".to_string()),
+                MetadataUpdate::NewDataUri("third.com".to_string()),
+            ],
+            ..Default::default()
+        };
+        updates.validate(&metadata)?;
+
+        let metadata_update = BatchMetadataUpdater.spend(ctx, updates)?;
+        let parent_nft = nft.clone();
+        let nft: Nft<NftMetadata> =
+            nft.transfer_with_metadata(ctx, &p2, puzzle_hash, metadata_update, Conditions::new())?;
+
+        assert_eq!(
+            nft.info.metadata,
+            NftMetadata {
+                data_uris: vec![
+                    "third.com".to_string(),
+                    "another.com".to_string(),
+                    "example.com".to_string()
+                ],
+                data_hash: Some(Bytes32::new([1; 32])),
+                metadata_uris: vec!["metadata.com".to_string()],
+                metadata_hash: Some(Bytes32::new([2; 32])),
+                ..Default::default()
+            }
+        );
+
+        let child_nft = nft.clone();
+        let _nft = nft.transfer(ctx, &p2, puzzle_hash, Conditions::new())?;
+
+        sim.spend_coins(ctx.take(), &[sk])?;
+
+        let parent_puzzle = sim
+            .puzzle_reveal(parent_nft.coin.coin_id())
+            .expect("missing puzzle")
+            .to_clvm(&mut ctx.allocator)?;
+        let parent_solution = sim
+            .solution(parent_nft.coin.coin_id())
+            .expect("missing solution")
+            .to_clvm(&mut ctx.allocator)?;
+
+        let parent_puzzle = Puzzle::parse(&ctx.allocator, parent_puzzle);
+        let new_child_nft = Nft::<NftMetadata>::parse_child(
+            &mut ctx.allocator,
+            parent_nft.coin,
+            parent_puzzle,
+            parent_solution,
+        )?
+        .expect("child is not an NFT");
+
+        assert_eq!(new_child_nft, child_nft);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_metadata_update() -> anyhow::Result<()> {
+        let ctx = &mut SpendContext::new();
+
+        let update = MetadataUpdate::NewLicenseUri("license.com".to_string());
+        let condition = NftStateLayer::<NftMetadata, NodePtr>::update_metadata_condition(
+            ctx,
+            &DefaultMetadataUpdater,
+            update.clone(),
+        )?;
+
+        assert_eq!(
+            NftStateLayer::<NftMetadata, NodePtr>::parse_metadata_update::<DefaultMetadataUpdater>(
+                &ctx.allocator,
+                condition
+            )?,
+            Some((DefaultMetadataUpdater, update))
+        );
+        assert_eq!(
+            NftStateLayer::<NftMetadata, NodePtr>::parse_metadata_update::<BatchMetadataUpdater>(
+                &ctx.allocator,
+                condition
+            )?,
+            None
+        );
+
+        let updates = MetadataUpdates {
+            data_hash: Some(Bytes32::new([1; 32])),
+            license_hash: Some(Bytes32::new([2; 32])),
+            uris: vec![
+                MetadataUpdate::NewDataUri("example.com".to_string()),
+                MetadataUpdate::NewLicenseUri("license.com".to_string()),
+            ],
+            ..Default::default()
+        };
+        let condition = NftStateLayer::<NftMetadata, NodePtr>::update_metadata_condition(
+            ctx,
+            &BatchMetadataUpdater,
+            updates.clone(),
+        )?;
+
+        assert_eq!(
+            NftStateLayer::<NftMetadata, NodePtr>::parse_metadata_update::<BatchMetadataUpdater>(
+                &ctx.allocator,
+                condition
+            )?,
+            Some((BatchMetadataUpdater, updates.clone()))
+        );
+
+        // Running the updater should result in the same metadata as applying the updates.
+        let metadata = NftStateLayer::<NftMetadata, NodePtr>::get_next_metadata(
+            &mut ctx.allocator,
+            &NftMetadata::default(),
+            NFT_BATCH_METADATA_UPDATER_PUZZLE_HASH.into(),
+            condition,
+        )?;
+
+        assert_eq!(
+            metadata,
+            NftMetadata {
+                data_uris: vec!["example.com".to_string()],
+                data_hash: updates.data_hash,
+                license_uris: vec!["license.com".to_string()],
+                license_hash: updates.license_hash,
+                ..Default::default()
+            }
+        );
+
+        Ok(())
+    }
+}
@@ -90,6 +90,15 @@ impl<M> NftInfo<M> {
         }
     }
 
+    /// The royalty transfer layer, which enforces the royalties of this NFT when it's traded.
+    pub fn royalty_transfer_layer(&self) -> RoyaltyTransferLayer {
+        RoyaltyTransferLayer::new(
+            self.launcher_id,
+            self.royalty_puzzle_hash,
+            self.royalty_ten_thousandths,
+        )
+    }
+
     #[must_use]
     pub fn into_layers<I>(self, p2_puzzle: I) -> StandardNftLayers<M, I> {
         SingletonLayer::new(
@@ -0,0 +1,491 @@
+use chia_protocol::Bytes32;
+use chia_puzzles::{
+    cat::CatArgs,
+    offer::{NotarizedPayment, Payment, SETTLEMENT_PAYMENTS_PUZZLE_HASH},
+};
+use chia_sdk_types::{announcement_id, run_puzzle, Condition, TradePrice};
+use clvm_traits::{FromClvm, ToClvm};
+use clvm_utils::tree_hash;
+use clvmr::{Allocator, NodePtr};
+
+use crate::{
+    DriverError, HashedPtr, Layer, NftOwnershipLayer, NftStateLayer, Puzzle, RoyaltyTransferLayer,
+    SingletonLayer, StandardNftLayers,
+};
+
+use super::{calculate_nft_royalty, calculate_nft_trace_price};
+
+/// An amount of XCH or a CAT that is paid in exchange for one or more NFTs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TradeAmount {
+    /// The asset id of the CAT, or [`None`] for XCH.
+    pub asset_id: Option<Bytes32>,
+    pub amount: u64,
+}
+
+impl TradeAmount {
+    pub fn xch(amount: u64) -> Self {
+        Self {
+            asset_id: None,
+            amount,
+        }
+    }
+
+    pub fn cat(asset_id: Bytes32, amount: u64) -> Self {
+        Self {
+            asset_id: Some(asset_id),
+            amount,
+        }
+    }
+
+    /// The puzzle hash of the settlement payments puzzle for this asset, which pays out the amount.
+    pub fn settlement_puzzle_hash(&self) -> Bytes32 {
+        match self.asset_id {
+            Some(asset_id) => {
+                CatArgs::curry_tree_hash(asset_id, SETTLEMENT_PAYMENTS_PUZZLE_HASH).into()
+            }
+            None => SETTLEMENT_PAYMENTS_PUZZLE_HASH.into(),
+        }
+    }
+}
+
+/// Calculates the trade prices that each of the NFTs in a trade should be transferred with,
+/// by splitting each amount evenly between the NFTs.
+pub fn calculate_trade_prices(
+    amounts: &[TradeAmount],
+    nft_count: usize,
+) -> Result<Vec<TradePrice>, DriverError> {
+    if nft_count == 0 {
+        return Ok(Vec::new());
+    }
+
+    amounts
+        .iter()
+        .map(|amount| {
+            Ok(TradePrice {
+                amount: calculate_nft_trace_price(amount.amount, nft_count)
+                    .ok_or(DriverError::RoyaltyOverflow)?,
+                puzzle_hash: amount.settlement_puzzle_hash(),
+            })
+        })
+        .collect()
+}
+
+/// A royalty that must be paid through the settlement payments puzzle when an NFT is transferred with a trade price.
+///
+/// The [`RoyaltyTransferLayer`] asserts a puzzle announcement from the settlement puzzle for each trade price,
+/// which is only created if the [`NotarizedPayment`] for the royalty is included in the settlement spend.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RoyaltyPayment {
+    /// The launcher id of the NFT, which is used as the nonce of the payment.
+    pub launcher_id: Bytes32,
+    /// The settlement puzzle hash of the asset the royalty is paid in.
+    pub settlement_puzzle_hash: Bytes32,
+    pub royalty_puzzle_hash: Bytes32,
+    pub amount: u64,
+}
+
+impl RoyaltyPayment {
+    /// Calculates the royalty that the NFT with the given royalty layer requires for a trade price.
+    pub fn new(
+        royalty: &RoyaltyTransferLayer,
+        trade_price: &TradePrice,
+    ) -> Result<Self, DriverError> {
+        Ok(Self {
+            launcher_id: royalty.launcher_id,
+            settlement_puzzle_hash: trade_price.puzzle_hash,
+            royalty_puzzle_hash: royalty.royalty_puzzle_hash,
+            amount: calculate_nft_royalty(trade_price.amount, royalty.royalty_ten_thousandths)
+                .ok_or(DriverError::RoyaltyOverflow)?,
+        })
+    }
+
+    /// The payment to include in the settlement spend, which is hinted to the royalty puzzle hash.
+    pub fn notarized_payment(&self) -> NotarizedPayment {
+        NotarizedPayment {
+            nonce: self.launcher_id,
+            payments: vec![Payment::with_memos(
+                self.royalty_puzzle_hash,
+                self.amount,
+                vec![self.royalty_puzzle_hash.into()],
+            )],
+        }
+    }
+
+    /// The id of the puzzle announcement that the NFT asserts for this royalty.
+    pub fn announcement_id(&self) -> Result<Bytes32, DriverError> {
+        let mut allocator = Allocator::new();
+        let notarized_payment = self.notarized_payment().to_clvm(&mut allocator)?;
+
+        Ok(announcement_id(
+            self.settlement_puzzle_hash,
+            tree_hash(&allocator, notarized_payment),
+        ))
+    }
+
+    /// Parses the royalties required by an NFT spend, from the trade prices it was transferred with.
+    /// Returns [`None`] if the puzzle isn't an NFT, and an empty list if it's not being traded.
+    pub fn parse_nft_spend(
+        allocator: &mut Allocator,
+        puzzle: Puzzle,
+        solution: NodePtr,
+    ) -> Result<Option<Vec<Self>>, DriverError> {
+        let Some(layers) = StandardNftLayers::<HashedPtr, Puzzle>::parse_puzzle(allocator, puzzle)?
+        else {
+            return Ok(None);
+        };
+
+        let solution = SingletonLayer::<
+            NftStateLayer<HashedPtr, NftOwnershipLayer<RoyaltyTransferLayer, Puzzle>>,
+        >::parse_solution(allocator, solution)?;
+
+        let royalty = layers.inner_puzzle.inner_puzzle.transfer_layer;
+        let inner_puzzle = layers.inner_puzzle.inner_puzzle.inner_puzzle;
+        let inner_solution = solution.inner_solution.inner_solution.inner_solution;
+
+        let output = run_puzzle(allocator, inner_puzzle.ptr(), inner_solution)?;
+        let conditions = Vec::<Condition>::from_clvm(allocator, output)?;
+
+        let mut payments = Vec::new();
+
+        for condition in conditions {
+            if let Condition::TransferNft(transfer) = condition {
+                payments = calculate_royalty_payments(&[royalty], &transfer.trade_prices)?;
+            }
+        }
+
+        Ok(Some(payments))
+    }
+}
+
+/// Calculates the royalties for each NFT that is transferred with the given trade prices.
+pub fn calculate_royalty_payments(
+    royalties: &[RoyaltyTransferLayer],
+    trade_prices: &[TradePrice],
+) -> Result<Vec<RoyaltyPayment>, DriverError> {
+    let mut payments = Vec::new();
+
+    for royalty in royalties {
+        for trade_price in trade_prices {
+            payments.push(RoyaltyPayment::new(royalty, trade_price)?);
+        }
+    }
+
+    Ok(payments)
+}
+
+#[cfg(test)]
+mod tests {
+    use chia_consensus::gen::validation_error::ErrorCode;
+    use chia_protocol::Coin;
+    use chia_puzzles::{nft::NftMetadata, offer::SettlementPaymentsSolution};
+    use chia_sdk_test::{Simulator, SimulatorError};
+    use chia_sdk_types::Conditions;
+
+    use crate::{
+        Cat, CatSpend, Launcher, NftMint, SettlementLayer, SpendContext, SpendWithConditions,
+        StandardLayer,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_multi_asset_trade_prices() -> anyhow::Result<()> {
+        let asset_id = Bytes32::new([1; 32]);
+        let amounts = [TradeAmount::xch(1001), TradeAmount::cat(asset_id, 300)];
+
+        let trade_prices = calculate_trade_prices(&amounts, 2)?;
+
+        assert_eq!(
+            trade_prices,
+            [
+                TradePrice {
+                    amount: 500,
+                    puzzle_hash: SETTLEMENT_PAYMENTS_PUZZLE_HASH.into(),
+                },
+                TradePrice {
+                    amount: 150,
+                    puzzle_hash: CatArgs::curry_tree_hash(
+                        asset_id,
+                        SETTLEMENT_PAYMENTS_PUZZLE_HASH
+                    )
+                    .into(),
+                }
+            ]
+        );
+
+        let first = RoyaltyTransferLayer::new(Bytes32::new([2; 32]), Bytes32::new([3; 32]), 300);
+        let second = RoyaltyTransferLayer::new(Bytes32::new([4; 32]), Bytes32::new([5; 32]), 1000);
+
+        let amounts: Vec<u64> = calculate_royalty_payments(&[first, second], &trade_prices)?
+            .into_iter()
+            .map(|payment| payment.amount)
+            .collect();
+
+        assert_eq!(amounts, [15, 4, 50, 15]);
+        assert!(calculate_trade_prices(&[TradeAmount::xch(1000)], 0)?.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_multi_asset_royalties() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let ctx = &mut SpendContext::new();
+
+        let (maker_secret_key, maker_pk, maker_puzzle_hash, maker_coin) = sim.new_p2(1)?;
+        let other_maker_coin = sim.new_coin(maker_puzzle_hash, 1);
+        let (taker_secret_key, taker_pk, taker_puzzle_hash, taker_coin) = sim.new_p2(1_000_000)?;
+        let maker = StandardLayer::new(maker_pk);
+        let taker = StandardLayer::new(taker_pk);
+
+        let creator_puzzle_hash = Bytes32::new([1; 32]);
+        let other_creator_puzzle_hash = Bytes32::new([2; 32]);
+
+        // Mint two NFTs with different royalties.
+        let (mint_first, first_nft) = Launcher::new(maker_coin.coin_id(), 1).mint_nft(
+            ctx,
+            NftMint::new(NftMetadata::default(), maker_puzzle_hash, 300, None)
+                .with_royalty_puzzle_hash(creator_puzzle_hash),
+        )?;
+        let (mint_second, second_nft) = Launcher::new(other_maker_coin.coin_id(), 1).mint_nft(
+            ctx,
+            NftMint::new(NftMetadata::default(), maker_puzzle_hash, 1000, None)
+                .with_royalty_puzzle_hash(other_creator_puzzle_hash),
+        )?;
+        maker.spend(ctx, maker_coin, mint_first)?;
+        maker.spend(ctx, other_maker_coin, mint_second)?;
+
+        // Issue a CAT to the taker.
+        let cat_coin = sim.new_coin(taker_puzzle_hash, 10_000);
+        let (issue_cat, cat) = Cat::single_issuance_eve(
+            ctx,
+            cat_coin.coin_id(),
+            10_000,
+            Conditions::new().create_coin(
+                taker_puzzle_hash,
+                10_000,
+                vec![taker_puzzle_hash.into()],
+            ),
+        )?;
+        taker.spend(ctx, cat_coin, issue_cat)?;
+        let cat = cat.wrapped_child(taker_puzzle_hash, 10_000);
+
+        sim.spend_coins(
+            ctx.take(),
+            &[maker_secret_key.clone(), taker_secret_key.clone()],
+        )?;
+
+        // The maker locks both NFTs with the trade prices, which are split between them.
+        let amounts = [
+            TradeAmount::xch(500_001),
+            TradeAmount::cat(cat.asset_id, 3000),
+        ];
+        let trade_prices = calculate_trade_prices(&amounts, 2)?;
+
+        let first_launcher_id = first_nft.info.launcher_id;
+        let second_launcher_id = second_nft.info.launcher_id;
+
+        let royalties = [
+            first_nft.info.royalty_transfer_layer(),
+            second_nft.info.royalty_transfer_layer(),
+        ];
+        let royalty_payments = calculate_royalty_payments(&royalties, &trade_prices)?;
+
+        let first_nft =
+            first_nft.lock_settlement(ctx, &maker, trade_prices.clone(), Conditions::new())?;
+        let second_nft =
+            second_nft.lock_settlement(ctx, &maker, trade_prices, Conditions::new())?;
+
+        // The royalties can be parsed from the spends of the NFTs.
+        let coin_spends = ctx.take();
+
+        let mut parsed_payments = Vec::new();
+
+        for coin_spend in &coin_spends {
+            let puzzle = coin_spend.puzzle_reveal.to_clvm(&mut ctx.allocator)?;
+            let puzzle = Puzzle::parse(&ctx.allocator, puzzle);
+            let solution = coin_spend.solution.to_clvm(&mut ctx.allocator)?;
+
+            if let Some(payments) =
+                RoyaltyPayment::parse_nft_spend(&mut ctx.allocator, puzzle, solution)?
+            {
+                parsed_payments.extend(payments);
+            }
+        }
+
+        assert_eq!(parsed_payments, royalty_payments);
+
+        let expected: Vec<(Bytes32, u64)> = royalty_payments
+            .iter()
+            .map(|payment| (payment.royalty_puzzle_hash, payment.amount))
+            .collect();
+        assert_eq!(
+            expected,
+            [
+                (creator_puzzle_hash, 7500),
+                (creator_puzzle_hash, 45),
+                (other_creator_puzzle_hash, 25000),
+                (other_creator_puzzle_hash, 150),
+            ]
+        );
+
+        // Without the royalties, the NFTs can't be unlocked.
+        for coin_spend in coin_spends.clone() {
+            ctx.insert(coin_spend);
+        }
+
+        settle(ctx, &taker, taker_coin, cat, maker_puzzle_hash, &[])?;
+        let _ = first_nft
+            .clone()
+            .unlock_settlement(ctx, vec![nft_payment(first_launcher_id, taker_puzzle_hash)])?;
+        let _ = second_nft.clone().unlock_settlement(
+            ctx,
+            vec![nft_payment(second_launcher_id, taker_puzzle_hash)],
+        )?;
+
+        let Err(SimulatorError::Validation(error)) = sim.spend_coins(
+            ctx.take(),
+            &[maker_secret_key.clone(), taker_secret_key.clone()],
+        ) else {
+            panic!("expected a validation error");
+        };
+        assert_eq!(error.code, ErrorCode::AssertPuzzleAnnouncementFailed);
+
+        for coin_spend in coin_spends {
+            ctx.insert(coin_spend);
+        }
+
+        // The taker pays the royalties in both assets along with the maker's payments.
+        settle(
+            ctx,
+            &taker,
+            taker_coin,
+            cat,
+            maker_puzzle_hash,
+            &royalty_payments,
+        )?;
+        let first_nft = first_nft
+            .unlock_settlement(ctx, vec![nft_payment(first_launcher_id, taker_puzzle_hash)])?;
+        let second_nft = second_nft.unlock_settlement(
+            ctx,
+            vec![nft_payment(second_launcher_id, taker_puzzle_hash)],
+        )?;
+
+        sim.spend_coins(ctx.take(), &[maker_secret_key, taker_secret_key])?;
+
+        assert!(sim.coin_state(first_nft.coin.coin_id()).is_some());
+        assert!(sim.coin_state(second_nft.coin.coin_id()).is_some());
+        assert_eq!(sim.hinted_coins(creator_puzzle_hash).len(), 2);
+        assert_eq!(sim.hinted_coins(other_creator_puzzle_hash).len(), 2);
+
+        Ok(())
+    }
+
+    fn nft_payment(launcher_id: Bytes32, puzzle_hash: Bytes32) -> NotarizedPayment {
+        NotarizedPayment {
+            nonce: launcher_id,
+            payments: vec![Payment::with_memos(
+                puzzle_hash,
+                1,
+                vec![puzzle_hash.into()],
+            )],
+        }
+    }
+
+    /// Pays the maker in XCH and the CAT, along with the given royalties, through the settlement puzzle.
+    fn settle(
+        ctx: &mut SpendContext,
+        taker: &StandardLayer,
+        taker_coin: Coin,
+        cat: Cat,
+        maker_puzzle_hash: Bytes32,
+        royalty_payments: &[RoyaltyPayment],
+    ) -> anyhow::Result<()> {
+        let nonce = Bytes32::new([42; 32]);
+        let xch_settlement_puzzle_hash = TradeAmount::xch(0).settlement_puzzle_hash();
+
+        let mut xch_payments = vec![NotarizedPayment {
+            nonce,
+            payments: vec![Payment::new(maker_puzzle_hash, 500_001)],
+        }];
+        let mut cat_payments = vec![NotarizedPayment {
+            nonce,
+            payments: vec![Payment::with_memos(
+                maker_puzzle_hash,
+                3000,
+                vec![maker_puzzle_hash.into()],
+            )],
+        }];
+
+        for payment in royalty_payments {
+            if payment.settlement_puzzle_hash == xch_settlement_puzzle_hash {
+                xch_payments.push(payment.notarized_payment());
+            } else {
+                cat_payments.push(payment.notarized_payment());
+            }
+        }
+
+        let xch_total = total(&xch_payments);
+        let cat_total = total(&cat_payments);
+
+        taker.spend(
+            ctx,
+            taker_coin,
+            Conditions::new().create_coin(
+                SETTLEMENT_PAYMENTS_PUZZLE_HASH.into(),
+                xch_total,
+                Vec::new(),
+            ),
+        )?;
+
+        let settlement_coin = Coin::new(
+            taker_coin.coin_id(),
+            SETTLEMENT_PAYMENTS_PUZZLE_HASH.into(),
+            xch_total,
+        );
+        let coin_spend = SettlementLayer.construct_coin_spend(
+            ctx,
+            settlement_coin,
+            SettlementPaymentsSolution {
+                notarized_payments: xch_payments,
+            },
+        )?;
+        ctx.insert(coin_spend);
+
+        let inner_spend = taker.spend_with_conditions(
+            ctx,
+            Conditions::new()
+                .create_coin(
+                    SETTLEMENT_PAYMENTS_PUZZLE_HASH.into(),
+                    cat_total,
+                    Vec::new(),
+                )
+                .create_coin(
+                    cat.p2_puzzle_hash,
+                    cat.coin.amount - cat_total,
+                    vec![cat.p2_puzzle_hash.into()],
+                ),
+        )?;
+        Cat::spend_all(ctx, &[CatSpend::new(cat, inner_spend)])?;
+
+        let settlement_cat = cat.wrapped_child(SETTLEMENT_PAYMENTS_PUZZLE_HASH.into(), cat_total);
+        let inner_spend = SettlementLayer.construct_spend(
+            ctx,
+            SettlementPaymentsSolution {
+                notarized_payments: cat_payments,
+            },
+        )?;
+        Cat::spend_all(ctx, &[CatSpend::new(settlement_cat, inner_spend)])?;
+
+        Ok(())
+    }
+
+    fn total(notarized_payments: &[NotarizedPayment]) -> u64 {
+        notarized_payments
+            .iter()
+            .flat_map(|item| &item.payments)
+            .map(|payment| payment.amount)
+            .sum()
+    }
+}
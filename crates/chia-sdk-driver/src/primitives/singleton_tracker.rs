@@ -0,0 +1,482 @@
+use chia_protocol::{Bytes32, Coin, CoinSpend, CoinState};
+use chia_puzzles::singleton::SINGLETON_LAUNCHER_PUZZLE_HASH;
+use chia_sdk_types::ChainReader;
+use clvm_traits::{FromClvm, ToClvm};
+use clvm_utils::ToTreeHash;
+use clvmr::Allocator;
+
+use crate::{Did, DriverError, Layer, Nft, Puzzle, SingletonLayer, SyncError};
+
+/// A singleton primitive which can be parsed from each spend in its lineage,
+/// so that it can be followed by a [`SingletonTracker`].
+pub trait TrackableSingleton: Sized {
+    /// The coin that currently holds the singleton.
+    fn coin(&self) -> Coin;
+
+    /// Parses the eve singleton from the launcher spend, if the launcher solution contains enough information.
+    fn parse_launcher(
+        _allocator: &mut Allocator,
+        _launcher_spend: &CoinSpend,
+    ) -> Result<Option<Self>, DriverError> {
+        Ok(None)
+    }
+
+    /// Parses the child singleton created by spending its parent.
+    ///
+    /// The previous state is [`None`] if the parent is the eve singleton and it couldn't be parsed from the launcher.
+    fn parse_child(
+        allocator: &mut Allocator,
+        previous: Option<&Self>,
+        parent_spend: &CoinSpend,
+        child_coin: Coin,
+    ) -> Result<Option<Self>, DriverError>;
+}
+
+impl<M> TrackableSingleton for Nft<M>
+where
+    M: ToClvm<Allocator> + FromClvm<Allocator> + ToTreeHash + Clone,
+{
+    fn coin(&self) -> Coin {
+        self.coin
+    }
+
+    fn parse_child(
+        allocator: &mut Allocator,
+        _previous: Option<&Self>,
+        parent_spend: &CoinSpend,
+        _child_coin: Coin,
+    ) -> Result<Option<Self>, DriverError> {
+        let puzzle = parent_spend.puzzle_reveal.to_clvm(allocator)?;
+        let puzzle = Puzzle::parse(allocator, puzzle);
+        let solution = parent_spend.solution.to_clvm(allocator)?;
+        Nft::parse_child(allocator, parent_spend.coin, puzzle, solution)
+    }
+}
+
+impl<M> TrackableSingleton for Did<M>
+where
+    M: ToClvm<Allocator> + FromClvm<Allocator> + Clone,
+{
+    fn coin(&self) -> Coin {
+        self.coin
+    }
+
+    fn parse_child(
+        allocator: &mut Allocator,
+        _previous: Option<&Self>,
+        parent_spend: &CoinSpend,
+        child_coin: Coin,
+    ) -> Result<Option<Self>, DriverError> {
+        let puzzle = parent_spend.puzzle_reveal.to_clvm(allocator)?;
+        let puzzle = Puzzle::parse(allocator, puzzle);
+        let solution = parent_spend.solution.to_clvm(allocator)?;
+        Did::parse_child(allocator, parent_spend.coin, puzzle, solution, child_coin)
+    }
+}
+
+#[cfg(feature = "chip-0035")]
+impl<M> TrackableSingleton for crate::DataStore<M>
+where
+    M: ToClvm<Allocator> + FromClvm<Allocator> + crate::MetadataWithRootHash,
+{
+    fn coin(&self) -> Coin {
+        self.coin
+    }
+
+    fn parse_launcher(
+        allocator: &mut Allocator,
+        launcher_spend: &CoinSpend,
+    ) -> Result<Option<Self>, DriverError> {
+        Self::from_spend(allocator, launcher_spend, &[])
+    }
+
+    fn parse_child(
+        allocator: &mut Allocator,
+        previous: Option<&Self>,
+        parent_spend: &CoinSpend,
+        _child_coin: Coin,
+    ) -> Result<Option<Self>, DriverError> {
+        let delegated_puzzles = previous.map_or(&[][..], |previous| {
+            previous.info.delegated_puzzles.as_slice()
+        });
+        Self::from_spend(allocator, parent_spend, delegated_puzzles)
+    }
+}
+
+/// Follows a singleton from its launcher coin through every spend, keeping track of each state along the way.
+///
+/// The tracker can be synced repeatedly, and it will only look up spends that happened since the last sync.
+#[derive(Debug, Clone)]
+pub struct SingletonTracker<S> {
+    launcher_id: Bytes32,
+    history: Vec<S>,
+    latest_coin_state: Option<CoinState>,
+    melted: bool,
+}
+
+impl<S> SingletonTracker<S>
+where
+    S: TrackableSingleton,
+{
+    pub fn new(launcher_id: Bytes32) -> Self {
+        Self {
+            launcher_id,
+            history: Vec::new(),
+            latest_coin_state: None,
+            melted: false,
+        }
+    }
+
+    pub fn launcher_id(&self) -> Bytes32 {
+        self.launcher_id
+    }
+
+    /// Every state of the singleton that has been parsed so far, from oldest to newest.
+    ///
+    /// The eve singleton is only included if it can be parsed from the launcher spend.
+    pub fn history(&self) -> &[S] {
+        &self.history
+    }
+
+    /// The latest known coin state in the singleton's lineage, starting with the launcher coin.
+    pub fn latest_coin_state(&self) -> Option<CoinState> {
+        self.latest_coin_state
+    }
+
+    /// Whether the singleton has been melted, in which case there will be no further spends.
+    /// The latest coin state is the coin that was melted.
+    pub fn is_melted(&self) -> bool {
+        self.melted
+    }
+
+    /// The current unspent singleton, if it has been parsed.
+    pub fn current(&self) -> Option<&S> {
+        let coin_state = self.latest_coin_state?;
+
+        if coin_state.spent_height.is_some() {
+            return None;
+        }
+
+        self.history
+            .last()
+            .filter(|state| state.coin() == coin_state.coin)
+    }
+
+    /// Looks up any spends that have happened since the last sync, and parses the new states.
+    ///
+    /// Returns the states that were added to the history. If a child singleton can't be parsed,
+    /// [`SyncError::UnparsableChild`] is returned, and the history stops at its parent.
+    pub async fn sync<C>(
+        &mut self,
+        chain: &C,
+        allocator: &mut Allocator,
+    ) -> Result<&[S], SyncError<C::Error>>
+    where
+        C: ChainReader,
+    {
+        let previous_len = self.history.len();
+
+        if self.melted {
+            return Ok(&[]);
+        }
+
+        let coin_id = self
+            .latest_coin_state
+            .map_or(self.launcher_id, |coin_state| coin_state.coin.coin_id());
+
+        let Some(mut coin_state) = chain
+            .coin_states(vec![coin_id])
+            .await
+            .map_err(SyncError::Chain)?
+            .into_iter()
+            .next()
+        else {
+            return Ok(&[]);
+        };
+
+        self.latest_coin_state = Some(coin_state);
+
+        while let Some(spent_height) = coin_state.spent_height {
+            let coin = coin_state.coin;
+
+            let Some((puzzle_reveal, solution)) = chain
+                .puzzle_and_solution(coin.coin_id(), spent_height)
+                .await
+                .map_err(SyncError::Chain)?
+            else {
+                break;
+            };
+
+            let coin_spend = CoinSpend::new(coin, puzzle_reveal, solution);
+
+            let Some(child) = chain
+                .children(coin.coin_id())
+                .await
+                .map_err(SyncError::Chain)?
+                .into_iter()
+                .find(|child| child.coin.amount % 2 == 1)
+            else {
+                // The singleton top layer requires an odd output, unless the singleton was melted.
+                let puzzle = coin_spend
+                    .puzzle_reveal
+                    .to_clvm(allocator)
+                    .map_err(DriverError::from)?;
+                let puzzle = Puzzle::parse(allocator, puzzle);
+
+                if SingletonLayer::<Puzzle>::parse_puzzle(allocator, puzzle)?.is_none() {
+                    return Err(DriverError::NonStandardLayer.into());
+                }
+
+                self.melted = true;
+                break;
+            };
+
+            let state = if coin.puzzle_hash == SINGLETON_LAUNCHER_PUZZLE_HASH.into() {
+                S::parse_launcher(allocator, &coin_spend)?
+            } else {
+                let Some(state) =
+                    S::parse_child(allocator, self.history.last(), &coin_spend, child.coin)?
+                else {
+                    return Err(SyncError::UnparsableChild(child.coin.coin_id()));
+                };
+                Some(state)
+            };
+
+            self.history.extend(state);
+            self.latest_coin_state = Some(child);
+            coin_state = child;
+        }
+
+        Ok(&self.history[previous_len..])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chia_puzzles::nft::NftMetadata;
+    use chia_sdk_test::Simulator;
+    use chia_sdk_types::Conditions;
+
+    use crate::{HashedPtr, Launcher, NftMint, SpendContext, StandardLayer};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_track_nft_history() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let ctx = &mut SpendContext::new();
+        let allocator = &mut Allocator::new();
+
+        let (alice_secret_key, alice_public_key, alice_puzzle_hash, coin) = sim.child_p2(1, 0)?;
+        let alice = StandardLayer::new(alice_public_key);
+
+        let (bob_secret_key, bob_public_key, bob_puzzle_hash, _) = sim.child_p2(0, 1)?;
+        let bob = StandardLayer::new(bob_public_key);
+
+        let (mint_nft, nft) = Launcher::new(coin.coin_id(), 1).mint_nft(
+            ctx,
+            NftMint::new(NftMetadata::default(), alice_puzzle_hash, 300, None),
+        )?;
+        alice.spend(ctx, coin, mint_nft)?;
+        sim.spend_coins(ctx.take(), &[alice_secret_key.clone()])?;
+
+        let mut tracker = SingletonTracker::<Nft<NftMetadata>>::new(nft.info.launcher_id);
+
+        assert_eq!(tracker.sync(&sim, allocator).await?, [nft.clone()]);
+        assert_eq!(tracker.current(), Some(&nft));
+
+        // Syncing again without any new spends doesn't change anything.
+        assert!(tracker.sync(&sim, allocator).await?.is_empty());
+
+        let bob_nft = nft
+            .clone()
+            .transfer(ctx, &alice, bob_puzzle_hash, Conditions::new())?;
+        sim.spend_coins(ctx.take(), &[alice_secret_key.clone()])?;
+
+        let alice_nft =
+            bob_nft
+                .clone()
+                .transfer(ctx, &bob, alice_puzzle_hash, Conditions::new())?;
+        sim.spend_coins(ctx.take(), &[bob_secret_key])?;
+
+        assert_eq!(
+            tracker.sync(&sim, allocator).await?,
+            [bob_nft.clone(), alice_nft.clone()]
+        );
+        assert_eq!(tracker.history(), [nft, bob_nft, alice_nft.clone()]);
+        assert_eq!(tracker.current(), Some(&alice_nft));
+
+        let owners: Vec<Bytes32> = tracker
+            .history()
+            .iter()
+            .map(|nft| nft.info.p2_puzzle_hash)
+            .collect();
+        assert_eq!(
+            owners,
+            [alice_puzzle_hash, bob_puzzle_hash, alice_puzzle_hash]
+        );
+
+        // Burned NFTs are still tracked, since the singleton lives on at the burn puzzle hash.
+        let burned_nft = alice_nft.burn(ctx, &alice, Conditions::new())?;
+        sim.spend_coins(ctx.take(), &[alice_secret_key])?;
+
+        assert_eq!(tracker.sync(&sim, allocator).await?, [burned_nft.clone()]);
+        assert!(tracker.current().is_some_and(Nft::is_burned));
+        assert!(!tracker.is_melted());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_track_did_history() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let ctx = &mut SpendContext::new();
+        let allocator = &mut Allocator::new();
+
+        let (sk, pk, puzzle_hash, coin) = sim.new_p2(1)?;
+        let p2 = StandardLayer::new(pk);
+
+        let (create_did, did) =
+            Launcher::new(coin.coin_id(), 1).create_did(ctx, None, 1, HashedPtr::NIL, &p2)?;
+        p2.spend(ctx, coin, create_did)?;
+
+        let updated_did = did.update(ctx, &p2, Conditions::new())?;
+
+        sim.spend_coins(ctx.take(), &[sk.clone()])?;
+
+        let mut tracker = SingletonTracker::<Did<HashedPtr>>::new(did.info.launcher_id);
+
+        // The eve DID can't be parsed from the launcher spend, so the history starts after it.
+        assert_eq!(tracker.sync(&sim, allocator).await?, [did, updated_did]);
+        assert_eq!(tracker.current(), Some(&updated_did));
+
+        // Once melted, the DID has no current state and there's nothing left to sync.
+        updated_did.melt(ctx, &p2, puzzle_hash, Conditions::new())?;
+        sim.spend_coins(ctx.take(), &[sk])?;
+
+        assert!(tracker.sync(&sim, allocator).await?.is_empty());
+        assert!(tracker.is_melted());
+        assert_eq!(tracker.current(), None);
+        assert_eq!(tracker.history(), [did, updated_did]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_track_wrong_singleton_type() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let ctx = &mut SpendContext::new();
+        let allocator = &mut Allocator::new();
+
+        let (sk, pk, puzzle_hash, coin) = sim.new_p2(1)?;
+        let p2 = StandardLayer::new(pk);
+
+        let (mint_nft, nft) = Launcher::new(coin.coin_id(), 1).mint_nft(
+            ctx,
+            NftMint::new(NftMetadata::default(), puzzle_hash, 300, None),
+        )?;
+        p2.spend(ctx, coin, mint_nft)?;
+        sim.spend_coins(ctx.take(), &[sk])?;
+
+        // The NFT can't be parsed as a DID, so syncing stops at the child of the eve spend.
+        let mut tracker = SingletonTracker::<Did<HashedPtr>>::new(nft.info.launcher_id);
+
+        assert!(matches!(
+            tracker.sync(&sim, allocator).await,
+            Err(SyncError::UnparsableChild(coin_id)) if coin_id == nft.coin.coin_id()
+        ));
+        assert!(tracker.history().is_empty());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_track_unknown_launcher() -> anyhow::Result<()> {
+        let sim = Simulator::new();
+        let allocator = &mut Allocator::new();
+
+        let mut tracker = SingletonTracker::<Did<HashedPtr>>::new(Bytes32::default());
+
+        assert!(tracker.sync(&sim, allocator).await?.is_empty());
+        assert!(tracker.current().is_none());
+        assert!(tracker.latest_coin_state().is_none());
+
+        Ok(())
+    }
+
+    #[cfg(feature = "chip-0035")]
+    #[tokio::test]
+    async fn test_track_datastore_history() -> anyhow::Result<()> {
+        use crate::{DataStore, DataStoreMetadata, MetadataWithRootHash, SpendWithConditions};
+
+        let mut sim = Simulator::new();
+        let ctx = &mut SpendContext::new();
+        let allocator = &mut Allocator::new();
+
+        let (sk, pk, puzzle_hash, coin) = sim.new_p2(1)?;
+        let p2 = StandardLayer::new(pk);
+
+        let (launch_singleton, datastore) = Launcher::new(coin.coin_id(), 1).mint_datastore(
+            ctx,
+            DataStoreMetadata::root_hash_only(Bytes32::default()),
+            puzzle_hash.into(),
+            vec![],
+        )?;
+        p2.spend(ctx, coin, launch_singleton)?;
+
+        let new_root_hash = Bytes32::new([1; 32]);
+        let new_metadata_condition = DataStore::new_metadata_condition(
+            ctx,
+            DataStoreMetadata::root_hash_only(new_root_hash),
+        )?;
+        let recreate_condition = DataStore::<DataStoreMetadata>::owner_create_coin_condition(
+            ctx,
+            datastore.info.launcher_id,
+            puzzle_hash,
+            vec![],
+            false,
+        )?;
+        let inner_spend = p2.spend_with_conditions(
+            ctx,
+            Conditions::new()
+                .with(new_metadata_condition)
+                .with(recreate_condition),
+        )?;
+        let coin_spend = datastore.clone().spend(ctx, inner_spend)?;
+        ctx.insert(coin_spend);
+
+        sim.spend_coins(ctx.take(), &[sk.clone()])?;
+
+        let mut tracker = SingletonTracker::<DataStore>::new(datastore.info.launcher_id);
+        tracker.sync(&sim, allocator).await?;
+
+        // The eve datastore is parsed from the launcher spend.
+        let root_hashes: Vec<Bytes32> = tracker
+            .history()
+            .iter()
+            .map(|datastore| datastore.info.metadata.root_hash)
+            .collect();
+        assert_eq!(root_hashes, [Bytes32::default(), new_root_hash]);
+        assert_eq!(tracker.history()[0], datastore);
+        assert_eq!(
+            tracker
+                .current()
+                .map(|datastore| datastore.info.owner_puzzle_hash),
+            Some(puzzle_hash)
+        );
+
+        let current = tracker
+            .current()
+            .cloned()
+            .expect("datastore should be unspent");
+        let coin_spend = current.melt(ctx, &p2, puzzle_hash, Conditions::new())?;
+        ctx.insert(coin_spend);
+        sim.spend_coins(ctx.take(), &[sk])?;
+
+        assert!(tracker.sync(&sim, allocator).await?.is_empty());
+        assert!(tracker.is_melted());
+        assert_eq!(tracker.current(), None);
+
+        Ok(())
+    }
+}
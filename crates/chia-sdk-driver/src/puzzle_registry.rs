@@ -0,0 +1,296 @@
+use std::{collections::HashMap, fmt};
+
+use clvm_utils::TreeHash;
+use clvmr::{Allocator, NodePtr};
+
+use crate::{
+    CatLayer, DidLayer, DriverError, HashedPtr, Layer, NftOwnershipLayer, NftStateLayer,
+    P2DelegatedConditionsLayer, P2DelegatedSingletonLayer, P2OneOfMany, P2Singleton, Puzzle,
+    RoyaltyTransferLayer, SettlementLayer, SingletonLayer, StandardLayer,
+};
+
+/// A [`Layer`] which can be identified by its mod hash, and peeled off to reveal the puzzles it wraps.
+/// This allows it to be registered in a [`PuzzleRegistry`].
+///
+/// Layers which wrap other puzzles should implement this with [`Puzzle`] as the inner layer type,
+/// so that only a single layer is parsed at a time.
+pub trait RecognizableLayer: Layer + Sized {
+    /// The mod hash of the layer's puzzle, or the puzzle hash if it isn't curried.
+    const MOD_HASH: TreeHash;
+
+    /// The name of the layer, as it appears in a [`RecognizedPuzzle`].
+    const NAME: &'static str;
+
+    /// The puzzles wrapped by this layer, in the order they're curried in.
+    fn inner_puzzles(&self) -> Vec<Puzzle> {
+        Vec::new()
+    }
+}
+
+/// Parses a single layer from a puzzle, returning the puzzles it wraps if it matched.
+pub type Recognizer =
+    dyn Fn(&Allocator, Puzzle) -> Result<Option<Vec<Puzzle>>, DriverError> + Send + Sync;
+
+/// A collection of layers, keyed by mod hash, which can be used to identify arbitrary puzzles.
+#[derive(Default)]
+pub struct PuzzleRegistry {
+    layers: HashMap<TreeHash, (&'static str, Box<Recognizer>)>,
+}
+
+impl fmt::Debug for PuzzleRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map()
+            .entries(
+                self.layers
+                    .iter()
+                    .map(|(mod_hash, (name, _))| (mod_hash, name)),
+            )
+            .finish()
+    }
+}
+
+impl PuzzleRegistry {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a registry with every layer that's built into this crate.
+    pub fn with_builtin_layers() -> Self {
+        let mut registry = Self::new();
+        registry.register::<SingletonLayer<Puzzle>>();
+        registry.register::<CatLayer<Puzzle>>();
+        registry.register::<DidLayer<HashedPtr, Puzzle>>();
+        registry.register::<NftStateLayer<HashedPtr, Puzzle>>();
+        registry.register::<NftOwnershipLayer<Puzzle, Puzzle>>();
+        registry.register::<RoyaltyTransferLayer>();
+        registry.register::<StandardLayer>();
+        registry.register::<P2DelegatedConditionsLayer>();
+        registry.register::<P2DelegatedSingletonLayer>();
+        registry.register::<P2OneOfMany>();
+        registry.register::<P2Singleton>();
+        registry.register::<SettlementLayer>();
+
+        #[cfg(feature = "chip-0035")]
+        {
+            registry.register::<crate::DelegationLayer>();
+            registry.register::<crate::WriterLayer<Puzzle>>();
+        }
+
+        registry
+    }
+
+    /// Registers a layer by its mod hash, replacing any layer previously registered with the same one.
+    pub fn register<L>(&mut self)
+    where
+        L: RecognizableLayer,
+    {
+        self.register_fn(L::MOD_HASH, L::NAME, |allocator, puzzle| {
+            Ok(L::parse_puzzle(allocator, puzzle)?.map(|layer| layer.inner_puzzles()))
+        });
+    }
+
+    /// Registers a custom function for recognizing puzzles with the given mod hash.
+    /// This is useful for puzzles which don't have a [`Layer`] implementation.
+    pub fn register_fn<F>(&mut self, mod_hash: TreeHash, name: &'static str, recognize: F)
+    where
+        F: Fn(&Allocator, Puzzle) -> Result<Option<Vec<Puzzle>>, DriverError>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.layers.insert(mod_hash, (name, Box::new(recognize)));
+    }
+
+    /// Peels off every registered layer from the puzzle, recursively.
+    /// Puzzles which aren't registered, or don't match the layer registered with their mod hash,
+    /// are returned without a name. This includes puzzles with the right mod hash but malformed
+    /// curried arguments, which the layer fails to parse.
+    pub fn recognize(&self, allocator: &Allocator, puzzle: Puzzle) -> RecognizedPuzzle {
+        let Some((name, recognize)) = self.layers.get(&puzzle.mod_hash()) else {
+            return RecognizedPuzzle::unknown(puzzle);
+        };
+
+        let Ok(Some(inner_puzzles)) = recognize(allocator, puzzle) else {
+            return RecognizedPuzzle::unknown(puzzle);
+        };
+
+        RecognizedPuzzle {
+            name: Some(name),
+            puzzle,
+            inner_puzzles: inner_puzzles
+                .into_iter()
+                .map(|inner_puzzle| self.recognize(allocator, inner_puzzle))
+                .collect(),
+        }
+    }
+}
+
+/// A tree describing the layers of a puzzle, as recognized by a [`PuzzleRegistry`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecognizedPuzzle {
+    /// The name of the layer, or [`None`] if the puzzle wasn't recognized.
+    pub name: Option<&'static str>,
+    /// The puzzle itself, including its curried arguments.
+    pub puzzle: Puzzle,
+    /// The puzzles wrapped by this layer, in the order they're curried in.
+    pub inner_puzzles: Vec<RecognizedPuzzle>,
+}
+
+impl RecognizedPuzzle {
+    fn unknown(puzzle: Puzzle) -> Self {
+        Self {
+            name: None,
+            puzzle,
+            inner_puzzles: Vec::new(),
+        }
+    }
+
+    /// The curried arguments of the puzzle, or [`None`] if it isn't curried.
+    pub fn args(&self) -> Option<NodePtr> {
+        self.puzzle.as_curried().map(|curried| curried.args)
+    }
+
+    /// Whether every layer in the tree was recognized.
+    pub fn is_fully_recognized(&self) -> bool {
+        self.name.is_some() && self.inner_puzzles.iter().all(Self::is_fully_recognized)
+    }
+
+    /// The names of every layer in the tree, in depth-first order.
+    /// Puzzles which weren't recognized are skipped.
+    pub fn layer_names(&self) -> Vec<&'static str> {
+        let mut names: Vec<&'static str> = self.name.into_iter().collect();
+
+        for inner_puzzle in &self.inner_puzzles {
+            names.extend(inner_puzzle.layer_names());
+        }
+
+        names
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chia_protocol::Bytes32;
+    use chia_puzzles::{nft::NFT_METADATA_UPDATER_PUZZLE_HASH, standard::StandardArgs};
+    use chia_sdk_test::test_secret_key;
+    use clvm_traits::{FromClvm, ToClvm};
+    use clvm_utils::CurriedProgram;
+
+    use crate::{NftInfo, SpendContext};
+
+    use super::*;
+
+    #[test]
+    fn test_recognize_nft() -> anyhow::Result<()> {
+        let ctx = &mut SpendContext::new();
+        let public_key = test_secret_key()?.public_key();
+        let p2_puzzle_hash = StandardArgs::curry_tree_hash(public_key).into();
+
+        let info = NftInfo::new(
+            Bytes32::new([1; 32]),
+            HashedPtr::NIL,
+            NFT_METADATA_UPDATER_PUZZLE_HASH.into(),
+            Some(Bytes32::new([2; 32])),
+            p2_puzzle_hash,
+            300,
+            p2_puzzle_hash,
+        );
+        let puzzle = info
+            .into_layers(StandardLayer::new(public_key))
+            .construct_puzzle(ctx)?;
+        let puzzle = Puzzle::parse(&ctx.allocator, puzzle);
+
+        let registry = PuzzleRegistry::with_builtin_layers();
+        let recognized = registry.recognize(&ctx.allocator, puzzle);
+
+        assert!(recognized.is_fully_recognized());
+        assert_eq!(
+            recognized.layer_names(),
+            [
+                "Singleton",
+                "NftState",
+                "NftOwnership",
+                "RoyaltyTransfer",
+                "Standard"
+            ]
+        );
+
+        // The ownership layer wraps both the transfer program and the inner puzzle.
+        let ownership = &recognized.inner_puzzles[0].inner_puzzles[0];
+        assert_eq!(ownership.inner_puzzles.len(), 2);
+
+        let standard = &ownership.inner_puzzles[1];
+        let args = StandardArgs::from_clvm(&ctx.allocator, standard.args().unwrap())?;
+        assert_eq!(args.synthetic_key, public_key);
+
+        Ok(())
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, ToClvm, FromClvm)]
+    #[clvm(curry)]
+    struct CustomArgs {
+        value: Bytes32,
+    }
+
+    #[test]
+    fn test_recognize_custom() -> anyhow::Result<()> {
+        let ctx = &mut SpendContext::new();
+        let custom_mod = ctx.alloc(&1)?;
+        let custom_mod_hash = ctx.tree_hash(custom_mod);
+
+        let inner = ctx.alloc(&CurriedProgram {
+            program: custom_mod,
+            args: CustomArgs {
+                value: Bytes32::default(),
+            },
+        })?;
+        let puzzle = CatLayer::new(Bytes32::default(), Puzzle::parse(&ctx.allocator, inner))
+            .construct_puzzle(ctx)?;
+        let puzzle = Puzzle::parse(&ctx.allocator, puzzle);
+
+        let mut registry = PuzzleRegistry::with_builtin_layers();
+
+        let recognized = registry.recognize(&ctx.allocator, puzzle);
+        assert_eq!(recognized.layer_names(), ["Cat"]);
+        assert!(!recognized.is_fully_recognized());
+
+        registry.register_fn(custom_mod_hash, "Custom", |allocator, puzzle| {
+            let Some(curried) = puzzle.as_curried() else {
+                return Ok(None);
+            };
+            CustomArgs::from_clvm(allocator, curried.args)?;
+            Ok(Some(Vec::new()))
+        });
+
+        let recognized = registry.recognize(&ctx.allocator, puzzle);
+        assert_eq!(recognized.layer_names(), ["Cat", "Custom"]);
+        assert!(recognized.is_fully_recognized());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_recognize_malformed_singleton() -> anyhow::Result<()> {
+        let ctx = &mut SpendContext::new();
+        let singleton_mod = ctx.singleton_top_layer()?;
+
+        // The mod hash matches the singleton layer, but the curried arguments aren't a singleton struct.
+        let puzzle = ctx.alloc(&CurriedProgram {
+            program: singleton_mod,
+            args: CustomArgs {
+                value: Bytes32::default(),
+            },
+        })?;
+        let puzzle = Puzzle::parse(&ctx.allocator, puzzle);
+
+        let registry = PuzzleRegistry::with_builtin_layers();
+        let recognized = registry.recognize(&ctx.allocator, puzzle);
+
+        assert_eq!(recognized.name, None);
+        assert_eq!(recognized.puzzle, puzzle);
+        assert!(recognized.inner_puzzles.is_empty());
+
+        Ok(())
+    }
+}
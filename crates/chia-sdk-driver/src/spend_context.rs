@@ -27,7 +27,8 @@ use clvm_utils::{tree_hash, TreeHash};
 use clvmr::{serde::node_from_bytes, Allocator, NodePtr};
 
 use crate::{
-    DriverError, Spend, P2_DELEGATED_CONDITIONS_PUZZLE, P2_DELEGATED_CONDITIONS_PUZZLE_HASH,
+    DriverError, Spend, NFT_BATCH_METADATA_UPDATER_PUZZLE, NFT_BATCH_METADATA_UPDATER_PUZZLE_HASH,
+    P2_DELEGATED_CONDITIONS_PUZZLE, P2_DELEGATED_CONDITIONS_PUZZLE_HASH,
     P2_DELEGATED_SINGLETON_PUZZLE, P2_DELEGATED_SINGLETON_PUZZLE_HASH, P2_ONE_OF_MANY_PUZZLE,
     P2_ONE_OF_MANY_PUZZLE_HASH, P2_SINGLETON_PUZZLE, P2_SINGLETON_PUZZLE_HASH,
 };
@@ -142,6 +143,14 @@ impl SpendContext {
         )
     }
 
+    /// Allocate the NFT batch metadata updater puzzle and return its pointer.
+    pub fn nft_batch_metadata_updater(&mut self) -> Result<NodePtr, DriverError> {
+        self.puzzle(
+            NFT_BATCH_METADATA_UPDATER_PUZZLE_HASH,
+            &NFT_BATCH_METADATA_UPDATER_PUZZLE,
+        )
+    }
+
     /// Allocate the NFT ownership layer puzzle and return its pointer.
     pub fn nft_ownership_layer(&mut self) -> Result<NodePtr, DriverError> {
         self.puzzle(NFT_OWNERSHIP_LAYER_PUZZLE_HASH, &NFT_OWNERSHIP_LAYER_PUZZLE)
@@ -0,0 +1,17 @@
+use chia_protocol::Bytes32;
+use thiserror::Error;
+
+use crate::DriverError;
+
+/// An error that occurred while syncing state from a [`ChainReader`](chia_sdk_types::ChainReader).
+#[derive(Debug, Error)]
+pub enum SyncError<E> {
+    #[error("chain error: {0}")]
+    Chain(E),
+
+    #[error("driver error: {0}")]
+    Driver(#[from] DriverError),
+
+    #[error("child singleton coin {0} could not be parsed")]
+    UnparsableChild(Bytes32),
+}
@@ -0,0 +1,645 @@
+use std::mem;
+
+use chia_protocol::{Bytes, Bytes32, Coin, CoinSpend};
+use chia_sdk_signer::{AggSigConstants, RequiredSignature};
+use chia_sdk_types::{announcement_id, Conditions};
+use chia_sdk_utils::select_coins;
+
+use crate::{
+    Cat, CatSpend, DriverError, HashedPtr, Nft, SpendContext, SpendWithConditions, StandardLayer,
+};
+
+/// A coin to be created by a transaction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Payment {
+    pub puzzle_hash: Bytes32,
+    pub amount: u64,
+    pub memos: Vec<Bytes>,
+}
+
+impl Payment {
+    pub fn new(puzzle_hash: Bytes32, amount: u64, memos: Vec<Bytes>) -> Self {
+        Self {
+            puzzle_hash,
+            amount,
+            memos,
+        }
+    }
+}
+
+/// Builds a balanced transaction from a set of payments, a fee, and the coins available to spend.
+/// Payments can be made in XCH and any number of CATs, and NFTs can be sent in the same transaction.
+///
+/// Only as many coins as are needed to cover the payments of each asset are selected, and the fee is
+/// paid from the XCH inputs. The first selected coin of each asset creates its payments and change,
+/// and every input (including NFTs) is linked to the next with a coin announcement,
+/// so that none of them can be spent without the others.
+#[derive(Debug, Default, Clone)]
+#[must_use]
+pub struct TransactionBuilder {
+    payments: Vec<Payment>,
+    cat_payments: Vec<(Bytes32, Payment)>,
+    nft_transfers: Vec<(Nft<HashedPtr>, StandardLayer, Bytes32)>,
+    fee: u64,
+    spendable_coins: Vec<(Coin, StandardLayer)>,
+    spendable_cats: Vec<(Cat, StandardLayer)>,
+    change_puzzle_hash: Option<Bytes32>,
+}
+
+impl TransactionBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a payment to the transaction.
+    pub fn pay(mut self, puzzle_hash: Bytes32, amount: u64, memos: Vec<Bytes>) -> Self {
+        self.payments.push(Payment::new(puzzle_hash, amount, memos));
+        self
+    }
+
+    /// Adds a payment of the CAT with the given asset id to the transaction.
+    /// The puzzle hash is the inner puzzle hash, so the memos should usually include it as a hint.
+    pub fn pay_cat(
+        mut self,
+        asset_id: Bytes32,
+        puzzle_hash: Bytes32,
+        amount: u64,
+        memos: Vec<Bytes>,
+    ) -> Self {
+        self.cat_payments
+            .push((asset_id, Payment::new(puzzle_hash, amount, memos)));
+        self
+    }
+
+    /// Sends an NFT to a new p2 puzzle hash. The NFT's inner puzzle must be spendable with the given [`StandardLayer`].
+    ///
+    /// If the NFT is currently owned by a DID, it's unassigned as part of the transfer,
+    /// which is consistent with how NFTs are sent by the reference wallet.
+    pub fn send_nft(
+        mut self,
+        nft: Nft<HashedPtr>,
+        p2: StandardLayer,
+        puzzle_hash: Bytes32,
+    ) -> Self {
+        self.nft_transfers.push((nft, p2, puzzle_hash));
+        self
+    }
+
+    /// Sets the fee paid by the transaction, which defaults to 0.
+    pub fn fee(mut self, fee: u64) -> Self {
+        self.fee = fee;
+        self
+    }
+
+    /// Adds coins that can be selected as inputs, all of which must be spendable with the given [`StandardLayer`].
+    /// Coins which have already been added are skipped, so that they can't be selected twice.
+    pub fn spendable_coins(
+        mut self,
+        p2: StandardLayer,
+        coins: impl IntoIterator<Item = Coin>,
+    ) -> Self {
+        for coin in coins {
+            let coin_id = coin.coin_id();
+
+            if self
+                .spendable_coins
+                .iter()
+                .all(|(spendable_coin, _)| spendable_coin.coin_id() != coin_id)
+            {
+                self.spendable_coins.push((coin, p2));
+            }
+        }
+        self
+    }
+
+    /// Adds CATs that can be selected as inputs, all of which must be spendable with the given [`StandardLayer`].
+    /// CATs which have already been added are skipped, so that they can't be selected twice.
+    pub fn spendable_cats(
+        mut self,
+        p2: StandardLayer,
+        cats: impl IntoIterator<Item = Cat>,
+    ) -> Self {
+        for cat in cats {
+            let coin_id = cat.coin.coin_id();
+
+            if self
+                .spendable_cats
+                .iter()
+                .all(|(spendable_cat, _)| spendable_cat.coin.coin_id() != coin_id)
+            {
+                self.spendable_cats.push((cat, p2));
+            }
+        }
+        self
+    }
+
+    /// Sets the puzzle hash that change is sent to, for both XCH and CATs.
+    /// By default, this is the p2 puzzle hash of the first selected coin of each asset.
+    pub fn change_puzzle_hash(mut self, change_puzzle_hash: Bytes32) -> Self {
+        self.change_puzzle_hash = Some(change_puzzle_hash);
+        self
+    }
+
+    /// Selects the inputs and creates the coin spends for the transaction.
+    /// The coin spends are returned rather than being added to the [`SpendContext`].
+    pub fn build(
+        self,
+        ctx: &mut SpendContext,
+        constants: &AggSigConstants,
+    ) -> Result<UnsignedTransaction, DriverError> {
+        let mut inputs = Vec::new();
+
+        let mut change = None;
+
+        let xch_total =
+            total_amount(self.payments.iter().map(|payment| payment.amount)) + u128::from(self.fee);
+
+        if xch_total > 0 {
+            let selected = select_coins(
+                self.spendable_coins.iter().map(|(coin, _)| *coin).collect(),
+                xch_total,
+            )?;
+
+            let change_amount =
+                u64::try_from(total_amount(selected.iter().map(|coin| coin.amount)) - xch_total)?;
+
+            let primary_coin = selected[0];
+            let change_puzzle_hash = self.change_puzzle_hash.unwrap_or(primary_coin.puzzle_hash);
+
+            let mut conditions = create_payments(self.payments);
+
+            if change_amount > 0 {
+                conditions = conditions.create_coin(change_puzzle_hash, change_amount, Vec::new());
+                change = Some(Coin::new(
+                    primary_coin.coin_id(),
+                    change_puzzle_hash,
+                    change_amount,
+                ));
+            }
+
+            if self.fee > 0 {
+                conditions = conditions.reserve_fee(self.fee);
+            }
+
+            for coin in selected {
+                let p2 = find_p2(&self.spendable_coins, coin)?;
+                inputs.push(Input {
+                    kind: InputKind::Xch(coin, p2),
+                    conditions: mem::take(&mut conditions),
+                });
+            }
+        }
+
+        let mut asset_ids = Vec::new();
+
+        for (asset_id, _) in &self.cat_payments {
+            if !asset_ids.contains(asset_id) {
+                asset_ids.push(*asset_id);
+            }
+        }
+
+        let mut cat_change = Vec::new();
+
+        for &asset_id in &asset_ids {
+            let payments: Vec<Payment> = self
+                .cat_payments
+                .iter()
+                .filter(|(payment_asset_id, _)| *payment_asset_id == asset_id)
+                .map(|(_, payment)| payment.clone())
+                .collect();
+
+            let cat_total = total_amount(payments.iter().map(|payment| payment.amount));
+
+            let selected = select_coins(
+                self.spendable_cats
+                    .iter()
+                    .filter(|(cat, _)| cat.asset_id == asset_id)
+                    .map(|(cat, _)| cat.coin)
+                    .collect(),
+                cat_total,
+            )?;
+
+            let change_amount =
+                u64::try_from(total_amount(selected.iter().map(|coin| coin.amount)) - cat_total)?;
+
+            let cats = selected
+                .into_iter()
+                .map(|coin| {
+                    self.spendable_cats
+                        .iter()
+                        .find(|(cat, _)| cat.coin == coin)
+                        .copied()
+                        .ok_or(DriverError::UnspendableCoin(coin.coin_id()))
+                })
+                .collect::<Result<Vec<(Cat, StandardLayer)>, _>>()?;
+
+            let primary_cat = cats[0].0;
+            let change_puzzle_hash = self
+                .change_puzzle_hash
+                .unwrap_or(primary_cat.p2_puzzle_hash);
+
+            let mut conditions = create_payments(payments);
+
+            if change_amount > 0 {
+                conditions = conditions.create_coin(
+                    change_puzzle_hash,
+                    change_amount,
+                    vec![change_puzzle_hash.into()],
+                );
+                cat_change.push(primary_cat.wrapped_child(change_puzzle_hash, change_amount));
+            }
+
+            for (cat, p2) in cats {
+                inputs.push(Input {
+                    kind: InputKind::Cat(cat, p2),
+                    conditions: mem::take(&mut conditions),
+                });
+            }
+        }
+
+        for (nft, p2, puzzle_hash) in self.nft_transfers {
+            inputs.push(Input {
+                kind: InputKind::Nft(nft, p2, puzzle_hash),
+                conditions: Conditions::new(),
+            });
+        }
+
+        if inputs.len() > 1 {
+            let coin_ids: Vec<Bytes32> =
+                inputs.iter().map(|input| input.coin().coin_id()).collect();
+
+            for (index, input) in inputs.iter_mut().enumerate() {
+                let next_coin_id = coin_ids[(index + 1) % coin_ids.len()];
+                input.conditions = mem::take(&mut input.conditions)
+                    .create_coin_announcement(b"$".to_vec().into())
+                    .assert_coin_announcement(announcement_id(next_coin_id, "$"));
+            }
+        }
+
+        // Any spends which were already in the context are set aside, so they aren't included.
+        let existing_spends = ctx.take();
+        let result = spend_inputs(ctx, inputs, &asset_ids);
+        let coin_spends = ctx.take();
+
+        for coin_spend in existing_spends {
+            ctx.insert(coin_spend);
+        }
+
+        let nfts = result?;
+
+        let required_signatures =
+            RequiredSignature::from_coin_spends(&mut ctx.allocator, &coin_spends, constants)?;
+
+        Ok(UnsignedTransaction {
+            coin_spends,
+            required_signatures,
+            change,
+            cat_change,
+            nfts,
+        })
+    }
+}
+
+/// The result of building a transaction with the [`TransactionBuilder`].
+#[derive(Debug, Clone)]
+pub struct UnsignedTransaction {
+    pub coin_spends: Vec<CoinSpend>,
+    /// The signatures which must be aggregated to form the spend bundle's signature.
+    pub required_signatures: Vec<RequiredSignature>,
+    /// The XCH change coin, if the selected coins exceeded the payments and fee.
+    pub change: Option<Coin>,
+    /// The change for each CAT, if the selected CATs exceeded the payments.
+    pub cat_change: Vec<Cat>,
+    /// The NFTs that were sent, in the order they were added.
+    pub nfts: Vec<Nft<HashedPtr>>,
+}
+
+#[derive(Debug, Clone)]
+struct Input {
+    kind: InputKind,
+    conditions: Conditions,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum InputKind {
+    Xch(Coin, StandardLayer),
+    Cat(Cat, StandardLayer),
+    Nft(Nft<HashedPtr>, StandardLayer, Bytes32),
+}
+
+impl Input {
+    fn coin(&self) -> Coin {
+        match self.kind {
+            InputKind::Xch(coin, _) => coin,
+            InputKind::Cat(cat, _) => cat.coin,
+            InputKind::Nft(nft, _, _) => nft.coin,
+        }
+    }
+}
+
+fn spend_inputs(
+    ctx: &mut SpendContext,
+    inputs: Vec<Input>,
+    asset_ids: &[Bytes32],
+) -> Result<Vec<Nft<HashedPtr>>, DriverError> {
+    let mut cat_spends = Vec::new();
+    let mut nfts = Vec::new();
+
+    for Input { kind, conditions } in inputs {
+        match kind {
+            InputKind::Xch(coin, p2) => p2.spend(ctx, coin, conditions)?,
+            InputKind::Cat(cat, p2) => {
+                let inner_spend = p2.spend_with_conditions(ctx, conditions)?;
+                cat_spends.push(CatSpend::new(cat, inner_spend));
+            }
+            InputKind::Nft(nft, p2, puzzle_hash) => {
+                let nft = if nft.info.current_owner.is_some() {
+                    nft.transfer_to_did(ctx, &p2, puzzle_hash, None, conditions)?
+                        .1
+                } else {
+                    nft.transfer(ctx, &p2, puzzle_hash, conditions)?
+                };
+                nfts.push(nft);
+            }
+        }
+    }
+
+    for &asset_id in asset_ids {
+        let cat_spends: Vec<CatSpend> = cat_spends
+            .iter()
+            .filter(|cat_spend| cat_spend.cat.asset_id == asset_id)
+            .copied()
+            .collect();
+
+        Cat::spend_all(ctx, &cat_spends)?;
+    }
+
+    Ok(nfts)
+}
+
+fn create_payments(payments: Vec<Payment>) -> Conditions {
+    payments
+        .into_iter()
+        .fold(Conditions::new(), |conditions, payment| {
+            conditions.create_coin(payment.puzzle_hash, payment.amount, payment.memos)
+        })
+}
+
+fn total_amount(amounts: impl Iterator<Item = u64>) -> u128 {
+    amounts.map(u128::from).sum()
+}
+
+fn find_p2(
+    spendable_coins: &[(Coin, StandardLayer)],
+    coin: Coin,
+) -> Result<StandardLayer, DriverError> {
+    spendable_coins
+        .iter()
+        .find_map(|(spendable_coin, p2)| (*spendable_coin == coin).then_some(*p2))
+        .ok_or(DriverError::UnspendableCoin(coin.coin_id()))
+}
+
+#[cfg(test)]
+mod tests {
+    use chia_sdk_test::Simulator;
+    use chia_sdk_utils::CoinSelectionError;
+
+    use crate::{DidOwner, IntermediateLauncher, Launcher, NftMint};
+
+    use super::*;
+
+    #[test]
+    fn test_transaction_builder() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let constants = AggSigConstants::from(&sim.config().constants);
+        let ctx = &mut SpendContext::new();
+
+        let (sk, pk, puzzle_hash, coin) = sim.new_p2(600)?;
+        let other_coin = sim.new_coin(puzzle_hash, 700);
+        let small_coin = sim.new_coin(puzzle_hash, 50);
+
+        let recipient = Bytes32::new([1; 32]);
+
+        let tx = TransactionBuilder::new()
+            .pay(recipient, 1000, vec![recipient.into()])
+            .fee(100)
+            .spendable_coins(StandardLayer::new(pk), [coin, other_coin, small_coin])
+            .build(ctx, &constants)?;
+
+        assert_eq!(tx.coin_spends.len(), 2);
+        assert_eq!(tx.required_signatures.len(), 2);
+        assert!(tx
+            .required_signatures
+            .iter()
+            .all(|required| required.public_key() == pk));
+
+        let change = tx.change.expect("missing change");
+        assert_eq!(change.puzzle_hash, puzzle_hash);
+        assert_eq!(change.amount, 200);
+
+        sim.spend_coins(tx.coin_spends, &[sk])?;
+
+        assert!(sim.coin_state(change.coin_id()).is_some());
+        assert!(sim
+            .coin_state(small_coin.coin_id())
+            .unwrap()
+            .spent_height
+            .is_none());
+        assert_eq!(sim.hinted_coins(recipient).len(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_transaction_builder_exact() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let constants = AggSigConstants::from(&sim.config().constants);
+        let ctx = &mut SpendContext::new();
+
+        let (sk, pk, _puzzle_hash, coin) = sim.new_p2(1000)?;
+
+        let tx = TransactionBuilder::new()
+            .pay(Bytes32::new([1; 32]), 1000, Vec::new())
+            .spendable_coins(StandardLayer::new(pk), [coin])
+            .build(ctx, &constants)?;
+
+        assert_eq!(tx.coin_spends.len(), 1);
+        assert_eq!(tx.change, None);
+
+        sim.spend_coins(tx.coin_spends, &[sk])?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_transaction_builder_partial_spend() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let constants = AggSigConstants::from(&sim.config().constants);
+        let ctx = &mut SpendContext::new();
+
+        let (sk, pk, puzzle_hash, coin) = sim.new_p2(600)?;
+        let other_coin = sim.new_coin(puzzle_hash, 600);
+
+        let mut tx = TransactionBuilder::new()
+            .pay(puzzle_hash, 1000, Vec::new())
+            .spendable_coins(StandardLayer::new(pk), [coin, other_coin])
+            .build(ctx, &constants)?;
+
+        // The remaining coin asserts an announcement from the removed coin.
+        tx.coin_spends.remove(0);
+        assert!(sim.spend_coins(tx.coin_spends, &[sk]).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_transaction_builder_insufficient_balance() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let constants = AggSigConstants::from(&sim.config().constants);
+        let ctx = &mut SpendContext::new();
+
+        let (_sk, pk, _puzzle_hash, coin) = sim.new_p2(1000)?;
+
+        let result = TransactionBuilder::new()
+            .pay(Bytes32::new([1; 32]), 1000, Vec::new())
+            .fee(1)
+            .spendable_coins(StandardLayer::new(pk), [coin])
+            .build(ctx, &constants);
+
+        assert!(matches!(
+            result,
+            Err(DriverError::CoinSelection(
+                CoinSelectionError::InsufficientBalance(1000)
+            ))
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_transaction_builder_duplicate_coins() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let constants = AggSigConstants::from(&sim.config().constants);
+        let ctx = &mut SpendContext::new();
+
+        let (sk, pk, puzzle_hash, coin) = sim.new_p2(1000)?;
+        let p2 = StandardLayer::new(pk);
+
+        let (issue_cat, cat) = Cat::single_issuance_eve(
+            ctx,
+            coin.coin_id(),
+            1000,
+            Conditions::new().create_coin(puzzle_hash, 1000, vec![puzzle_hash.into()]),
+        )?;
+        p2.spend(ctx, coin, issue_cat)?;
+        let cat = cat.wrapped_child(puzzle_hash, 1000);
+
+        sim.spend_coins(ctx.take(), &[sk.clone()])?;
+
+        let xch_coin = sim.new_coin(puzzle_hash, 600);
+
+        // Each coin can only be selected once, no matter how many times it's added.
+        let builder = TransactionBuilder::new()
+            .spendable_coins(p2, [xch_coin, xch_coin])
+            .spendable_coins(p2, [xch_coin])
+            .spendable_cats(p2, [cat, cat])
+            .spendable_cats(p2, [cat]);
+
+        assert!(matches!(
+            builder
+                .clone()
+                .pay(puzzle_hash, 1000, Vec::new())
+                .build(ctx, &constants),
+            Err(DriverError::CoinSelection(
+                CoinSelectionError::InsufficientBalance(600)
+            ))
+        ));
+
+        assert!(matches!(
+            builder
+                .clone()
+                .pay_cat(cat.asset_id, puzzle_hash, 1500, Vec::new())
+                .build(ctx, &constants),
+            Err(DriverError::CoinSelection(
+                CoinSelectionError::InsufficientBalance(1000)
+            ))
+        ));
+
+        let tx = builder
+            .pay(puzzle_hash, 600, Vec::new())
+            .pay_cat(cat.asset_id, puzzle_hash, 1000, Vec::new())
+            .build(ctx, &constants)?;
+        assert_eq!(tx.coin_spends.len(), 2);
+
+        sim.spend_coins(tx.coin_spends, &[sk])?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_transaction_builder_multi_asset() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let constants = AggSigConstants::from(&sim.config().constants);
+        let ctx = &mut SpendContext::new();
+
+        let (sk, pk, puzzle_hash, coin) = sim.new_p2(1000)?;
+        let p2 = StandardLayer::new(pk);
+
+        let (issue_cat, cat) = Cat::single_issuance_eve(
+            ctx,
+            coin.coin_id(),
+            1000,
+            Conditions::new().create_coin(puzzle_hash, 1000, vec![puzzle_hash.into()]),
+        )?;
+        p2.spend(ctx, coin, issue_cat)?;
+        let cat = cat.wrapped_child(puzzle_hash, 1000);
+
+        let did_coin = sim.new_coin(puzzle_hash, 2);
+        let (create_did, did) = Launcher::new(did_coin.coin_id(), 1).create_simple_did(ctx, &p2)?;
+        p2.spend(ctx, did_coin, create_did)?;
+
+        let mint = NftMint::new(
+            HashedPtr::NIL,
+            puzzle_hash,
+            300,
+            Some(DidOwner::from_did_info(&did.info)),
+        );
+        let (mint_nft, nft) = IntermediateLauncher::new(did.coin.coin_id(), 0, 1)
+            .create(ctx)?
+            .mint_nft(ctx, mint)?;
+        let _did = did.update(ctx, &p2, mint_nft)?;
+
+        sim.spend_coins(ctx.take(), &[sk.clone()])?;
+
+        let xch_coin = sim.new_coin(puzzle_hash, 100);
+
+        let xch_recipient = Bytes32::new([1; 32]);
+        let cat_recipient = Bytes32::new([2; 32]);
+        let nft_recipient = Bytes32::new([3; 32]);
+
+        let tx = TransactionBuilder::new()
+            .pay(xch_recipient, 5, vec![xch_recipient.into()])
+            .pay_cat(cat.asset_id, cat_recipient, 100, vec![cat_recipient.into()])
+            .send_nft(nft, p2, nft_recipient)
+            .fee(1)
+            .spendable_coins(p2, [xch_coin])
+            .spendable_cats(p2, [cat])
+            .build(ctx, &constants)?;
+
+        assert_eq!(tx.coin_spends.len(), 3);
+        assert_eq!(tx.change.map(|coin| coin.amount), Some(94));
+        assert_eq!(tx.cat_change.len(), 1);
+        assert_eq!(tx.cat_change[0].coin.amount, 900);
+        assert_eq!(tx.nfts[0].info.p2_puzzle_hash, nft_recipient);
+        assert_eq!(tx.nfts[0].info.current_owner, None);
+
+        sim.spend_coins(tx.coin_spends, &[sk])?;
+
+        assert_eq!(sim.hinted_coins(xch_recipient).len(), 1);
+        assert_eq!(sim.hinted_coins(cat_recipient).len(), 1);
+        assert!(sim.coin_state(tx.cat_change[0].coin.coin_id()).is_some());
+        assert!(sim.coin_state(tx.nfts[0].coin.coin_id()).is_some());
+
+        Ok(())
+    }
+}
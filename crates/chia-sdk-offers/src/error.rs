@@ -1,5 +1,7 @@
 use std::{array::TryFromSliceError, io, num::TryFromIntError};
 
+use chia_protocol::Bytes32;
+use chia_sdk_driver::DriverError;
 use clvm_traits::{FromClvmError, ToClvmError};
 use thiserror::Error;
 
@@ -46,4 +48,10 @@ pub enum OfferError {
 
     #[error("Requested payment puzzle mismatch")]
     PuzzleMismatch,
+
+    #[error("Driver error: {0}")]
+    Driver(#[from] DriverError),
+
+    #[error("Missing royalty payment for NFT {0}")]
+    MissingRoyalty(Bytes32),
 }
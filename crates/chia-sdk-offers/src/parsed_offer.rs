@@ -1,10 +1,12 @@
 use chia_bls::Signature;
 use chia_protocol::{Bytes32, CoinSpend};
 use chia_puzzles::offer::NotarizedPayment;
-use chia_sdk_driver::Puzzle;
+use chia_sdk_driver::{Puzzle, RoyaltyPayment};
+use clvm_traits::ToClvm;
+use clvmr::Allocator;
 use indexmap::IndexMap;
 
-use crate::{OfferBuilder, Take};
+use crate::{OfferBuilder, OfferError, Take};
 
 #[derive(Debug, Default, Clone)]
 pub struct ParsedOffer {
@@ -17,4 +19,48 @@ impl ParsedOffer {
     pub fn take(self) -> OfferBuilder<Take> {
         OfferBuilder::from_parsed_offer(self)
     }
+
+    /// Returns the royalties required by the NFTs being offered, based on the trade prices they're transferred with.
+    pub fn royalty_payments(
+        &self,
+        allocator: &mut Allocator,
+    ) -> Result<Vec<RoyaltyPayment>, OfferError> {
+        let mut royalty_payments = Vec::new();
+
+        for coin_spend in &self.coin_spends {
+            let puzzle = coin_spend.puzzle_reveal.to_clvm(allocator)?;
+            let puzzle = Puzzle::parse(allocator, puzzle);
+            let solution = coin_spend.solution.to_clvm(allocator)?;
+
+            if let Some(payments) = RoyaltyPayment::parse_nft_spend(allocator, puzzle, solution)? {
+                royalty_payments.extend(payments);
+            }
+        }
+
+        Ok(royalty_payments)
+    }
+
+    /// Checks that the royalty for every NFT being offered is included in the requested payments,
+    /// so that the royalties are paid when the offer is taken. Returns the royalty payments.
+    pub fn verify_royalties(
+        &self,
+        allocator: &mut Allocator,
+    ) -> Result<Vec<RoyaltyPayment>, OfferError> {
+        let royalty_payments = self.royalty_payments(allocator)?;
+
+        for royalty_payment in &royalty_payments {
+            let is_requested = self
+                .requested_payments
+                .get(&royalty_payment.settlement_puzzle_hash)
+                .is_some_and(|(_, notarized_payments)| {
+                    notarized_payments.contains(&royalty_payment.notarized_payment())
+                });
+
+            if !is_requested {
+                return Err(OfferError::MissingRoyalty(royalty_payment.launcher_id));
+            }
+        }
+
+        Ok(royalty_payments)
+    }
 }
@@ -0,0 +1,113 @@
+use chia_protocol::{Bytes32, SpendBundle};
+use chia_puzzles::{nft::NftMetadata, offer::Payment};
+use chia_sdk_driver::{
+    calculate_royalty_payments, calculate_trade_prices, CatLayer, Launcher, Layer, NftMint,
+    SpendContext, StandardLayer, TradeAmount,
+};
+use chia_sdk_offers::{Offer, OfferBuilder, OfferError};
+use chia_sdk_test::{sign_transaction, Simulator};
+use chia_sdk_types::Conditions;
+
+#[test]
+fn test_verify_multi_asset_royalties() -> anyhow::Result<()> {
+    let mut sim = Simulator::new();
+    let mut ctx = SpendContext::new();
+
+    let (alice_secret_key, alice_pk, alice_puzzle_hash, alice_coin) = sim.child_p2(1, 1)?;
+    let alice = StandardLayer::new(alice_pk);
+
+    let royalty_puzzle_hash = Bytes32::new([1; 32]);
+
+    let (conditions, nft) = Launcher::new(alice_coin.coin_id(), 1).mint_nft(
+        &mut ctx,
+        NftMint::new(NftMetadata::default(), alice_puzzle_hash, 500, None)
+            .with_royalty_puzzle_hash(royalty_puzzle_hash),
+    )?;
+    alice.spend(&mut ctx, alice_coin, conditions)?;
+
+    sim.spend_coins(ctx.take(), &[alice_secret_key.clone()])?;
+
+    // The NFT is offered for both XCH and a CAT.
+    let asset_id = Bytes32::new([2; 32]);
+    let trade_prices = calculate_trade_prices(
+        &[
+            TradeAmount::xch(1_000_000),
+            TradeAmount::cat(asset_id, 10_000),
+        ],
+        1,
+    )?;
+    let royalty_payments =
+        calculate_royalty_payments(&[nft.info.royalty_transfer_layer()], &trade_prices)?;
+
+    let settlement = ctx.settlement_payments_puzzle()?;
+    let cat_settlement = CatLayer::new(asset_id, settlement).construct_puzzle(&mut ctx)?;
+
+    let nonce = Offer::nonce(vec![nft.coin.coin_id()]);
+
+    let make_offer = |ctx: &mut SpendContext, include_cat_royalty: bool| -> anyhow::Result<Offer> {
+        let mut builder = OfferBuilder::new(nonce)
+            .request(
+                ctx,
+                &settlement,
+                vec![Payment::new(alice_puzzle_hash, 1_000_000)],
+            )?
+            .request(
+                ctx,
+                &cat_settlement,
+                vec![Payment::with_memos(
+                    alice_puzzle_hash,
+                    10_000,
+                    vec![alice_puzzle_hash.into()],
+                )],
+            )?;
+
+        for royalty_payment in &royalty_payments {
+            let is_cat =
+                royalty_payment.settlement_puzzle_hash == ctx.tree_hash(cat_settlement).into();
+
+            if is_cat && !include_cat_royalty {
+                continue;
+            }
+
+            let puzzle = if is_cat { cat_settlement } else { settlement };
+            let notarized_payment = royalty_payment.notarized_payment();
+
+            builder = builder.request_with_nonce(
+                ctx,
+                &puzzle,
+                notarized_payment.nonce,
+                notarized_payment.payments,
+            )?;
+        }
+
+        let (assertions, builder) = builder.finish();
+
+        let _nft = nft.clone().lock_settlement(
+            ctx,
+            &alice,
+            trade_prices.clone(),
+            Conditions::new().extend(assertions),
+        )?;
+
+        let coin_spends = ctx.take();
+        let signature = sign_transaction(&coin_spends, &[alice_secret_key.clone()])?;
+
+        Ok(builder.bundle(ctx, SpendBundle::new(coin_spends, signature))?)
+    };
+
+    let offer = make_offer(&mut ctx, true)?;
+    let parsed = offer.parse(&mut ctx.allocator)?;
+    assert_eq!(
+        parsed.verify_royalties(&mut ctx.allocator)?,
+        royalty_payments
+    );
+
+    let offer = make_offer(&mut ctx, false)?;
+    let parsed = offer.parse(&mut ctx.allocator)?;
+    assert!(matches!(
+        parsed.verify_royalties(&mut ctx.allocator),
+        Err(OfferError::MissingRoyalty(launcher_id)) if launcher_id == nft.info.launcher_id
+    ));
+
+    Ok(())
+}
@@ -0,0 +1,886 @@
+use std::fmt;
+
+use chia_consensus::gen::validation_error::ErrorCode;
+use chia_protocol::{Bytes32, Coin, CoinSpend, Program};
+use chia_sdk_types::{announcement_id, Condition};
+use clvm_traits::{FromClvm, ToClvm};
+use clvm_utils::tree_hash;
+use clvmr::{reduction::Reduction, run_program, Allocator, ChiaDialect, NodePtr};
+use serde_json::{json, Value};
+
+/// A structured breakdown of what each coin spend in a bundle does, and how the spends depend on
+/// each other. This is meant to make it easier to figure out why a spend bundle is invalid.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BundleReport {
+    pub spends: Vec<SpendReport>,
+    pub assertions: Vec<AssertionReport>,
+    /// Announcements and messages which were created, but never asserted or received.
+    pub unasserted: Vec<AssertionReport>,
+    pub removal_amount: u128,
+    pub addition_amount: u128,
+    pub reserved_fee: u128,
+}
+
+/// The output of running a single coin spend.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpendReport {
+    pub coin: Coin,
+    /// Whether the tree hash of the puzzle reveal matches the coin's puzzle hash.
+    pub puzzle_hash_matches: bool,
+    /// The cost of running the puzzle, excluding the cost of the conditions.
+    pub cost: u64,
+    pub conditions: Vec<ReportedCondition>,
+    /// The coins created by this spend.
+    pub additions: Vec<Coin>,
+    /// Why the puzzle couldn't be run, if it failed.
+    pub error: Option<String>,
+}
+
+/// A condition output by a spend, and its decoded form if it's a known condition.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReportedCondition {
+    pub raw: Program,
+    pub decoded: Option<Condition<Program>>,
+}
+
+/// A condition that depends on another spend in the bundle, and the spend which satisfies it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssertionReport {
+    pub kind: AssertionKind,
+    pub spend_index: usize,
+    pub condition_index: usize,
+    /// The index of the spend which satisfies the assertion, if there is one.
+    pub matched_by: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AssertionKind {
+    CoinAnnouncement(Bytes32),
+    PuzzleAnnouncement(Bytes32),
+    SendMessage { mode: u8, message: Program },
+    ReceiveMessage { mode: u8, message: Program },
+    ConcurrentSpend(Bytes32),
+    ConcurrentPuzzle(Bytes32),
+}
+
+/// The spend and condition that most likely caused a validation error.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Failure {
+    pub error: ErrorCode,
+    pub spend_index: Option<usize>,
+    pub condition_index: Option<usize>,
+    pub reason: String,
+}
+
+impl BundleReport {
+    /// Runs each coin spend and matches up the conditions between them.
+    pub fn new(coin_spends: &[CoinSpend]) -> Self {
+        let mut allocator = Allocator::new();
+
+        let spends: Vec<SpendReport> = coin_spends
+            .iter()
+            .map(|coin_spend| SpendReport::new(&mut allocator, coin_spend))
+            .collect();
+
+        let mut assertions = Vec::new();
+        let mut unasserted = Vec::new();
+
+        for (spend_index, spend) in spends.iter().enumerate() {
+            for (condition_index, condition) in spend.conditions.iter().enumerate() {
+                let Some(decoded) = &condition.decoded else {
+                    continue;
+                };
+
+                let (kind, matched_by) = match decoded {
+                    Condition::AssertCoinAnnouncement(cond) => (
+                        AssertionKind::CoinAnnouncement(cond.announcement_id),
+                        find_spend(&spends, |coin, decoded| {
+                            decoded
+                                .as_create_coin_announcement()
+                                .is_some_and(|created| {
+                                    announcement_id(coin.coin_id(), created.message.clone())
+                                        == cond.announcement_id
+                                })
+                        }),
+                    ),
+                    Condition::AssertPuzzleAnnouncement(cond) => (
+                        AssertionKind::PuzzleAnnouncement(cond.announcement_id),
+                        find_spend(&spends, |coin, decoded| {
+                            decoded
+                                .as_create_puzzle_announcement()
+                                .is_some_and(|created| {
+                                    announcement_id(coin.puzzle_hash, created.message.clone())
+                                        == cond.announcement_id
+                                })
+                        }),
+                    ),
+                    Condition::ReceiveMessage(cond) => (
+                        AssertionKind::ReceiveMessage {
+                            mode: cond.mode,
+                            message: bytes_program(&mut allocator, &cond.message),
+                        },
+                        find_spend(&spends, |coin, decoded| {
+                            decoded.as_send_message().is_some_and(|sent| {
+                                sent.mode == cond.mode
+                                    && sent.message == cond.message
+                                    && commitment(&mut Allocator::new(), cond.mode >> 3, *coin)
+                                        == cond.data
+                                    && commitment(&mut Allocator::new(), cond.mode, spend.coin)
+                                        == sent.data
+                            })
+                        }),
+                    ),
+                    Condition::AssertConcurrentSpend(cond) => (
+                        AssertionKind::ConcurrentSpend(cond.coin_id),
+                        spends
+                            .iter()
+                            .position(|spend| spend.coin.coin_id() == cond.coin_id),
+                    ),
+                    Condition::AssertConcurrentPuzzle(cond) => (
+                        AssertionKind::ConcurrentPuzzle(cond.puzzle_hash),
+                        spends
+                            .iter()
+                            .position(|spend| spend.coin.puzzle_hash == cond.puzzle_hash),
+                    ),
+                    _ => {
+                        if let Some(kind) = unasserted_kind(&mut allocator, &spends, spend, decoded)
+                        {
+                            unasserted.push(AssertionReport {
+                                kind,
+                                spend_index,
+                                condition_index,
+                                matched_by: None,
+                            });
+                        }
+                        continue;
+                    }
+                };
+
+                assertions.push(AssertionReport {
+                    kind,
+                    spend_index,
+                    condition_index,
+                    matched_by,
+                });
+            }
+        }
+
+        let removal_amount = spends
+            .iter()
+            .map(|spend| u128::from(spend.coin.amount))
+            .sum();
+
+        let addition_amount = spends
+            .iter()
+            .flat_map(|spend| &spend.additions)
+            .map(|coin| u128::from(coin.amount))
+            .sum();
+
+        let reserved_fee = spends
+            .iter()
+            .flat_map(|spend| &spend.conditions)
+            .filter_map(|condition| condition.decoded.as_ref()?.as_reserve_fee())
+            .map(|reserve_fee| u128::from(reserve_fee.amount))
+            .sum();
+
+        Self {
+            spends,
+            assertions,
+            unasserted,
+            removal_amount,
+            addition_amount,
+            reserved_fee,
+        }
+    }
+
+    /// The difference between the amounts spent and created, if it isn't negative.
+    pub fn fee(&self) -> Option<u128> {
+        self.removal_amount.checked_sub(self.addition_amount)
+    }
+
+    /// The assertions which aren't satisfied by any spend in the bundle.
+    pub fn unmatched(&self) -> impl Iterator<Item = &AssertionReport> {
+        self.assertions
+            .iter()
+            .filter(|assertion| assertion.matched_by.is_none())
+    }
+
+    /// Finds the spend and condition which most likely caused the given validation error.
+    pub fn diagnose(&self, error: ErrorCode) -> Failure {
+        let failure = |spend_index, condition_index, reason: String| Failure {
+            error,
+            spend_index,
+            condition_index,
+            reason,
+        };
+
+        let unmatched = |matches: fn(&AssertionKind) -> bool| {
+            self.unmatched().find(|assertion| matches(&assertion.kind))
+        };
+
+        let found = match error {
+            ErrorCode::AssertCoinAnnouncementFailed => {
+                unmatched(|kind| matches!(kind, AssertionKind::CoinAnnouncement(..)))
+            }
+            ErrorCode::AssertPuzzleAnnouncementFailed => {
+                unmatched(|kind| matches!(kind, AssertionKind::PuzzleAnnouncement(..)))
+            }
+            ErrorCode::AssertConcurrentSpendFailed => {
+                unmatched(|kind| matches!(kind, AssertionKind::ConcurrentSpend(..)))
+            }
+            ErrorCode::AssertConcurrentPuzzleFailed => {
+                unmatched(|kind| matches!(kind, AssertionKind::ConcurrentPuzzle(..)))
+            }
+            ErrorCode::MessageNotSentOrReceived => {
+                unmatched(|kind| matches!(kind, AssertionKind::ReceiveMessage { .. })).or_else(
+                    || {
+                        self.unasserted.iter().find(|assertion| {
+                            matches!(assertion.kind, AssertionKind::SendMessage { .. })
+                        })
+                    },
+                )
+            }
+            _ => None,
+        };
+
+        if let Some(assertion) = found {
+            return failure(
+                Some(assertion.spend_index),
+                Some(assertion.condition_index),
+                format!("{} is not satisfied by any spend", assertion.kind),
+            );
+        }
+
+        if let Some((spend_index, spend)) = self
+            .spends
+            .iter()
+            .enumerate()
+            .find(|(_, spend)| spend.error.is_some())
+        {
+            return failure(
+                Some(spend_index),
+                None,
+                format!(
+                    "the puzzle failed to run: {}",
+                    spend.error.as_deref().unwrap_or_default()
+                ),
+            );
+        }
+
+        match error {
+            ErrorCode::WrongPuzzleHash => {
+                if let Some(spend_index) = self
+                    .spends
+                    .iter()
+                    .position(|spend| !spend.puzzle_hash_matches)
+                {
+                    return failure(
+                        Some(spend_index),
+                        None,
+                        "the puzzle reveal doesn't match the coin's puzzle hash".to_string(),
+                    );
+                }
+            }
+            ErrorCode::MintingCoin => {
+                return failure(
+                    None,
+                    None,
+                    format!(
+                        "the bundle creates {} mojos, but only spends {}",
+                        self.addition_amount, self.removal_amount
+                    ),
+                );
+            }
+            ErrorCode::ReserveFeeConditionFailed => {
+                let position = self.find_condition(|_, condition| condition.is_reserve_fee());
+
+                return failure(
+                    position.map(|(spend_index, _)| spend_index),
+                    position.map(|(_, condition_index)| condition_index),
+                    format!(
+                        "the bundle reserves {} mojos, but the fee is {}",
+                        self.reserved_fee,
+                        self.removal_amount.saturating_sub(self.addition_amount)
+                    ),
+                );
+            }
+            ErrorCode::DoubleSpend => {
+                if let Some(spend_index) = self.spends.iter().enumerate().position(|(i, spend)| {
+                    self.spends[..i]
+                        .iter()
+                        .any(|other| other.coin == spend.coin)
+                }) {
+                    return failure(
+                        Some(spend_index),
+                        None,
+                        "the coin is spent more than once".to_string(),
+                    );
+                }
+            }
+            ErrorCode::DuplicateOutput => {
+                if let Some((spend_index, spend)) =
+                    self.spends.iter().enumerate().find(|(_, spend)| {
+                        spend
+                            .additions
+                            .iter()
+                            .enumerate()
+                            .any(|(i, coin)| spend.additions[..i].contains(coin))
+                    })
+                {
+                    let condition_index =
+                        spend
+                            .conditions
+                            .iter()
+                            .enumerate()
+                            .position(|(i, condition)| {
+                                let Some(create_coin) = condition
+                                    .decoded
+                                    .as_ref()
+                                    .and_then(Condition::as_create_coin)
+                                else {
+                                    return false;
+                                };
+
+                                spend.conditions[..i].iter().any(|other| {
+                                    other
+                                        .decoded
+                                        .as_ref()
+                                        .and_then(Condition::as_create_coin)
+                                        .is_some_and(|other| {
+                                            other.puzzle_hash == create_coin.puzzle_hash
+                                                && other.amount == create_coin.amount
+                                        })
+                                })
+                            });
+
+                    return failure(
+                        Some(spend_index),
+                        condition_index,
+                        "the same coin is created more than once".to_string(),
+                    );
+                }
+            }
+            ErrorCode::AssertMyCoinIdFailed
+            | ErrorCode::AssertMyParentIdFailed
+            | ErrorCode::AssertMyPuzzleHashFailed
+            | ErrorCode::AssertMyAmountFailed => {
+                let position = self.find_condition(|coin, condition| match condition {
+                    Condition::AssertMyCoinId(cond) => cond.coin_id != coin.coin_id(),
+                    Condition::AssertMyParentId(cond) => cond.parent_id != coin.parent_coin_info,
+                    Condition::AssertMyPuzzleHash(cond) => cond.puzzle_hash != coin.puzzle_hash,
+                    Condition::AssertMyAmount(cond) => cond.amount != coin.amount,
+                    _ => false,
+                });
+
+                if let Some((spend_index, condition_index)) = position {
+                    return failure(
+                        Some(spend_index),
+                        Some(condition_index),
+                        "the condition doesn't match the coin being spent".to_string(),
+                    );
+                }
+            }
+            _ => {
+                if let Some((spend_index, condition_index)) =
+                    self.find_condition(|_, condition| triggers(error, condition))
+                {
+                    return failure(
+                        Some(spend_index),
+                        Some(condition_index),
+                        "the condition isn't satisfied".to_string(),
+                    );
+                }
+            }
+        }
+
+        failure(
+            None,
+            None,
+            "the error couldn't be traced to a specific spend".to_string(),
+        )
+    }
+
+    /// Renders the report as JSON, with hex encoded bytes.
+    pub fn to_json(&self) -> Value {
+        json!({
+            "spends": self.spends.iter().map(SpendReport::to_json).collect::<Vec<_>>(),
+            "assertions": self.assertions.iter().map(AssertionReport::to_json).collect::<Vec<_>>(),
+            "unasserted": self.unasserted.iter().map(AssertionReport::to_json).collect::<Vec<_>>(),
+            "removal_amount": self.removal_amount.to_string(),
+            "addition_amount": self.addition_amount.to_string(),
+            "reserved_fee": self.reserved_fee.to_string(),
+        })
+    }
+
+    fn find_condition(
+        &self,
+        predicate: impl Fn(&Coin, &Condition<Program>) -> bool,
+    ) -> Option<(usize, usize)> {
+        self.spends
+            .iter()
+            .enumerate()
+            .find_map(|(spend_index, spend)| {
+                spend
+                    .conditions
+                    .iter()
+                    .position(|condition| {
+                        condition
+                            .decoded
+                            .as_ref()
+                            .is_some_and(|decoded| predicate(&spend.coin, decoded))
+                    })
+                    .map(|condition_index| (spend_index, condition_index))
+            })
+    }
+}
+
+impl SpendReport {
+    fn new(allocator: &mut Allocator, coin_spend: &CoinSpend) -> Self {
+        let mut report = Self {
+            coin: coin_spend.coin,
+            puzzle_hash_matches: false,
+            cost: 0,
+            conditions: Vec::new(),
+            additions: Vec::new(),
+            error: None,
+        };
+
+        if let Err(error) = report.run(allocator, coin_spend) {
+            report.error = Some(error);
+        }
+
+        report
+    }
+
+    fn run(&mut self, allocator: &mut Allocator, coin_spend: &CoinSpend) -> Result<(), String> {
+        let puzzle = coin_spend
+            .puzzle_reveal
+            .to_clvm(allocator)
+            .map_err(|error| error.to_string())?;
+
+        let solution = coin_spend
+            .solution
+            .to_clvm(allocator)
+            .map_err(|error| error.to_string())?;
+
+        self.puzzle_hash_matches =
+            tree_hash(allocator, puzzle) == coin_spend.coin.puzzle_hash.into();
+
+        let Reduction(cost, output) = run_program(
+            allocator,
+            &ChiaDialect::new(0),
+            puzzle,
+            solution,
+            11_000_000_000,
+        )
+        .map_err(|error| format!("{error:?}"))?;
+
+        self.cost = cost;
+
+        let conditions =
+            Vec::<NodePtr>::from_clvm(allocator, output).map_err(|error| error.to_string())?;
+
+        for condition in conditions {
+            let raw =
+                Program::from_clvm(allocator, condition).map_err(|error| error.to_string())?;
+            let decoded = Condition::<Program>::from_clvm(allocator, condition).ok();
+
+            if let Some(create_coin) = decoded.as_ref().and_then(Condition::as_create_coin) {
+                self.additions.push(Coin::new(
+                    self.coin.coin_id(),
+                    create_coin.puzzle_hash,
+                    create_coin.amount,
+                ));
+            }
+
+            self.conditions.push(ReportedCondition { raw, decoded });
+        }
+
+        Ok(())
+    }
+
+    fn to_json(&self) -> Value {
+        json!({
+            "coin_id": hex::encode(self.coin.coin_id()),
+            "parent_coin_info": hex::encode(self.coin.parent_coin_info),
+            "puzzle_hash": hex::encode(self.coin.puzzle_hash),
+            "amount": self.coin.amount,
+            "puzzle_hash_matches": self.puzzle_hash_matches,
+            "cost": self.cost,
+            "conditions": self.conditions.iter().map(|condition| json!({
+                "raw": hex::encode(condition.raw.as_ref()),
+                "decoded": condition.decoded.as_ref().map(|decoded| format!("{decoded:?}")),
+            })).collect::<Vec<_>>(),
+            "additions": self.additions.iter().map(|coin| json!({
+                "coin_id": hex::encode(coin.coin_id()),
+                "puzzle_hash": hex::encode(coin.puzzle_hash),
+                "amount": coin.amount,
+            })).collect::<Vec<_>>(),
+            "error": self.error,
+        })
+    }
+}
+
+impl AssertionReport {
+    fn to_json(&self) -> Value {
+        json!({
+            "kind": self.kind.to_string(),
+            "spend_index": self.spend_index,
+            "condition_index": self.condition_index,
+            "matched_by": self.matched_by,
+        })
+    }
+}
+
+impl fmt::Display for BundleReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (spend_index, spend) in self.spends.iter().enumerate() {
+            writeln!(
+                f,
+                "spend {spend_index}: coin {} with amount {}, cost {}",
+                hex::encode(spend.coin.coin_id()),
+                spend.coin.amount,
+                spend.cost
+            )?;
+
+            if !spend.puzzle_hash_matches {
+                writeln!(f, "  puzzle reveal doesn't match the puzzle hash")?;
+            }
+
+            if let Some(error) = &spend.error {
+                writeln!(f, "  failed to run: {error}")?;
+            }
+
+            for (condition_index, condition) in spend.conditions.iter().enumerate() {
+                match &condition.decoded {
+                    Some(decoded) => writeln!(f, "  condition {condition_index}: {decoded:?}")?,
+                    None => writeln!(
+                        f,
+                        "  condition {condition_index}: unknown {}",
+                        hex::encode(condition.raw.as_ref())
+                    )?,
+                }
+            }
+        }
+
+        for assertion in &self.assertions {
+            match assertion.matched_by {
+                Some(matched_by) => writeln!(
+                    f,
+                    "spend {} condition {}: {} satisfied by spend {matched_by}",
+                    assertion.spend_index, assertion.condition_index, assertion.kind
+                )?,
+                None => writeln!(
+                    f,
+                    "spend {} condition {}: {} is not satisfied",
+                    assertion.spend_index, assertion.condition_index, assertion.kind
+                )?,
+            }
+        }
+
+        for created in &self.unasserted {
+            writeln!(
+                f,
+                "spend {} condition {}: {} is never asserted",
+                created.spend_index, created.condition_index, created.kind
+            )?;
+        }
+
+        write!(
+            f,
+            "spent {} mojos, created {} mojos, reserved {} mojos",
+            self.removal_amount, self.addition_amount, self.reserved_fee
+        )
+    }
+}
+
+impl fmt::Display for AssertionKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::CoinAnnouncement(id) => write!(f, "coin announcement {}", hex::encode(id)),
+            Self::PuzzleAnnouncement(id) => write!(f, "puzzle announcement {}", hex::encode(id)),
+            Self::SendMessage { mode, message } => write!(
+                f,
+                "sent message {} with mode {mode:#o}",
+                hex::encode(message.as_ref())
+            ),
+            Self::ReceiveMessage { mode, message } => write!(
+                f,
+                "received message {} with mode {mode:#o}",
+                hex::encode(message.as_ref())
+            ),
+            Self::ConcurrentSpend(coin_id) => {
+                write!(f, "concurrent spend of {}", hex::encode(coin_id))
+            }
+            Self::ConcurrentPuzzle(puzzle_hash) => {
+                write!(f, "concurrent puzzle {}", hex::encode(puzzle_hash))
+            }
+        }
+    }
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self.error)?;
+
+        if let Some(spend_index) = self.spend_index {
+            write!(f, " in spend {spend_index}")?;
+        }
+
+        if let Some(condition_index) = self.condition_index {
+            write!(f, " at condition {condition_index}")?;
+        }
+
+        write!(f, ": {}", self.reason)
+    }
+}
+
+/// Whether the condition is one that can fail with the given error, such as a timelock.
+pub(crate) fn triggers(error: ErrorCode, condition: &Condition<Program>) -> bool {
+    match error {
+        ErrorCode::AssertHeightAbsoluteFailed => condition.is_assert_height_absolute(),
+        ErrorCode::AssertHeightRelativeFailed => condition.is_assert_height_relative(),
+        ErrorCode::AssertSecondsAbsoluteFailed => condition.is_assert_seconds_absolute(),
+        ErrorCode::AssertSecondsRelativeFailed => condition.is_assert_seconds_relative(),
+        ErrorCode::AssertBeforeHeightAbsoluteFailed => condition.is_assert_before_height_absolute(),
+        ErrorCode::AssertBeforeHeightRelativeFailed => condition.is_assert_before_height_relative(),
+        ErrorCode::AssertBeforeSecondsAbsoluteFailed => {
+            condition.is_assert_before_seconds_absolute()
+        }
+        ErrorCode::AssertBeforeSecondsRelativeFailed => {
+            condition.is_assert_before_seconds_relative()
+        }
+        ErrorCode::AssertMyBirthHeightFailed => condition.is_assert_my_birth_height(),
+        ErrorCode::AssertMyBirthSecondsFailed => condition.is_assert_my_birth_seconds(),
+        ErrorCode::AssertEphemeralFailed => condition.is_assert_ephemeral(),
+        ErrorCode::EphemeralRelativeCondition => {
+            condition.is_assert_height_relative()
+                || condition.is_assert_seconds_relative()
+                || condition.is_assert_before_height_relative()
+                || condition.is_assert_before_seconds_relative()
+                || condition.is_assert_my_birth_height()
+                || condition.is_assert_my_birth_seconds()
+        }
+        ErrorCode::ReserveFeeConditionFailed => condition.is_reserve_fee(),
+        ErrorCode::AssertMyCoinIdFailed => condition.is_assert_my_coin_id(),
+        ErrorCode::AssertMyParentIdFailed => condition.is_assert_my_parent_id(),
+        ErrorCode::AssertMyPuzzleHashFailed => condition.is_assert_my_puzzle_hash(),
+        ErrorCode::AssertMyAmountFailed => condition.is_assert_my_amount(),
+        ErrorCode::AssertCoinAnnouncementFailed => condition.is_assert_coin_announcement(),
+        ErrorCode::AssertPuzzleAnnouncementFailed => condition.is_assert_puzzle_announcement(),
+        ErrorCode::AssertConcurrentSpendFailed => condition.is_assert_concurrent_spend(),
+        ErrorCode::AssertConcurrentPuzzleFailed => condition.is_assert_concurrent_puzzle(),
+        ErrorCode::MessageNotSentOrReceived => {
+            condition.is_send_message() || condition.is_receive_message()
+        }
+        ErrorCode::BadAggregateSignature => condition.is_agg_sig(),
+        _ => false,
+    }
+}
+
+fn find_spend(
+    spends: &[SpendReport],
+    mut predicate: impl FnMut(&Coin, &Condition<Program>) -> bool,
+) -> Option<usize> {
+    spends.iter().position(|spend| {
+        spend.conditions.iter().any(|condition| {
+            condition
+                .decoded
+                .as_ref()
+                .is_some_and(|decoded| predicate(&spend.coin, decoded))
+        })
+    })
+}
+
+/// Returns the kind of a created announcement or sent message that no spend asserts or receives.
+fn unasserted_kind(
+    allocator: &mut Allocator,
+    spends: &[SpendReport],
+    spend: &SpendReport,
+    decoded: &Condition<Program>,
+) -> Option<AssertionKind> {
+    let asserted = |predicate: &dyn Fn(&Coin, &Condition<Program>) -> bool| {
+        find_spend(spends, predicate).is_some()
+    };
+
+    match decoded {
+        Condition::CreateCoinAnnouncement(cond) => {
+            let id = announcement_id(spend.coin.coin_id(), cond.message.clone());
+
+            (!asserted(&|_, decoded| {
+                decoded
+                    .as_assert_coin_announcement()
+                    .is_some_and(|assertion| assertion.announcement_id == id)
+            }))
+            .then_some(AssertionKind::CoinAnnouncement(id))
+        }
+        Condition::CreatePuzzleAnnouncement(cond) => {
+            let id = announcement_id(spend.coin.puzzle_hash, cond.message.clone());
+
+            (!asserted(&|_, decoded| {
+                decoded
+                    .as_assert_puzzle_announcement()
+                    .is_some_and(|assertion| assertion.announcement_id == id)
+            }))
+            .then_some(AssertionKind::PuzzleAnnouncement(id))
+        }
+        Condition::SendMessage(cond) => {
+            let received = asserted(&|coin, decoded| {
+                decoded.as_receive_message().is_some_and(|received| {
+                    received.mode == cond.mode
+                        && received.message == cond.message
+                        && commitment(&mut Allocator::new(), cond.mode, *coin) == cond.data
+                        && commitment(&mut Allocator::new(), cond.mode >> 3, spend.coin)
+                            == received.data
+                })
+            });
+
+            (!received).then(|| AssertionKind::SendMessage {
+                mode: cond.mode,
+                message: bytes_program(allocator, &cond.message),
+            })
+        }
+        _ => None,
+    }
+}
+
+/// The arguments a message uses to commit to a coin, given the three mode bits for that side.
+fn commitment(allocator: &mut Allocator, mode: u8, coin: Coin) -> Vec<Program> {
+    let mode = mode & 0b111;
+
+    let values: Vec<NodePtr> = if mode == 0b111 {
+        vec![allocator.new_atom(&coin.coin_id()).unwrap()]
+    } else {
+        let mut values = Vec::new();
+
+        if mode & 0b100 != 0 {
+            values.push(allocator.new_atom(&coin.parent_coin_info).unwrap());
+        }
+
+        if mode & 0b010 != 0 {
+            values.push(allocator.new_atom(&coin.puzzle_hash).unwrap());
+        }
+
+        if mode & 0b001 != 0 {
+            values.push(coin.amount.to_clvm(allocator).unwrap());
+        }
+
+        values
+    };
+
+    values
+        .into_iter()
+        .map(|value| Program::from_clvm(allocator, value).unwrap())
+        .collect()
+}
+
+fn bytes_program(allocator: &mut Allocator, bytes: &[u8]) -> Program {
+    let atom = allocator.new_atom(bytes).unwrap();
+    Program::from_clvm(allocator, atom).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use chia_sdk_types::{
+        AssertCoinAnnouncement, AssertConcurrentSpend, Conditions, CreateCoin,
+        CreateCoinAnnouncement, ReceiveMessage, SendMessage,
+    };
+
+    use crate::{to_program, to_puzzle, Simulator};
+
+    use super::*;
+
+    fn spend(coin: Coin, conditions: impl ToClvm<Allocator>) -> anyhow::Result<CoinSpend> {
+        let (_, puzzle_reveal) = to_puzzle(1)?;
+        Ok(CoinSpend::new(coin, puzzle_reveal, to_program(conditions)?))
+    }
+
+    #[test]
+    fn test_bundle_report() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let (puzzle_hash, _) = to_puzzle(1)?;
+
+        let alice = sim.new_coin(puzzle_hash, 1000);
+        let bob = sim.new_coin(puzzle_hash, 500);
+
+        let report = BundleReport::new(&[
+            spend(
+                alice,
+                Conditions::new()
+                    .with(CreateCoin::new(puzzle_hash, 900, Vec::new()))
+                    .with(CreateCoinAnnouncement::new(b"hello".to_vec().into())),
+            )?,
+            spend(
+                bob,
+                Conditions::new()
+                    .with(AssertCoinAnnouncement::new(announcement_id(
+                        alice.coin_id(),
+                        b"hello",
+                    )))
+                    .with(AssertCoinAnnouncement::new(Bytes32::default()))
+                    .with(AssertConcurrentSpend::new(alice.coin_id())),
+            )?,
+        ]);
+
+        assert!(report.spends.iter().all(|spend| spend.error.is_none()));
+        assert_eq!(
+            report.spends[0].additions,
+            vec![Coin::new(alice.coin_id(), puzzle_hash, 900)]
+        );
+        assert_eq!(report.fee(), Some(600));
+
+        let matched: Vec<Option<usize>> = report
+            .assertions
+            .iter()
+            .map(|assertion| assertion.matched_by)
+            .collect();
+        assert_eq!(matched, vec![Some(0), None, Some(0)]);
+        assert!(report.unasserted.is_empty());
+
+        let failure = report.diagnose(ErrorCode::AssertCoinAnnouncementFailed);
+        assert_eq!(failure.spend_index, Some(1));
+        assert_eq!(failure.condition_index, Some(1));
+
+        let failure = report.diagnose(ErrorCode::MintingCoin);
+        assert_eq!(failure.spend_index, None);
+
+        assert!(report.to_string().contains("is not satisfied"));
+        assert_eq!(report.to_json()["assertions"][0]["matched_by"], 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_bundle_report_messages() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let (puzzle_hash, _) = to_puzzle(1)?;
+
+        let alice = sim.new_coin(puzzle_hash, 1000);
+        let bob = sim.new_coin(puzzle_hash, 500);
+
+        // Sent from alice's puzzle hash to bob's coin id.
+        let mode = 0b010_111;
+        let message: chia_protocol::Bytes = b"hello".to_vec().into();
+
+        let send = SendMessage::new(mode, message.clone(), vec![to_program(bob.coin_id())?]);
+        let receive = ReceiveMessage::new(mode, message, vec![to_program(alice.puzzle_hash)?]);
+
+        let report = BundleReport::new(&[
+            spend(alice, vec![send.clone()])?,
+            spend(bob, vec![receive])?,
+        ]);
+
+        assert_eq!(report.assertions[0].matched_by, Some(0));
+        assert!(report.unasserted.is_empty());
+
+        // Without the receiver, the message is never received.
+        let report = BundleReport::new(&[spend(alice, vec![send])?]);
+
+        assert!(report.assertions.is_empty());
+        let failure = report.diagnose(ErrorCode::MessageNotSentOrReceived);
+        assert_eq!(failure.spend_index, Some(0));
+        assert_eq!(failure.condition_index, Some(0));
+
+        Ok(())
+    }
+}
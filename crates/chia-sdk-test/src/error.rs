@@ -1,7 +1,9 @@
-use std::io;
+use std::{fmt, io};
 
 use chia_consensus::gen::validation_error::ErrorCode;
-use chia_sdk_signer::SignerError;
+use chia_protocol::{Bytes32, Program};
+use chia_sdk_signer::{RequiredSignature, SignerError};
+use chia_sdk_types::Condition;
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -9,12 +11,94 @@ pub enum SimulatorError {
     #[error("IO error: {0}")]
     Io(#[from] io::Error),
 
-    #[error("Validation error: {0:?}")]
-    Validation(ErrorCode),
+    #[error("Validation error: {0}")]
+    Validation(Box<ValidationError>),
 
     #[error("Signer error: {0}")]
     Signer(#[from] SignerError),
 
     #[error("Missing key ")]
     MissingKey,
+
+    #[error("Streamable error: {0}")]
+    Streamable(#[from] chia_traits::Error),
+
+    #[error("Unsupported snapshot version {0}")]
+    UnsupportedSnapshotVersion(u32),
+
+    #[error("Invalid snapshot")]
+    InvalidSnapshot,
+}
+
+impl SimulatorError {
+    /// The consensus error code, if this is a validation error.
+    pub fn error_code(&self) -> Option<ErrorCode> {
+        match self {
+            Self::Validation(error) => Some(error.code),
+            _ => None,
+        }
+    }
+}
+
+impl From<ErrorCode> for SimulatorError {
+    fn from(code: ErrorCode) -> Self {
+        Self::Validation(Box::new(ValidationError::new(code)))
+    }
+}
+
+impl From<ValidationError> for SimulatorError {
+    fn from(error: ValidationError) -> Self {
+        Self::Validation(Box::new(error))
+    }
+}
+
+/// Why a spend bundle was rejected by the simulator, and which part of it caused the failure.
+#[derive(Debug, Clone)]
+pub struct ValidationError {
+    pub code: ErrorCode,
+    pub coin_id: Option<Bytes32>,
+    pub spend_index: Option<usize>,
+    pub condition: Option<Condition<Program>>,
+    /// For signature errors, the signatures which weren't satisfied by the aggregated signature.
+    pub required_signatures: Vec<RequiredSignature>,
+}
+
+impl ValidationError {
+    pub fn new(code: ErrorCode) -> Self {
+        Self {
+            code,
+            coin_id: None,
+            spend_index: None,
+            condition: None,
+            required_signatures: Vec::new(),
+        }
+    }
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self.code)?;
+
+        if let Some(spend_index) = self.spend_index {
+            write!(f, " in spend {spend_index}")?;
+        }
+
+        if let Some(coin_id) = self.coin_id {
+            write!(f, " of coin {}", hex::encode(coin_id))?;
+        }
+
+        if let Some(condition) = &self.condition {
+            write!(f, " caused by {condition:?}")?;
+        }
+
+        if !self.required_signatures.is_empty() {
+            write!(
+                f,
+                " with {} unsatisfied signatures",
+                self.required_signatures.len()
+            )?;
+        }
+
+        Ok(())
+    }
 }
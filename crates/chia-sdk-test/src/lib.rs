@@ -1,19 +1,29 @@
 mod announcements;
+mod bundle_report;
 mod error;
 mod events;
 mod keys;
 mod peer_simulator;
 mod simulator;
+mod simulator_config;
 mod transaction;
 
+#[cfg(feature = "proptest")]
+mod strategies;
+
 pub use announcements::*;
+pub use bundle_report::*;
 pub use error::*;
 pub use events::*;
 pub use keys::*;
 pub use peer_simulator::*;
 pub use simulator::*;
+pub use simulator_config::*;
 pub use transaction::*;
 
+#[cfg(feature = "proptest")]
+pub use strategies::*;
+
 use chia_protocol::{Bytes32, Program};
 use clvm_traits::{FromClvm, ToClvm};
 use clvm_utils::tree_hash;
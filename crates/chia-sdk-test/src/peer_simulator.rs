@@ -1,24 +1,36 @@
 use std::{net::SocketAddr, sync::Arc};
 
-use chia_protocol::{Bytes32, Coin, CoinState, Message};
-use chia_sdk_client::{Peer, PeerOptions};
+use chia_protocol::{
+    Bytes32, Coin, CoinState, Message, Program, ProtocolMessageTypes, SpendBundle, TransactionAck,
+};
+use chia_sdk_client::{InboundAction, Peer, PeerOptions};
+use chia_sdk_types::{ChainReader, ChainWriter};
 use error::PeerSimulatorError;
+use faults::Faults;
+use indexmap::IndexMap;
 use peer_map::PeerMap;
-use simulator_config::SimulatorConfig;
 use subscriptions::Subscriptions;
 use tokio::{
+    io::{AsyncRead, AsyncWrite},
     net::TcpListener,
     sync::{mpsc, Mutex},
     task::JoinHandle,
 };
 use tokio_tungstenite::connect_async;
-use ws_connection::ws_connection;
+use ws_connection::{broadcast_updates, peer_updates, process_transaction, ws_connection};
+
+use crate::{PeerFault, Simulator, SimulatorConfig};
 
-use crate::Simulator;
+#[cfg(feature = "native-tls")]
+use chia_sdk_client::{connect_peer, create_native_tls_connector};
+#[cfg(feature = "native-tls")]
+use chia_ssl::ChiaCertificate;
+#[cfg(feature = "native-tls")]
+use native_tls::Identity;
 
 mod error;
+mod faults;
 mod peer_map;
-mod simulator_config;
 mod subscriptions;
 mod ws_connection;
 
@@ -28,35 +40,88 @@ pub struct PeerSimulator {
     addr: SocketAddr,
     simulator: Arc<Mutex<Simulator>>,
     subscriptions: Arc<Mutex<Subscriptions>>,
+    faults: Faults,
+    peer_map: PeerMap,
     join_handle: JoinHandle<()>,
+    #[cfg(feature = "native-tls")]
+    client_cert: Option<ChiaCertificate>,
 }
 
+/// A connection to a peer, which may be encrypted with TLS.
+trait Stream: AsyncRead + AsyncWrite + Unpin + Send {}
+
+impl<T> Stream for T where T: AsyncRead + AsyncWrite + Unpin + Send {}
+
+#[cfg(feature = "native-tls")]
+type Acceptor = tokio_native_tls::TlsAcceptor;
+
+#[cfg(not(feature = "native-tls"))]
+type Acceptor = std::convert::Infallible;
+
 impl PeerSimulator {
     pub async fn new() -> Result<Self, PeerSimulatorError> {
         Self::with_config(SimulatorConfig::default()).await
     }
 
     pub async fn with_config(config: SimulatorConfig) -> Result<Self, PeerSimulatorError> {
+        Self::start(config, None).await
+    }
+
+    /// Starts a simulator which serves `wss://` with a newly generated certificate signed by the
+    /// Chia CA, and expects a `Handshake` before sending anything else, like a full node does.
+    /// This allows [`connect_peer`] and [`Client::connect`](chia_sdk_client::Client::connect)
+    /// to be used with the [`socket_addr`](Self::socket_addr) of the simulator.
+    #[cfg(feature = "native-tls")]
+    pub async fn with_tls(config: SimulatorConfig) -> Result<Self, PeerSimulatorError> {
+        let cert = ChiaCertificate::generate()?;
+        let identity = Identity::from_pkcs8(cert.cert_pem.as_bytes(), cert.key_pem.as_bytes())?;
+        let acceptor = native_tls::TlsAcceptor::new(identity)?;
+
+        let mut simulator = Self::start(config, Some(acceptor.into())).await?;
+        simulator.client_cert = Some(ChiaCertificate::generate()?);
+        Ok(simulator)
+    }
+
+    async fn start(
+        config: SimulatorConfig,
+        acceptor: Option<Acceptor>,
+    ) -> Result<Self, PeerSimulatorError> {
         tracing::info!("starting simulator");
 
         let addr = "127.0.0.1:0";
         let peer_map = PeerMap::default();
         let listener = TcpListener::bind(addr).await?;
         let addr = listener.local_addr()?;
-        let simulator = Arc::new(Mutex::new(Simulator::default()));
+        let simulator = Arc::new(Mutex::new(Simulator::with_config(config.clone())));
         let subscriptions = Arc::new(Mutex::new(Subscriptions::default()));
+        let faults = Faults::new(&config.faults);
         let config = Arc::new(config);
 
         let simulator_clone = simulator.clone();
         let subscriptions_clone = subscriptions.clone();
+        let faults_clone = faults.clone();
         let config_clone = config.clone();
+        let peer_map_clone = peer_map.clone();
 
         let join_handle = tokio::spawn(async move {
             let simulator = simulator_clone;
             let subscriptions = subscriptions_clone;
+            let faults = faults_clone;
             let config = config_clone;
+            let peer_map = peer_map_clone;
 
             while let Ok((stream, addr)) = listener.accept().await {
+                let stream: Box<dyn Stream> = match &acceptor {
+                    #[cfg(feature = "native-tls")]
+                    Some(acceptor) => match acceptor.accept(stream).await {
+                        Ok(stream) => Box::new(stream),
+                        Err(error) => {
+                            tracing::error!("error accepting tls connection: {}", error);
+                            continue;
+                        }
+                    },
+                    _ => Box::new(stream),
+                };
                 let stream = match tokio_tungstenite::accept_async(stream).await {
                     Ok(stream) => stream,
                     Err(error) => {
@@ -71,6 +136,8 @@ impl PeerSimulator {
                     config.clone(),
                     simulator.clone(),
                     subscriptions.clone(),
+                    faults.clone(),
+                    acceptor.is_some(),
                 ));
             }
         });
@@ -80,7 +147,11 @@ impl PeerSimulator {
             addr,
             simulator,
             subscriptions,
+            faults,
+            peer_map,
             join_handle,
+            #[cfg(feature = "native-tls")]
+            client_cert: None,
         })
     }
 
@@ -88,15 +159,39 @@ impl PeerSimulator {
         &self.config
     }
 
+    /// The IP address and port the simulator is listening on.
+    pub fn socket_addr(&self) -> SocketAddr {
+        self.addr
+    }
+
     pub async fn connect_raw(&self) -> Result<(Peer, mpsc::Receiver<Message>), PeerSimulatorError> {
+        self.connect_raw_with_options(PeerOptions {
+            inbound_action: InboundAction::Drop,
+            genesis_challenge: Some(self.config.constants.genesis_challenge),
+            ..PeerOptions::default()
+        })
+        .await
+    }
+
+    /// Connects a new peer with the given options, without waiting for the initial peak.
+    ///
+    /// If the simulator uses TLS, the handshake is performed with [`connect_peer`] first,
+    /// using a wallet certificate which was generated when the simulator started.
+    pub async fn connect_raw_with_options(
+        &self,
+        options: PeerOptions,
+    ) -> Result<(Peer, mpsc::Receiver<Message>), PeerSimulatorError> {
         tracing::info!("connecting new peer to simulator");
+
+        #[cfg(feature = "native-tls")]
+        if let Some(cert) = &self.client_cert {
+            let connector = create_native_tls_connector(cert)?;
+            let network_id = self.config.network_id.clone();
+            return Ok(connect_peer(network_id, connector, self.addr, options).await?);
+        }
+
         let (ws, _) = connect_async(format!("ws://{}", self.addr)).await?;
-        Ok(Peer::from_websocket(
-            ws,
-            PeerOptions {
-                rate_limit_factor: 0.6,
-            },
-        )?)
+        Ok(Peer::from_websocket(ws, options)?)
     }
 
     pub async fn connect_split(
@@ -123,11 +218,28 @@ impl PeerSimulator {
     }
 
     pub async fn reset(&self) -> Result<(), PeerSimulatorError> {
-        *self.simulator.lock().await = Simulator::default();
+        *self.simulator.lock().await = Simulator::with_config(self.config.as_ref().clone());
         *self.subscriptions.lock().await = Subscriptions::default();
+        self.faults.reset(&self.config.faults).await;
         Ok(())
     }
 
+    /// Injects a fault for the next given number of messages of the given type, from any peer.
+    /// This replaces the existing fault for the message type, including one from the config.
+    pub async fn inject_fault(
+        &self,
+        message_type: ProtocolMessageTypes,
+        fault: PeerFault,
+        times: usize,
+    ) {
+        self.faults.insert(message_type, fault, times).await;
+    }
+
+    /// Removes all faults, including the ones from the config, until the simulator is reset.
+    pub async fn clear_faults(&self) {
+        self.faults.reset(&IndexMap::new()).await;
+    }
+
     pub async fn mint_coin(&self, puzzle_hash: Bytes32, amount: u64) -> Coin {
         self.simulator.lock().await.new_coin(puzzle_hash, amount)
     }
@@ -151,6 +263,99 @@ impl PeerSimulator {
     pub async fn peak_hash(&self) -> Bytes32 {
         self.simulator.lock().await.header_hash()
     }
+
+    /// Undoes every block above the given height, and notifies connected peers of the rollback
+    /// with a [`CoinStateUpdate`](chia_protocol::CoinStateUpdate) and `NewPeakWallet` message.
+    pub async fn rewind_to(&self, height: u32) -> Result<(), PeerSimulatorError> {
+        self.reorg_from(height, 0).await
+    }
+
+    /// Undoes the given number of blocks, farms the given number of new blocks in their place,
+    /// and notifies connected peers of the reorg.
+    pub async fn reorg(&self, depth: u32, new_blocks: u32) -> Result<(), PeerSimulatorError> {
+        let height = self.height().await.saturating_sub(depth);
+        self.reorg_from(height, new_blocks).await
+    }
+
+    async fn reorg_from(&self, height: u32, new_blocks: u32) -> Result<(), PeerSimulatorError> {
+        let mut simulator = self.simulator.lock().await;
+        let subscriptions = self.subscriptions.lock().await;
+
+        if height >= simulator.height() && new_blocks == 0 {
+            return Ok(());
+        }
+
+        let fork_height = height.min(simulator.height());
+        let (mut updates, removed_hints) = simulator.rewind(height);
+
+        for _ in 0..new_blocks {
+            updates.extend(simulator.farm_block());
+        }
+
+        let updates = peer_updates(&simulator, &subscriptions, &updates, &removed_hints);
+        broadcast_updates(&self.peer_map, &simulator, fork_height, updates).await
+    }
+}
+
+impl ChainReader for PeerSimulator {
+    type Error = PeerSimulatorError;
+
+    async fn peak_height(&self) -> Result<u32, PeerSimulatorError> {
+        Ok(self.height().await)
+    }
+
+    async fn coin_states(
+        &self,
+        coin_ids: Vec<Bytes32>,
+    ) -> Result<Vec<CoinState>, PeerSimulatorError> {
+        Ok(self.simulator.lock().await.coin_states(coin_ids).await?)
+    }
+
+    async fn coin_states_by_puzzle_hash(
+        &self,
+        puzzle_hashes: Vec<Bytes32>,
+        include_hinted: bool,
+    ) -> Result<Vec<CoinState>, PeerSimulatorError> {
+        Ok(self
+            .simulator
+            .lock()
+            .await
+            .coin_states_by_puzzle_hash(puzzle_hashes, include_hinted)
+            .await?)
+    }
+
+    async fn children(&self, coin_id: Bytes32) -> Result<Vec<CoinState>, PeerSimulatorError> {
+        Ok(self.simulator.lock().await.children(coin_id))
+    }
+
+    async fn puzzle_and_solution(
+        &self,
+        coin_id: Bytes32,
+        spent_height: u32,
+    ) -> Result<Option<(Program, Program)>, PeerSimulatorError> {
+        Ok(self
+            .simulator
+            .lock()
+            .await
+            .puzzle_and_solution(coin_id, spent_height)
+            .await?)
+    }
+}
+
+impl ChainWriter for PeerSimulator {
+    /// Processes the transaction and notifies connected peers, as if it had been sent by a peer.
+    async fn submit_transaction(
+        &mut self,
+        spend_bundle: SpendBundle,
+    ) -> Result<TransactionAck, PeerSimulatorError> {
+        process_transaction(
+            self.peer_map.clone(),
+            spend_bundle,
+            self.simulator.lock().await,
+            self.subscriptions.lock().await,
+        )
+        .await
+    }
 }
 
 impl Drop for PeerSimulator {
@@ -161,17 +366,91 @@ impl Drop for PeerSimulator {
 
 #[cfg(test)]
 mod tests {
+    use std::time::{Duration, Instant};
+
     use chia_bls::{DerivableKey, PublicKey, Signature};
     use chia_protocol::{
-        Bytes, CoinSpend, CoinStateFilters, CoinStateUpdate, RespondCoinState, RespondPuzzleState,
-        SpendBundle,
+        Bytes, CoinSpend, CoinStateFilters, CoinStateUpdate, Handshake, NodeType,
+        RejectPuzzleState, RejectStateReason, RespondCoinState, RespondPuzzleState, SpendBundle,
     };
+    use chia_sdk_client::InboundViolation;
     use chia_sdk_types::{AggSigMe, CreateCoin, Remark};
+    use chia_traits::Streamable;
+
+    #[cfg(feature = "native-tls")]
+    use chia_sdk_client::{Client, ClientError, Network};
 
     use crate::{coin_state_updates, test_secret_key, test_transaction, to_program, to_puzzle};
 
     use super::*;
 
+    async fn check_chain<T>(chain: &mut T, coin: Coin) -> anyhow::Result<()>
+    where
+        T: ChainWriter,
+        anyhow::Error: From<T::Error>,
+    {
+        let (puzzle_hash, puzzle_reveal) = to_puzzle(1)?;
+        let solution = to_program([CreateCoin::new(puzzle_hash, coin.amount, Vec::new())])?;
+        let child = Coin::new(coin.coin_id(), puzzle_hash, coin.amount);
+
+        let ack = chain
+            .submit_transaction(SpendBundle::new(
+                vec![CoinSpend::new(
+                    coin,
+                    puzzle_reveal.clone(),
+                    solution.clone(),
+                )],
+                Signature::default(),
+            ))
+            .await?;
+        assert_eq!(ack.status, 1);
+
+        assert_eq!(chain.peak_height().await?, 1);
+
+        assert_eq!(
+            chain.coin_states(vec![coin.coin_id()]).await?,
+            vec![CoinState::new(coin, Some(0), Some(0))]
+        );
+
+        let children = chain.children(coin.coin_id()).await?;
+        assert_eq!(children, vec![CoinState::new(child, None, Some(0))]);
+
+        let coin_states = chain
+            .coin_states_by_puzzle_hash(vec![puzzle_hash], false)
+            .await?;
+        assert_eq!(coin_states.len(), 2);
+
+        assert_eq!(
+            chain.puzzle_and_solution(coin.coin_id(), 0).await?,
+            Some((puzzle_reveal, solution))
+        );
+        assert_eq!(chain.puzzle_and_solution(child.coin_id(), 0).await?, None);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_chain_reader_simulator() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let coin = sim.new_coin(to_puzzle(1)?.0, 1);
+        check_chain(&mut sim, coin).await
+    }
+
+    #[tokio::test]
+    async fn test_chain_reader_peer_simulator() -> anyhow::Result<()> {
+        let mut sim = PeerSimulator::new().await?;
+        let coin = sim.mint_coin(to_puzzle(1)?.0, 1).await;
+        check_chain(&mut sim, coin).await
+    }
+
+    #[tokio::test]
+    async fn test_chain_reader_peer() -> anyhow::Result<()> {
+        let sim = PeerSimulator::new().await?;
+        let mut peer = sim.connect().await?;
+        let coin = sim.mint_coin(to_puzzle(1)?.0, 1).await;
+        check_chain(&mut peer, coin).await
+    }
+
     #[tokio::test]
     async fn test_coin_state() -> anyhow::Result<()> {
         let sim = PeerSimulator::new().await?;
@@ -681,6 +960,62 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_rewind_subscription() -> anyhow::Result<()> {
+        let sim = PeerSimulator::new().await?;
+        let (peer, mut receiver) = sim.connect_split().await?;
+
+        let (puzzle_hash, puzzle_reveal) = to_puzzle(1)?;
+
+        let coin = sim.mint_coin(puzzle_hash, 1).await;
+        let child_coin = Coin::new(coin.coin_id(), Bytes32::default(), 1);
+
+        peer.register_for_ph_updates(vec![child_coin.puzzle_hash], 0)
+            .await?;
+
+        let spend_bundle = SpendBundle::new(
+            vec![CoinSpend::new(
+                coin,
+                puzzle_reveal,
+                to_program([CreateCoin::new(child_coin.puzzle_hash, 1, Vec::new())])?,
+            )],
+            Signature::default(),
+        );
+
+        let ack = peer.send_transaction(spend_bundle).await?;
+        assert_eq!(ack.status, 1);
+        assert_eq!(coin_state_updates(&mut receiver).len(), 1);
+
+        sim.rewind_to(0).await?;
+
+        // Wait for a response, so that the updates sent before it have been received.
+        assert!(peer
+            .request_children(coin.coin_id())
+            .await?
+            .coin_states
+            .is_empty());
+
+        let updates = coin_state_updates(&mut receiver);
+        assert_eq!(updates.len(), 1);
+
+        assert_eq!(
+            updates[0],
+            CoinStateUpdate::new(
+                0,
+                0,
+                sim.peak_hash().await,
+                vec![CoinState::new(child_coin, None, None)]
+            )
+        );
+
+        assert_eq!(
+            sim.coin_state(coin.coin_id()).await,
+            Some(CoinState::new(coin, None, Some(0)))
+        );
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_spent_hint_subscription() -> anyhow::Result<()> {
         let sim = PeerSimulator::new().await?;
@@ -894,4 +1229,243 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_fault_reject() -> anyhow::Result<()> {
+        let mut config = SimulatorConfig::default();
+        config.faults.insert(
+            ProtocolMessageTypes::RequestPuzzleState,
+            PeerFault::Reject(RejectStateReason::ExceededSubscriptionLimit),
+        );
+
+        let sim = PeerSimulator::with_config(config).await?;
+        let peer = sim.connect().await?;
+        let header_hash = sim.config().constants.genesis_challenge;
+
+        for _ in 0..2 {
+            let response = peer
+                .request_puzzle_state(
+                    vec![Bytes32::default()],
+                    None,
+                    header_hash,
+                    CoinStateFilters::new(true, true, true, 0),
+                    false,
+                )
+                .await?;
+            assert_eq!(
+                response,
+                Err(RejectPuzzleState::new(
+                    RejectStateReason::ExceededSubscriptionLimit
+                ))
+            );
+        }
+
+        // Other message types aren't affected.
+        let response = peer
+            .request_coin_state(vec![Bytes32::default()], None, header_hash, false)
+            .await?;
+        assert!(response.is_ok());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_fault_drop_and_delay() -> anyhow::Result<()> {
+        let sim = PeerSimulator::new().await?;
+        let peer = sim.connect().await?;
+        let timeout = Duration::from_millis(200);
+
+        sim.inject_fault(ProtocolMessageTypes::RequestChildren, PeerFault::Drop, 1)
+            .await;
+
+        let result = tokio::time::timeout(timeout, peer.request_children(Bytes32::default())).await;
+        assert!(result.is_err());
+
+        // The fault only applies once, so retrying succeeds.
+        let response =
+            tokio::time::timeout(timeout, peer.request_children(Bytes32::default())).await??;
+        assert!(response.coin_states.is_empty());
+
+        let delay = Duration::from_millis(300);
+
+        sim.inject_fault(
+            ProtocolMessageTypes::RequestChildren,
+            PeerFault::Delay(delay),
+            1,
+        )
+        .await;
+
+        let start = Instant::now();
+        peer.request_children(Bytes32::default()).await?;
+        assert!(start.elapsed() >= delay);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_fault_disconnect() -> anyhow::Result<()> {
+        let sim = PeerSimulator::new().await?;
+        let peer = sim.connect().await?;
+
+        sim.inject_fault(
+            ProtocolMessageTypes::RequestChildren,
+            PeerFault::Disconnect,
+            1,
+        )
+        .await;
+
+        let result = tokio::time::timeout(
+            Duration::from_millis(200),
+            peer.request_children(Bytes32::default()),
+        )
+        .await;
+        assert!(result.is_err());
+
+        // The connection was closed without a violation.
+        assert_eq!(peer.violation().await, None);
+
+        let peer = sim.connect().await?;
+        peer.request_children(Bytes32::default()).await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_fault_wrong_network() -> anyhow::Result<()> {
+        let mut config = SimulatorConfig::default();
+        config.faults.insert(
+            ProtocolMessageTypes::Handshake,
+            PeerFault::WrongNetwork("mainnet".to_string()),
+        );
+
+        let sim = PeerSimulator::with_config(config).await?;
+        let (peer, mut receiver) = sim.connect_split().await?;
+
+        peer.send(Handshake {
+            network_id: "testnet11".to_string(),
+            protocol_version: "0.0.37".to_string(),
+            software_version: "0.0.0".to_string(),
+            server_port: 0,
+            node_type: NodeType::Wallet,
+            capabilities: Vec::new(),
+        })
+        .await?;
+
+        let message = receiver.recv().await.expect("expected Handshake message");
+        assert_eq!(message.msg_type, ProtocolMessageTypes::Handshake);
+
+        let handshake = Handshake::from_bytes(&message.data)?;
+        assert_eq!(handshake.network_id, "mainnet");
+        assert_eq!(handshake.node_type, NodeType::FullNode);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_fault_flood() -> anyhow::Result<()> {
+        let sim = PeerSimulator::new().await?;
+        let (peer, mut receiver) = sim
+            .connect_raw_with_options(PeerOptions {
+                rate_limit_factor: 0.05,
+                inbound_action: InboundAction::Disconnect,
+                ..PeerOptions::default()
+            })
+            .await?;
+
+        tokio::spawn(async move { while receiver.recv().await.is_some() {} });
+
+        sim.inject_fault(
+            ProtocolMessageTypes::RequestChildren,
+            PeerFault::Flood(200),
+            1,
+        )
+        .await;
+
+        let request = tokio::spawn({
+            let peer = peer.clone();
+            async move { peer.request_children(Bytes32::default()).await }
+        });
+
+        assert_eq!(
+            peer.violation().await,
+            Some(InboundViolation::RateLimited(
+                ProtocolMessageTypes::NewPeakWallet
+            ))
+        );
+
+        request.abort();
+
+        Ok(())
+    }
+
+    #[cfg(feature = "native-tls")]
+    #[tokio::test]
+    async fn test_tls_connect_peer() -> anyhow::Result<()> {
+        let sim = PeerSimulator::with_tls(SimulatorConfig::default()).await?;
+        let connector = create_native_tls_connector(&ChiaCertificate::generate()?)?;
+
+        let (peer, mut receiver) = connect_peer(
+            "testnet11".to_string(),
+            connector.clone(),
+            sim.socket_addr(),
+            PeerOptions::default(),
+        )
+        .await?;
+
+        let message = receiver
+            .recv()
+            .await
+            .expect("expected NewPeakWallet message");
+        assert_eq!(message.msg_type, ProtocolMessageTypes::NewPeakWallet);
+        peer.request_children(Bytes32::default()).await?;
+
+        let error = connect_peer(
+            "mainnet".to_string(),
+            connector.clone(),
+            sim.socket_addr(),
+            PeerOptions::default(),
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(error, ClientError::WrongNetwork(..)));
+
+        let client = Client::new(
+            "testnet11".to_string(),
+            Network::default_testnet11(),
+            connector,
+        );
+        client
+            .connect(sim.socket_addr(), PeerOptions::default())
+            .await?;
+        assert_eq!(client.lock().await.peers().count(), 1);
+
+        Ok(())
+    }
+
+    #[cfg(feature = "native-tls")]
+    #[tokio::test]
+    async fn test_tls_node_type() -> anyhow::Result<()> {
+        let sim = PeerSimulator::with_tls(SimulatorConfig {
+            node_type: NodeType::Wallet,
+            ..SimulatorConfig::default()
+        })
+        .await?;
+
+        let error = sim.connect().await.unwrap_err();
+        assert!(matches!(
+            error,
+            PeerSimulatorError::Client(ClientError::WrongNodeType(..))
+        ));
+
+        Ok(())
+    }
+
+    #[cfg(feature = "native-tls")]
+    #[tokio::test]
+    async fn test_chain_reader_tls_peer() -> anyhow::Result<()> {
+        let sim = PeerSimulator::with_tls(SimulatorConfig::default()).await?;
+        let mut peer = sim.connect().await?;
+        let coin = sim.mint_coin(to_puzzle(1)?.0, 1).await;
+        check_chain(&mut peer, coin).await
+    }
 }
@@ -7,7 +7,7 @@ use futures_channel::mpsc::SendError;
 use thiserror::Error;
 use tokio_tungstenite::tungstenite;
 
-use crate::SimulatorError;
+use crate::{PeerFault, SimulatorError};
 
 #[derive(Debug, Error)]
 pub enum PeerSimulatorError {
@@ -26,6 +26,14 @@ pub enum PeerSimulatorError {
     #[error("consensus error: {0}")]
     Consensus(#[from] chia_consensus::error::Error),
 
+    #[cfg(feature = "native-tls")]
+    #[error("ssl error: {0}")]
+    Ssl(#[from] chia_ssl::Error),
+
+    #[cfg(feature = "native-tls")]
+    #[error("native tls error: {0}")]
+    NativeTls(#[from] native_tls::Error),
+
     #[error("signer error: {0}")]
     Signer(#[from] SignerError),
 
@@ -37,4 +45,7 @@ pub enum PeerSimulatorError {
 
     #[error("unsupported protocol message type: {0:?}")]
     UnsupportedMessage(ProtocolMessageTypes),
+
+    #[error("fault {0:?} is not supported for message type {1:?}")]
+    UnsupportedFault(PeerFault, ProtocolMessageTypes),
 }
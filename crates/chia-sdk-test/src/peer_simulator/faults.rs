@@ -0,0 +1,62 @@
+use std::sync::Arc;
+
+use chia_protocol::ProtocolMessageTypes;
+use indexmap::IndexMap;
+use tokio::sync::Mutex;
+
+use crate::PeerFault;
+
+/// Each fault is paired with the number of messages it still applies to, or [`None`] if it's permanent.
+type FaultMap = IndexMap<ProtocolMessageTypes, (PeerFault, Option<usize>)>;
+
+#[derive(Debug, Default, Clone)]
+pub(crate) struct Faults(Arc<Mutex<FaultMap>>);
+
+impl Faults {
+    pub(crate) fn new(faults: &IndexMap<ProtocolMessageTypes, PeerFault>) -> Self {
+        Self(Arc::new(Mutex::new(permanent(faults))))
+    }
+
+    pub(crate) async fn insert(
+        &self,
+        message_type: ProtocolMessageTypes,
+        fault: PeerFault,
+        times: usize,
+    ) {
+        let mut faults = self.0.lock().await;
+
+        if times == 0 {
+            faults.shift_remove(&message_type);
+        } else {
+            faults.insert(message_type, (fault, Some(times)));
+        }
+    }
+
+    pub(crate) async fn reset(&self, faults: &IndexMap<ProtocolMessageTypes, PeerFault>) {
+        *self.0.lock().await = permanent(faults);
+    }
+
+    /// Returns the fault for a message of the given type, and counts it towards its limit.
+    pub(crate) async fn take(&self, message_type: ProtocolMessageTypes) -> Option<PeerFault> {
+        let mut faults = self.0.lock().await;
+        let (fault, remaining) = faults.get_mut(&message_type)?;
+        let fault = fault.clone();
+
+        if let Some(remaining) = remaining {
+            *remaining -= 1;
+
+            if *remaining == 0 {
+                faults.shift_remove(&message_type);
+            }
+        }
+
+        Some(fault)
+    }
+}
+
+fn permanent(faults: &IndexMap<ProtocolMessageTypes, PeerFault>) -> FaultMap {
+    faults
+        .iter()
+        .map(|(message_type, fault)| (*message_type, (fault.clone(), None)))
+        .collect()
+}
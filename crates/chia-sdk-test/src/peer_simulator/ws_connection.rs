@@ -2,13 +2,14 @@ use std::{net::SocketAddr, sync::Arc};
 
 use chia_consensus::gen::validation_error::{ErrorCode, ValidationErr};
 use chia_protocol::{
-    Bytes, Bytes32, CoinState, CoinStateUpdate, Message, NewPeakWallet, ProtocolMessageTypes,
-    PuzzleSolutionResponse, RegisterForCoinUpdates, RegisterForPhUpdates, RejectCoinState,
-    RejectPuzzleSolution, RejectPuzzleState, RejectStateReason, RequestChildren, RequestCoinState,
-    RequestPuzzleSolution, RequestPuzzleState, RequestRemoveCoinSubscriptions,
-    RequestRemovePuzzleSubscriptions, RespondChildren, RespondCoinState, RespondPuzzleSolution,
-    RespondPuzzleState, RespondRemoveCoinSubscriptions, RespondRemovePuzzleSubscriptions,
-    RespondToCoinUpdates, RespondToPhUpdates, SendTransaction, SpendBundle, TransactionAck,
+    Bytes, Bytes32, ChiaProtocolMessage, CoinState, CoinStateUpdate, Handshake, Message,
+    NewPeakWallet, ProtocolMessageTypes, PuzzleSolutionResponse, RegisterForCoinUpdates,
+    RegisterForPhUpdates, RejectCoinState, RejectPuzzleSolution, RejectPuzzleState,
+    RejectStateReason, RequestChildren, RequestCoinState, RequestPuzzleSolution,
+    RequestPuzzleState, RequestRemoveCoinSubscriptions, RequestRemovePuzzleSubscriptions,
+    RespondChildren, RespondCoinState, RespondPuzzleSolution, RespondPuzzleState,
+    RespondRemoveCoinSubscriptions, RespondRemovePuzzleSubscriptions, RespondToCoinUpdates,
+    RespondToPhUpdates, SendTransaction, SpendBundle, TransactionAck,
 };
 use chia_traits::Streamable;
 use clvmr::NodePtr;
@@ -17,38 +18,44 @@ use futures_util::{SinkExt, StreamExt};
 use indexmap::{IndexMap, IndexSet};
 use itertools::Itertools;
 use tokio::{
-    net::TcpStream,
+    io::{AsyncRead, AsyncWrite},
     sync::{Mutex, MutexGuard},
 };
-use tokio_tungstenite::{
-    tungstenite::{self, Message as WsMessage},
-    WebSocketStream,
-};
+use tokio_tungstenite::{tungstenite, WebSocketStream};
 
-use crate::{Simulator, SimulatorError};
+use crate::{PeerFault, Simulator, SimulatorConfig, SimulatorError};
 
 use super::{
-    error::PeerSimulatorError, peer_map::Ws, simulator_config::SimulatorConfig,
-    subscriptions::Subscriptions, PeerMap,
+    error::PeerSimulatorError, faults::Faults, peer_map::Ws, subscriptions::Subscriptions, PeerMap,
 };
 
-pub(crate) async fn ws_connection(
+/// Handles messages from a peer until the connection is closed.
+///
+/// If a handshake is required, the first message must be a `Handshake`, and the peer isn't sent
+/// the initial peak or any updates until it has been answered, like a full node would.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn ws_connection<S>(
     peer_map: PeerMap,
-    ws: WebSocketStream<TcpStream>,
+    ws: WebSocketStream<S>,
     addr: SocketAddr,
     config: Arc<SimulatorConfig>,
     simulator: Arc<Mutex<Simulator>>,
     subscriptions: Arc<Mutex<Subscriptions>>,
-) {
+    faults: Faults,
+    require_handshake: bool,
+) where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
     let (mut tx, mut rx) = mpsc::unbounded();
+    let mut handshake_pending = require_handshake;
 
-    if let Err(error) = handle_initial_peak(&mut tx, &simulator).await {
-        tracing::error!("error sending initial peak: {}", error);
-        return;
+    if !handshake_pending {
+        if let Err(error) = handle_initial_peak(&peer_map, addr, &mut tx, &simulator).await {
+            tracing::error!("error sending initial peak: {}", error);
+            return;
+        }
     }
 
-    peer_map.insert(addr, tx.clone()).await;
-
     let (mut sink, mut stream) = ws.split();
 
     tokio::spawn(async move {
@@ -69,12 +76,42 @@ pub(crate) async fn ws_connection(
             }
         };
 
+        let request = match Message::from_bytes(&message.into_data()) {
+            Ok(request) => request,
+            Err(error) => {
+                tracing::error!("error parsing message: {}", error);
+                break;
+            }
+        };
+
+        if handshake_pending && request.msg_type != ProtocolMessageTypes::Handshake {
+            tracing::info!("expected handshake, found {:?} message", request.msg_type);
+            break;
+        }
+
+        let fault = match faults.take(request.msg_type).await {
+            Some(PeerFault::Delay(duration)) => {
+                tokio::time::sleep(duration).await;
+                None
+            }
+            Some(PeerFault::Drop) => {
+                tracing::info!("dropping {:?} message", request.msg_type);
+                continue;
+            }
+            Some(PeerFault::Disconnect) => {
+                tracing::info!("disconnecting on {:?} message", request.msg_type);
+                break;
+            }
+            fault => fault,
+        };
+
         if let Err(error) = handle_message(
             peer_map.clone(),
             &config,
             &simulator,
             &subscriptions,
-            message,
+            request,
+            fault,
             addr,
             tx.clone(),
         )
@@ -83,12 +120,24 @@ pub(crate) async fn ws_connection(
             tracing::error!("error handling message: {}", error);
             break;
         }
+
+        if handshake_pending {
+            handshake_pending = false;
+
+            if let Err(error) = handle_initial_peak(&peer_map, addr, &mut tx, &simulator).await {
+                tracing::error!("error sending initial peak: {}", error);
+                break;
+            }
+        }
     }
 
     peer_map.remove(addr).await;
 }
 
+/// Sends the current peak to a new peer, and registers it to receive future updates.
 async fn handle_initial_peak(
+    peer_map: &PeerMap,
+    addr: SocketAddr,
     tx: &mut UnboundedSender<tungstenite::Message>,
     sim: &Mutex<Simulator>,
 ) -> Result<(), PeerSimulatorError> {
@@ -111,22 +160,47 @@ async fn handle_initial_peak(
     )
     .await?;
 
+    peer_map.insert(addr, tx.clone()).await;
+
     Ok(())
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn handle_message(
     peer_map: PeerMap,
     config: &SimulatorConfig,
     simulator: &Mutex<Simulator>,
     subscriptions: &Mutex<Subscriptions>,
-    message: WsMessage,
+    request: Message,
+    fault: Option<PeerFault>,
     addr: SocketAddr,
     mut ws: Ws,
 ) -> Result<(), PeerSimulatorError> {
-    let request = Message::from_bytes(&message.into_data())?;
     let simulator = simulator.lock().await;
 
+    // The current peak is repeated, since it's one of the few messages a full node sends without a request.
+    let flood = match fault {
+        Some(PeerFault::Flood(count)) => {
+            let height = simulator.height();
+            let peak = NewPeakWallet::new(simulator.header_hash(), height, 0, height);
+            Some((count, peak))
+        }
+        _ => None,
+    };
+
+    let replaced = matches!(
+        fault,
+        Some(PeerFault::Reject(..) | PeerFault::WrongNetwork(..))
+    );
+
     let (response_type, response_data) = match request.msg_type {
+        message_type if replaced => {
+            fault_response(config, message_type, &request.data, fault.clone())?
+        }
+        ProtocolMessageTypes::Handshake => {
+            Handshake::from_bytes(&request.data)?;
+            handshake(config, None)?
+        }
         ProtocolMessageTypes::SendTransaction => {
             let request = SendTransaction::from_bytes(&request.data)?;
             let subscriptions = subscriptions.lock().await;
@@ -147,8 +221,7 @@ async fn handle_message(
         }
         ProtocolMessageTypes::RequestPuzzleSolution => {
             let request = RequestPuzzleSolution::from_bytes(&request.data)?;
-            let response = request_puzzle_solution(&request, &simulator)?;
-            (ProtocolMessageTypes::RespondPuzzleSolution, response)
+            request_puzzle_solution(&request, &simulator)?
         }
         ProtocolMessageTypes::RequestChildren => {
             let request = RequestChildren::from_bytes(&request.data)?;
@@ -158,14 +231,12 @@ async fn handle_message(
         ProtocolMessageTypes::RequestCoinState => {
             let request = RequestCoinState::from_bytes(&request.data)?;
             let subscriptions = subscriptions.lock().await;
-            let response = request_coin_state(addr, request, config, &simulator, subscriptions)?;
-            (ProtocolMessageTypes::RespondCoinState, response)
+            request_coin_state(addr, request, config, &simulator, subscriptions)?
         }
         ProtocolMessageTypes::RequestPuzzleState => {
             let request = RequestPuzzleState::from_bytes(&request.data)?;
             let subscriptions = subscriptions.lock().await;
-            let response = request_puzzle_state(addr, request, config, &simulator, subscriptions)?;
-            (ProtocolMessageTypes::RespondPuzzleState, response)
+            request_puzzle_state(addr, request, config, &simulator, subscriptions)?
         }
         ProtocolMessageTypes::RequestRemoveCoinSubscriptions => {
             let request = RequestRemoveCoinSubscriptions::from_bytes(&request.data)?;
@@ -190,6 +261,19 @@ async fn handle_message(
         }
     };
 
+    if let Some((count, peak)) = flood {
+        let message = Message {
+            msg_type: ProtocolMessageTypes::NewPeakWallet,
+            data: peak.to_bytes()?.into(),
+            id: None,
+        }
+        .to_bytes()?;
+
+        for _ in 0..count {
+            ws.send(message.clone().into()).await?;
+        }
+    }
+
     let message = Message {
         msg_type: response_type,
         data: response_data,
@@ -208,11 +292,28 @@ fn new_transaction(
     spend_bundle: SpendBundle,
 ) -> Result<IndexMap<SocketAddr, IndexSet<CoinState>>, PeerSimulatorError> {
     let updates = simulator.new_transaction(spend_bundle)?;
+    Ok(peer_updates(
+        simulator,
+        subscriptions,
+        &updates,
+        &IndexMap::new(),
+    ))
+}
+
+/// Filters coin state updates down to the ones each peer is subscribed to.
+///
+/// Hints which were removed by a rewind are passed in separately, since they're no longer
+/// known to the simulator, but peers subscribed to them still need to be notified.
+pub(crate) fn peer_updates(
+    simulator: &Simulator,
+    subscriptions: &Subscriptions,
+    updates: &IndexMap<Bytes32, CoinState>,
+    removed_hints: &IndexMap<Bytes32, Bytes32>,
+) -> IndexMap<SocketAddr, IndexSet<CoinState>> {
     let peers = subscriptions.peers();
 
     let mut peer_updates = IndexMap::new();
 
-    // Send updates to peers.
     for peer in peers {
         let mut coin_states = IndexSet::new();
 
@@ -226,15 +327,16 @@ fn new_transaction(
             .cloned()
             .unwrap_or_default();
 
-        for &coin_id in updates.keys() {
-            let Some(coin_state) = simulator.coin_state(coin_id) else {
-                continue;
-            };
+        for (coin_id, coin_state) in updates {
+            let hinted = removed_hints
+                .get(coin_id)
+                .is_some_and(|hint| puzzle_subscriptions.contains(hint));
 
-            if coin_subscriptions.contains(&coin_id)
+            if hinted
+                || coin_subscriptions.contains(coin_id)
                 || puzzle_subscriptions.contains(&coin_state.coin.puzzle_hash)
             {
-                coin_states.insert(coin_state);
+                coin_states.insert(*coin_state);
             }
         }
 
@@ -242,8 +344,8 @@ fn new_transaction(
             let coin_ids = simulator.hinted_coins(hint);
 
             for coin_id in coin_ids {
-                if updates.contains_key(&coin_id) {
-                    coin_states.extend(simulator.coin_state(coin_id));
+                if let Some(coin_state) = updates.get(&coin_id) {
+                    coin_states.insert(*coin_state);
                 }
             }
         }
@@ -255,50 +357,28 @@ fn new_transaction(
         peer_updates.insert(peer, coin_states);
     }
 
-    Ok(peer_updates)
+    peer_updates
 }
 
-async fn send_transaction(
-    peer_map: PeerMap,
-    request: SendTransaction,
-    mut simulator: MutexGuard<'_, Simulator>,
-    mut subscriptions: MutexGuard<'_, Subscriptions>,
-) -> Result<Bytes, PeerSimulatorError> {
-    let transaction_id = request.transaction.name();
-
-    let updates = match new_transaction(&mut simulator, &mut subscriptions, request.transaction) {
-        Ok(updates) => updates,
-        Err(error) => {
-            tracing::error!("error processing transaction: {:?}", &error);
-
-            let error_code = match error {
-                PeerSimulatorError::Simulator(SimulatorError::Validation(error_code)) => error_code,
-                _ => ErrorCode::Unknown,
-            };
-
-            return Ok(TransactionAck::new(
-                transaction_id,
-                3,
-                Some(format!("{:?}", ValidationErr(NodePtr::NIL, error_code))),
-            )
-            .to_bytes()?
-            .into());
-        }
-    };
-
+/// Sends the new peak to every peer, followed by the coin state updates they're subscribed to.
+pub(crate) async fn broadcast_updates(
+    peer_map: &PeerMap,
+    simulator: &Simulator,
+    fork_height: u32,
+    updates: IndexMap<SocketAddr, IndexSet<CoinState>>,
+) -> Result<(), PeerSimulatorError> {
     let header_hash = simulator.header_hash();
 
     let new_peak = Message {
         msg_type: ProtocolMessageTypes::NewPeakWallet,
         id: None,
-        data: NewPeakWallet::new(header_hash, simulator.height(), 0, simulator.height())
+        data: NewPeakWallet::new(header_hash, simulator.height(), 0, fork_height)
             .to_bytes()
             .unwrap()
             .into(),
     }
     .to_bytes()?;
 
-    // Send updates to peers.
     for (addr, mut peer) in peer_map.peers().await {
         peer.send(new_peak.clone().into()).await?;
 
@@ -311,7 +391,7 @@ async fn send_transaction(
             id: None,
             data: CoinStateUpdate::new(
                 simulator.height(),
-                simulator.height(),
+                fork_height,
                 header_hash,
                 peer_updates.into_iter().collect(),
             )
@@ -324,9 +404,54 @@ async fn send_transaction(
         peer.send(update.into()).await?;
     }
 
-    Ok(TransactionAck::new(transaction_id, 1, None)
-        .to_bytes()?
-        .into())
+    Ok(())
+}
+
+async fn send_transaction(
+    peer_map: PeerMap,
+    request: SendTransaction,
+    simulator: MutexGuard<'_, Simulator>,
+    subscriptions: MutexGuard<'_, Subscriptions>,
+) -> Result<Bytes, PeerSimulatorError> {
+    Ok(
+        process_transaction(peer_map, request.transaction, simulator, subscriptions)
+            .await?
+            .to_bytes()?
+            .into(),
+    )
+}
+
+/// Applies a transaction to the simulator, and notifies subscribed peers of the changes.
+pub(crate) async fn process_transaction(
+    peer_map: PeerMap,
+    spend_bundle: SpendBundle,
+    mut simulator: MutexGuard<'_, Simulator>,
+    mut subscriptions: MutexGuard<'_, Subscriptions>,
+) -> Result<TransactionAck, PeerSimulatorError> {
+    let transaction_id = spend_bundle.name();
+
+    let updates = match new_transaction(&mut simulator, &mut subscriptions, spend_bundle) {
+        Ok(updates) => updates,
+        Err(error) => {
+            tracing::error!("error processing transaction: {:?}", &error);
+
+            let error_code = match error {
+                PeerSimulatorError::Simulator(SimulatorError::Validation(error)) => error.code,
+                _ => ErrorCode::Unknown,
+            };
+
+            return Ok(TransactionAck::new(
+                transaction_id,
+                3,
+                Some(format!("{:?}", ValidationErr(NodePtr::NIL, error_code))),
+            ));
+        }
+    };
+
+    let fork_height = simulator.height();
+    broadcast_updates(&peer_map, &simulator, fork_height, updates).await?;
+
+    Ok(TransactionAck::new(transaction_id, 1, None))
 }
 
 fn register_for_coin_updates(
@@ -392,38 +517,36 @@ fn register_for_ph_updates(
 fn request_puzzle_solution(
     request: &RequestPuzzleSolution,
     simulator: &MutexGuard<'_, Simulator>,
-) -> Result<Bytes, PeerSimulatorError> {
-    let reject = RejectPuzzleSolution {
-        coin_name: request.coin_name,
-        height: request.height,
-    }
-    .to_bytes()?
-    .into();
+) -> Result<(ProtocolMessageTypes, Bytes), PeerSimulatorError> {
+    let reject = || {
+        reply(&RejectPuzzleSolution {
+            coin_name: request.coin_name,
+            height: request.height,
+        })
+    };
 
     let Some(coin_state) = simulator.coin_state(request.coin_name) else {
-        return Ok(reject);
+        return reject();
     };
 
     if coin_state.spent_height != Some(request.height) {
-        return Ok(reject);
+        return reject();
     }
 
     let Some(puzzle_reveal) = simulator.puzzle_reveal(request.coin_name) else {
-        return Ok(reject);
+        return reject();
     };
 
     let Some(solution) = simulator.solution(request.coin_name) else {
-        return Ok(reject);
+        return reject();
     };
 
-    Ok(RespondPuzzleSolution::new(PuzzleSolutionResponse::new(
+    reply(&RespondPuzzleSolution::new(PuzzleSolutionResponse::new(
         request.coin_name,
         request.height,
         puzzle_reveal,
         solution,
-    ))
-    .to_bytes()?
-    .into())
+    )))
 }
 
 fn request_children(
@@ -441,17 +564,13 @@ fn request_coin_state(
     config: &SimulatorConfig,
     simulator: &MutexGuard<'_, Simulator>,
     mut subscriptions: MutexGuard<'_, Subscriptions>,
-) -> Result<Bytes, PeerSimulatorError> {
+) -> Result<(ProtocolMessageTypes, Bytes), PeerSimulatorError> {
     if let Some(previous_height) = request.previous_height {
         if Some(request.header_hash) != simulator.header_hash_of(previous_height) {
-            return Ok(RejectCoinState::new(RejectStateReason::Reorg)
-                .to_bytes()?
-                .into());
+            return reply(&RejectCoinState::new(RejectStateReason::Reorg));
         }
     } else if request.header_hash != config.constants.genesis_challenge {
-        return Ok(RejectCoinState::new(RejectStateReason::Reorg)
-            .to_bytes()?
-            .into());
+        return reply(&RejectCoinState::new(RejectStateReason::Reorg));
     }
 
     let coin_ids: IndexSet<Bytes32> = request.coin_ids.iter().copied().collect();
@@ -459,11 +578,9 @@ fn request_coin_state(
     let subscription_count = subscriptions.subscription_count(peer);
 
     if subscription_count + coin_ids.len() > config.max_subscriptions && request.subscribe {
-        return Ok(
-            RejectCoinState::new(RejectStateReason::ExceededSubscriptionLimit)
-                .to_bytes()?
-                .into(),
-        );
+        return reply(&RejectCoinState::new(
+            RejectStateReason::ExceededSubscriptionLimit,
+        ));
     }
 
     let coin_states: Vec<CoinState> = simulator
@@ -481,12 +598,10 @@ fn request_coin_state(
         subscriptions.add_coin_subscriptions(peer, coin_ids);
     }
 
-    Ok(RespondCoinState {
+    reply(&RespondCoinState {
         coin_ids: request.coin_ids,
         coin_states,
-    }
-    .to_bytes()?
-    .into())
+    })
 }
 
 fn request_puzzle_state(
@@ -495,17 +610,13 @@ fn request_puzzle_state(
     config: &SimulatorConfig,
     simulator: &MutexGuard<'_, Simulator>,
     mut subscriptions: MutexGuard<'_, Subscriptions>,
-) -> Result<Bytes, PeerSimulatorError> {
+) -> Result<(ProtocolMessageTypes, Bytes), PeerSimulatorError> {
     if let Some(previous_height) = request.previous_height {
         if Some(request.header_hash) != simulator.header_hash_of(previous_height) {
-            return Ok(RejectPuzzleState::new(RejectStateReason::Reorg)
-                .to_bytes()?
-                .into());
+            return reply(&RejectPuzzleState::new(RejectStateReason::Reorg));
         }
     } else if request.header_hash != config.constants.genesis_challenge {
-        return Ok(RejectPuzzleState::new(RejectStateReason::Reorg)
-            .to_bytes()?
-            .into());
+        return reply(&RejectPuzzleState::new(RejectStateReason::Reorg));
     }
 
     let puzzle_hashes: IndexSet<Bytes32> = request.puzzle_hashes.iter().copied().collect();
@@ -515,11 +626,9 @@ fn request_puzzle_state(
     if subscription_count + puzzle_hashes.len() > config.max_subscriptions
         && request.subscribe_when_finished
     {
-        return Ok(
-            RejectPuzzleState::new(RejectStateReason::ExceededSubscriptionLimit)
-                .to_bytes()?
-                .into(),
-        );
+        return reply(&RejectPuzzleState::new(
+            RejectStateReason::ExceededSubscriptionLimit,
+        ));
     }
 
     let puzzle_hashes: IndexSet<Bytes32> = request.puzzle_hashes.iter().copied().collect();
@@ -567,15 +676,64 @@ fn request_puzzle_state(
 
     let height = next_height.unwrap_or(simulator.height());
 
-    Ok(RespondPuzzleState {
+    reply(&RespondPuzzleState {
         height,
         header_hash: simulator.header_hash_of(height).unwrap(),
         puzzle_hashes: request.puzzle_hashes,
         coin_states,
         is_finished: next_height.is_none(),
+    })
+}
+
+/// Creates the response for a fault which replaces the normal response to a message.
+fn fault_response(
+    config: &SimulatorConfig,
+    message_type: ProtocolMessageTypes,
+    data: &[u8],
+    fault: Option<PeerFault>,
+) -> Result<(ProtocolMessageTypes, Bytes), PeerSimulatorError> {
+    match (message_type, fault) {
+        (ProtocolMessageTypes::RequestPuzzleState, Some(PeerFault::Reject(reason))) => {
+            reply(&RejectPuzzleState::new(reason))
+        }
+        (ProtocolMessageTypes::RequestCoinState, Some(PeerFault::Reject(reason))) => {
+            reply(&RejectCoinState::new(reason))
+        }
+        (ProtocolMessageTypes::Handshake, Some(PeerFault::WrongNetwork(network_id))) => {
+            Handshake::from_bytes(data)?;
+            handshake(config, Some(network_id))
+        }
+        (message_type, Some(fault)) => {
+            Err(PeerSimulatorError::UnsupportedFault(fault, message_type))
+        }
+        (message_type, None) => Err(PeerSimulatorError::UnsupportedMessage(message_type)),
     }
-    .to_bytes()?
-    .into())
+}
+
+/// Responds to a handshake with the network id and node type from the config.
+fn handshake(
+    config: &SimulatorConfig,
+    network_id: Option<String>,
+) -> Result<(ProtocolMessageTypes, Bytes), PeerSimulatorError> {
+    reply(&Handshake {
+        network_id: network_id.unwrap_or_else(|| config.network_id.clone()),
+        protocol_version: "0.0.37".to_string(),
+        software_version: "0.0.0".to_string(),
+        server_port: 0,
+        node_type: config.node_type,
+        capabilities: vec![
+            (1, "1".to_string()),
+            (2, "1".to_string()),
+            (3, "1".to_string()),
+        ],
+    })
+}
+
+fn reply<T>(body: &T) -> Result<(ProtocolMessageTypes, Bytes), PeerSimulatorError>
+where
+    T: Streamable + ChiaProtocolMessage,
+{
+    Ok((T::msg_type(), body.to_bytes()?.into()))
 }
 
 fn request_remove_coin_subscriptions(
@@ -1,25 +1,96 @@
 use std::collections::HashSet;
 
-use chia_bls::{DerivableKey, PublicKey, SecretKey};
+use chia_bls::{aggregate_verify, DerivableKey, PublicKey, SecretKey};
 use chia_consensus::{
-    gen::validation_error::ErrorCode, spendbundle_validation::validate_clvm_and_signature,
+    allocator::make_allocator,
+    gen::{
+        flags::MEMPOOL_MODE,
+        owned_conditions::{OwnedSpendBundleConditions, OwnedSpendConditions},
+        validation_error::{ErrorCode, ValidationErr},
+    },
+    spendbundle_conditions::run_spendbundle,
 };
-use chia_protocol::{Bytes32, Coin, CoinSpend, CoinState, Program, SpendBundle};
+use chia_protocol::{Bytes32, Coin, CoinSpend, CoinState, Program, SpendBundle, TransactionAck};
 use chia_puzzles::standard::StandardArgs;
-use chia_sdk_types::TESTNET11_CONSTANTS;
+use chia_sdk_signer::{AggSigConstants, RequiredSignature};
+use chia_sdk_types::{ChainReader, ChainWriter};
+use clvmr::{Allocator, NodePtr, LIMIT_HEAP};
 use fastrand::Rng;
 use indexmap::{IndexMap, IndexSet};
 
-use crate::{sign_transaction, test_secret_key, SimulatorError};
+mod snapshot;
+
+pub use snapshot::*;
+
+use crate::{
+    bundle_report::triggers, sign_transaction_with, test_secret_key, BundleReport, SimulatorConfig,
+    SimulatorError, ValidationError,
+};
+
+/// The minimum amount by which a replacement transaction must increase the fee, in mojos.
+pub const MEMPOOL_MIN_FEE_INCREASE: u64 = 10_000_000;
+
+/// A validated spend bundle which is waiting in the mempool.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PendingTransaction {
+    transaction_id: Bytes32,
+    spend_bundle: SpendBundle,
+    fee: u64,
+    cost: u64,
+    removals: IndexMap<Bytes32, Coin>,
+    additions: IndexMap<Bytes32, (Coin, Option<Bytes32>)>,
+    conditions: OwnedSpendBundleConditions,
+}
+
+impl PendingTransaction {
+    pub fn transaction_id(&self) -> Bytes32 {
+        self.transaction_id
+    }
+
+    pub fn spend_bundle(&self) -> &SpendBundle {
+        &self.spend_bundle
+    }
+
+    pub fn fee(&self) -> u64 {
+        self.fee
+    }
+
+    pub fn cost(&self) -> u64 {
+        self.cost
+    }
+
+    pub fn removals(&self) -> Vec<Coin> {
+        self.removals.values().copied().collect()
+    }
+
+    pub fn additions(&self) -> Vec<Coin> {
+        self.additions.values().map(|(coin, _hint)| *coin).collect()
+    }
+}
+
+/// The previous values of everything that was changed, so that the changes can be undone.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+struct Journal {
+    coin_states: Vec<(Bytes32, Option<CoinState>)>,
+    hints: Vec<(Bytes32, Bytes32)>,
+    puzzle_and_solutions: Vec<(Bytes32, Option<(Program, Program)>)>,
+}
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Simulator {
+    config: SimulatorConfig,
     rng: Rng,
     height: u32,
     header_hashes: Vec<Bytes32>,
+    timestamps: Vec<u64>,
     coin_states: IndexMap<Bytes32, CoinState>,
     hinted_coins: IndexMap<Bytes32, IndexSet<Bytes32>>,
     puzzle_and_solutions: IndexMap<Bytes32, (Program, Program)>,
+    mempool: IndexMap<Bytes32, PendingTransaction>,
+    // Changes made directly at each height, such as minting coins.
+    inserted: Vec<Journal>,
+    // Changes made by the block on top of each height.
+    blocks: Vec<Journal>,
 }
 
 impl Default for Simulator {
@@ -30,24 +101,40 @@ impl Default for Simulator {
 
 impl Simulator {
     pub fn new() -> Self {
-        Self::with_seed(1337)
+        Self::with_config(SimulatorConfig::default())
     }
 
     pub fn with_seed(seed: u64) -> Self {
-        let mut rng = Rng::with_seed(seed);
+        Self::with_config(SimulatorConfig {
+            seed,
+            ..SimulatorConfig::default()
+        })
+    }
+
+    pub fn with_config(config: SimulatorConfig) -> Self {
+        let mut rng = Rng::with_seed(config.seed);
         let mut header_hash = [0; 32];
         rng.fill(&mut header_hash);
 
         Self {
+            config,
             rng,
             height: 0,
             header_hashes: vec![header_hash.into()],
+            timestamps: vec![0],
             coin_states: IndexMap::new(),
             hinted_coins: IndexMap::new(),
             puzzle_and_solutions: IndexMap::new(),
+            mempool: IndexMap::new(),
+            inserted: vec![Journal::default()],
+            blocks: Vec::new(),
         }
     }
 
+    pub fn config(&self) -> &SimulatorConfig {
+        &self.config
+    }
+
     pub fn height(&self) -> u32 {
         self.height
     }
@@ -60,9 +147,132 @@ impl Simulator {
         self.header_hashes.get(height as usize).copied()
     }
 
+    /// The timestamp of the current peak, in seconds. The simulator's clock starts at zero,
+    /// and only moves forward when [`Simulator::pass_time`] is called.
+    pub fn timestamp(&self) -> u64 {
+        self.timestamps.last().copied().unwrap()
+    }
+
+    pub fn timestamp_of(&self, height: u32) -> Option<u64> {
+        self.timestamps.get(height as usize).copied()
+    }
+
+    /// Farms an empty block with a timestamp the given number of seconds after the current peak.
+    pub fn pass_time(&mut self, seconds: u64) {
+        self.create_block(self.timestamp() + seconds, Journal::default());
+    }
+
+    /// Farms the given number of empty blocks, without moving the clock forward.
+    pub fn pass_blocks(&mut self, blocks: u32) {
+        for _ in 0..blocks {
+            self.create_block(self.timestamp(), Journal::default());
+        }
+    }
+
+    /// Undoes every block above the given height, and any coins minted or hinted after it,
+    /// as if those blocks were orphaned. Transactions in the mempool are kept.
+    ///
+    /// Returns the coin states which changed. Coins which no longer exist are reported
+    /// without a created or spent height, the same way a full node reports them after a reorg.
+    /// If the height isn't below the current peak, nothing happens.
+    pub fn rewind_to(&mut self, height: u32) -> IndexMap<Bytes32, CoinState> {
+        self.rewind(height).0
+    }
+
+    /// Undoes the given number of blocks, and then farms the given number of new blocks from
+    /// the mempool in their place. Returns the coin states which changed overall.
+    pub fn reorg(&mut self, depth: u32, new_blocks: u32) -> IndexMap<Bytes32, CoinState> {
+        let mut updates = self.rewind_to(self.height.saturating_sub(depth));
+
+        for _ in 0..new_blocks {
+            updates.extend(self.farm_block());
+        }
+
+        updates
+    }
+
+    /// Rewinds the chain, and also returns the hints which were removed, keyed by coin id.
+    pub(crate) fn rewind(
+        &mut self,
+        height: u32,
+    ) -> (IndexMap<Bytes32, CoinState>, IndexMap<Bytes32, Bytes32>) {
+        let mut coins = IndexMap::new();
+        let mut removed_hints = IndexMap::new();
+
+        while self.height > height {
+            let inserted = self.inserted.pop().unwrap();
+            let block = self.blocks.pop().unwrap();
+
+            for journal in [inserted, block] {
+                self.undo(journal, &mut coins, &mut removed_hints);
+            }
+
+            self.header_hashes.pop();
+            self.timestamps.pop();
+            self.height -= 1;
+        }
+
+        let updates = coins
+            .into_iter()
+            .map(|(coin_id, coin)| {
+                let coin_state = self
+                    .coin_states
+                    .get(&coin_id)
+                    .copied()
+                    .unwrap_or(CoinState::new(coin, None, None));
+                (coin_id, coin_state)
+            })
+            .collect();
+
+        (updates, removed_hints)
+    }
+
+    fn undo(
+        &mut self,
+        journal: Journal,
+        coins: &mut IndexMap<Bytes32, Coin>,
+        removed_hints: &mut IndexMap<Bytes32, Bytes32>,
+    ) {
+        for (coin_id, previous) in journal.coin_states.into_iter().rev() {
+            let current = match previous {
+                Some(previous) => self.coin_states.insert(coin_id, previous),
+                None => self.coin_states.shift_remove(&coin_id),
+            };
+
+            if let Some(coin_state) = current.or(previous) {
+                coins.insert(coin_id, coin_state.coin);
+            }
+        }
+
+        for (hint, coin_id) in journal.hints.into_iter().rev() {
+            if let Some(coin_ids) = self.hinted_coins.get_mut(&hint) {
+                coin_ids.shift_remove(&coin_id);
+
+                if coin_ids.is_empty() {
+                    self.hinted_coins.shift_remove(&hint);
+                }
+            }
+
+            removed_hints.insert(coin_id, hint);
+        }
+
+        for (coin_id, previous) in journal.puzzle_and_solutions.into_iter().rev() {
+            match previous {
+                Some(previous) => self.puzzle_and_solutions.insert(coin_id, previous),
+                None => self.puzzle_and_solutions.shift_remove(&coin_id),
+            };
+        }
+    }
+
     pub fn insert_coin(&mut self, coin: Coin) {
         let coin_state = CoinState::new(coin, None, Some(self.height));
-        self.coin_states.insert(coin.coin_id(), coin_state);
+        let previous = self.coin_states.insert(coin.coin_id(), coin_state);
+
+        self.inserted
+            .last_mut()
+            .unwrap()
+            .coin_states
+            .push((coin.coin_id(), previous));
     }
 
     pub fn new_coin(&mut self, puzzle_hash: Bytes32, amount: u64) -> Coin {
@@ -97,7 +307,13 @@ impl Simulator {
     }
 
     pub(crate) fn hint_coin(&mut self, coin_id: Bytes32, hint: Bytes32) {
-        self.hinted_coins.entry(hint).or_default().insert(coin_id);
+        if self.hinted_coins.entry(hint).or_default().insert(coin_id) {
+            self.inserted
+                .last_mut()
+                .unwrap()
+                .hints
+                .push((hint, coin_id));
+        }
     }
 
     pub fn coin_state(&self, coin_id: Bytes32) -> Option<CoinState> {
@@ -138,7 +354,36 @@ impl Simulator {
         coin_spends: Vec<CoinSpend>,
         secret_keys: &[SecretKey],
     ) -> Result<IndexMap<Bytes32, CoinState>, SimulatorError> {
-        let signature = sign_transaction(&coin_spends, secret_keys)?;
+        let constants = AggSigConstants::from(&self.config.constants);
+        let public_keys: HashSet<PublicKey> =
+            secret_keys.iter().map(SecretKey::public_key).collect();
+
+        let mut allocator = Allocator::new();
+        let mut missing = Vec::new();
+        let mut first_missing = None;
+
+        for coin_spend in &coin_spends {
+            for required in
+                RequiredSignature::from_coin_spend(&mut allocator, coin_spend, &constants)?
+            {
+                if !public_keys.contains(&required.public_key()) {
+                    first_missing.get_or_insert(coin_spend.coin.coin_id());
+                    missing.push(required);
+                }
+            }
+        }
+
+        if !missing.is_empty() {
+            let mut error = self.validation_error(
+                &coin_spends,
+                ErrorCode::BadAggregateSignature,
+                first_missing,
+            );
+            error.required_signatures = missing;
+            return Err(error.into());
+        }
+
+        let signature = sign_transaction_with(&coin_spends, secret_keys, &constants)?;
         self.new_transaction(SpendBundle::new(coin_spends, signature))
     }
 
@@ -147,103 +392,170 @@ impl Simulator {
         &mut self,
         spend_bundle: SpendBundle,
     ) -> Result<IndexMap<Bytes32, CoinState>, SimulatorError> {
-        if spend_bundle.coin_spends.is_empty() {
-            return Err(SimulatorError::Validation(ErrorCode::InvalidSpendBundle));
-        }
+        let transaction = self.validate(spend_bundle)?;
 
-        // TODO: Fix cost
-        let (conds, _pairings, _duration) = validate_clvm_and_signature(
-            &spend_bundle,
-            7_700_000_000,
-            &TESTNET11_CONSTANTS,
-            self.height,
-        )
-        .map_err(SimulatorError::Validation)?;
+        let mut updates = IndexMap::new();
+        self.include(&mut updates, &transaction)?;
+        self.commit_block(&updates, vec![transaction]);
 
-        let puzzle_hashes: HashSet<Bytes32> =
-            conds.spends.iter().map(|spend| spend.puzzle_hash).collect();
+        Ok(updates)
+    }
 
-        let bundle_puzzle_hashes: HashSet<Bytes32> = spend_bundle
-            .coin_spends
-            .iter()
-            .map(|cs| cs.coin.puzzle_hash)
-            .collect();
+    /// Validates a spend bundle and adds it to the mempool, to be included by [`Simulator::farm_block`].
+    ///
+    /// Spend bundles which spend the same coins as items already in the mempool follow Chia's
+    /// replace-by-fee rules. They must spend every coin the conflicting items spend, pay a higher
+    /// fee per cost, and increase the fee by at least [`MEMPOOL_MIN_FEE_INCREASE`] mojos.
+    pub fn add_to_mempool(&mut self, spend_bundle: SpendBundle) -> Result<Bytes32, SimulatorError> {
+        let transaction = self.validate(spend_bundle)?;
+        let transaction_id = transaction.transaction_id;
 
-        if puzzle_hashes != bundle_puzzle_hashes {
-            return Err(SimulatorError::Validation(ErrorCode::InvalidSpendBundle));
+        if self.mempool.contains_key(&transaction_id) {
+            return Ok(transaction_id);
         }
 
-        let mut removed_coins = IndexMap::new();
-        let mut added_coins = IndexMap::new();
-        let mut added_hints = IndexMap::new();
-        let mut puzzle_solutions = IndexMap::new();
-
-        for coin_spend in spend_bundle.coin_spends {
-            puzzle_solutions.insert(
-                coin_spend.coin.coin_id(),
-                (coin_spend.puzzle_reveal, coin_spend.solution),
-            );
+        for coin_id in transaction.removals.keys() {
+            if let Some(coin_state) = self.coin_states.get(coin_id) {
+                if coin_state.spent_height.is_some() {
+                    return Err(self
+                        .validation_error(
+                            &transaction.spend_bundle.coin_spends,
+                            ErrorCode::DoubleSpend,
+                            Some(*coin_id),
+                        )
+                        .into());
+                }
+            } else if !transaction.additions.contains_key(coin_id)
+                && !self
+                    .mempool
+                    .values()
+                    .any(|item| item.additions.contains_key(coin_id))
+            {
+                return Err(self
+                    .validation_error(
+                        &transaction.spend_bundle.coin_spends,
+                        ErrorCode::UnknownUnspent,
+                        Some(*coin_id),
+                    )
+                    .into());
+            }
         }
 
-        // Calculate additions and removals.
-        for spend in &conds.spends {
-            for new_coin in &spend.create_coin {
-                let coin = Coin::new(spend.coin_id, new_coin.0, new_coin.1);
+        let conflicts: Vec<Bytes32> = self
+            .mempool
+            .values()
+            .filter(|item| {
+                item.removals
+                    .keys()
+                    .any(|coin_id| transaction.removals.contains_key(coin_id))
+            })
+            .map(|item| item.transaction_id)
+            .collect();
 
-                added_coins.insert(
-                    coin.coin_id(),
-                    CoinState::new(coin, None, Some(self.height)),
-                );
+        if !conflicts.is_empty() {
+            let conflicts: Vec<&PendingTransaction> =
+                conflicts.iter().map(|id| &self.mempool[id]).collect();
 
-                let Some(hint) = new_coin.2.clone() else {
-                    continue;
-                };
+            if !can_replace(&conflicts, &transaction) {
+                return Err(ErrorCode::MempoolConflict.into());
+            }
 
-                if hint.len() != 32 {
-                    continue;
-                }
+            let conflict_ids: Vec<Bytes32> =
+                conflicts.iter().map(|item| item.transaction_id).collect();
 
-                added_hints
-                    .entry(Bytes32::try_from(hint).unwrap())
-                    .or_insert_with(IndexSet::new)
-                    .insert(coin.coin_id());
+            for id in conflict_ids {
+                self.mempool.shift_remove(&id);
             }
+        }
 
-            let coin = Coin::new(spend.parent_id, spend.puzzle_hash, spend.coin_amount);
+        self.mempool.insert(transaction_id, transaction);
 
-            let coin_state = self
-                .coin_states
-                .get(&spend.coin_id)
-                .copied()
-                .unwrap_or(CoinState::new(coin, None, Some(self.height)));
+        Ok(transaction_id)
+    }
 
-            removed_coins.insert(spend.coin_id, coin_state);
-        }
+    /// The transactions currently waiting in the mempool.
+    pub fn mempool_items(&self) -> Vec<&PendingTransaction> {
+        self.mempool.values().collect()
+    }
 
-        // Validate removals.
-        for (coin_id, coin_state) in &mut removed_coins {
-            let height = self.height;
+    /// Looks up a transaction in the mempool by its id.
+    pub fn mempool_item(&self, transaction_id: Bytes32) -> Option<&PendingTransaction> {
+        self.mempool.get(&transaction_id)
+    }
 
-            if !self.coin_states.contains_key(coin_id) && !added_coins.contains_key(coin_id) {
-                return Err(SimulatorError::Validation(ErrorCode::UnknownUnspent));
-            }
+    /// Creates a block from the mempool, including transactions with the highest fee per cost first,
+    /// until the block cost limit is reached. Coins can be created and spent in the same block.
+    ///
+    /// Transactions that don't fit remain in the mempool, and transactions that are no longer
+    /// valid are removed from it. Returns the updated coin states.
+    pub fn farm_block(&mut self) -> IndexMap<Bytes32, CoinState> {
+        let mut candidates: Vec<&PendingTransaction> = self.mempool.values().collect();
 
-            if coin_state.spent_height.is_some() {
-                return Err(SimulatorError::Validation(ErrorCode::DoubleSpend));
+        candidates.sort_by(|a, b| {
+            (u128::from(b.fee) * u128::from(a.cost)).cmp(&(u128::from(a.fee) * u128::from(b.cost)))
+        });
+
+        let mut updates = IndexMap::new();
+        let mut included = Vec::new();
+        let mut evicted = Vec::new();
+        let mut total_cost = 0;
+
+        // Transactions can depend on coins created by transactions with a lower fee per cost,
+        // so keep making passes until no more can be included.
+        loop {
+            let mut progress = false;
+
+            candidates.retain(|transaction| {
+                if total_cost + transaction.cost > self.config.max_block_cost {
+                    return true;
+                }
+
+                match self.include(&mut updates, transaction) {
+                    Ok(()) => {
+                        total_cost += transaction.cost;
+                        included.push(transaction.transaction_id);
+                        progress = true;
+                        false
+                    }
+                    // These may become valid in a later block.
+                    Err(error)
+                        if matches!(
+                            error.error_code(),
+                            Some(
+                                ErrorCode::UnknownUnspent
+                                    | ErrorCode::AssertHeightAbsoluteFailed
+                                    | ErrorCode::AssertHeightRelativeFailed
+                                    | ErrorCode::AssertSecondsAbsoluteFailed
+                                    | ErrorCode::AssertSecondsRelativeFailed
+                            )
+                        ) =>
+                    {
+                        true
+                    }
+                    Err(_) => {
+                        evicted.push(transaction.transaction_id);
+                        false
+                    }
+                }
+            });
+
+            if !progress {
+                break;
             }
+        }
+
+        let transactions = included
+            .iter()
+            .filter_map(|id| self.mempool.shift_remove(id))
+            .collect();
 
-            coin_state.spent_height = Some(height);
+        for id in evicted {
+            self.mempool.shift_remove(&id);
         }
 
-        // Update the coin data.
-        let mut updates = added_coins.clone();
-        updates.extend(removed_coins);
-        self.create_block();
-        self.coin_states.extend(updates.clone());
-        self.hinted_coins.extend(added_hints.clone());
-        self.puzzle_and_solutions.extend(puzzle_solutions);
+        self.commit_block(&updates, transactions);
 
-        Ok(updates)
+        updates
     }
 
     pub fn lookup_coin_ids(&self, coin_ids: &IndexSet<Bytes32>) -> Vec<CoinState> {
@@ -279,10 +591,944 @@ impl Simulator {
         coin_states.into_values().collect()
     }
 
-    fn create_block(&mut self) {
+    fn create_block(&mut self, timestamp: u64, journal: Journal) {
         let mut header_hash = [0; 32];
         self.rng.fill(&mut header_hash);
         self.header_hashes.push(header_hash.into());
+        self.timestamps.push(timestamp);
+        self.blocks.push(journal);
+        self.inserted.push(Journal::default());
         self.height += 1;
     }
+
+    fn validate(&self, spend_bundle: SpendBundle) -> Result<PendingTransaction, SimulatorError> {
+        if spend_bundle.coin_spends.is_empty() {
+            return Err(ErrorCode::InvalidSpendBundle.into());
+        }
+
+        let mut allocator = make_allocator(LIMIT_HEAP);
+
+        let (conds, signed_messages) = run_spendbundle(
+            &mut allocator,
+            &spend_bundle,
+            self.config.max_bundle_cost,
+            self.height,
+            self.config.flags | MEMPOOL_MODE,
+            &self.config.constants,
+        )
+        .map_err(|error| self.validation_error(&spend_bundle.coin_spends, error.1, None))?;
+
+        let conds = OwnedSpendBundleConditions::from(&allocator, conds);
+
+        if !aggregate_verify(
+            &spend_bundle.aggregated_signature,
+            signed_messages
+                .iter()
+                .map(|(public_key, message)| (public_key, message.as_ref())),
+        ) {
+            return Err(self
+                .validation_error(
+                    &spend_bundle.coin_spends,
+                    ErrorCode::BadAggregateSignature,
+                    None,
+                )
+                .into());
+        }
+
+        let puzzle_hashes: HashSet<Bytes32> =
+            conds.spends.iter().map(|spend| spend.puzzle_hash).collect();
+
+        let bundle_puzzle_hashes: HashSet<Bytes32> = spend_bundle
+            .coin_spends
+            .iter()
+            .map(|cs| cs.coin.puzzle_hash)
+            .collect();
+
+        if puzzle_hashes != bundle_puzzle_hashes {
+            return Err(ErrorCode::InvalidSpendBundle.into());
+        }
+
+        let fee = conds
+            .removal_amount
+            .checked_sub(conds.addition_amount)
+            .ok_or_else(|| {
+                self.validation_error(&spend_bundle.coin_spends, ErrorCode::MintingCoin, None)
+            })?;
+
+        let fee = u64::try_from(fee)
+            .map_err(|_| SimulatorError::from(ErrorCode::CoinAmountExceedsMaximum))?;
+
+        if fee < conds.reserve_fee {
+            return Err(self
+                .validation_error(
+                    &spend_bundle.coin_spends,
+                    ErrorCode::ReserveFeeConditionFailed,
+                    None,
+                )
+                .into());
+        }
+
+        let mut removals = IndexMap::new();
+        let mut additions = IndexMap::new();
+
+        // Calculate additions and removals.
+        for spend in &conds.spends {
+            for new_coin in &spend.create_coin {
+                let coin = Coin::new(spend.coin_id, new_coin.0, new_coin.1);
+
+                let hint = new_coin
+                    .2
+                    .clone()
+                    .and_then(|hint| Bytes32::try_from(hint).ok());
+
+                additions.insert(coin.coin_id(), (coin, hint));
+            }
+
+            let coin = Coin::new(spend.parent_id, spend.puzzle_hash, spend.coin_amount);
+            removals.insert(spend.coin_id, coin);
+        }
+
+        Ok(PendingTransaction {
+            transaction_id: spend_bundle.name(),
+            spend_bundle,
+            fee,
+            cost: conds.cost,
+            removals,
+            additions,
+            conditions: conds,
+        })
+    }
+
+    /// Adds the coin state updates of a transaction to those of the block being built,
+    /// or leaves them untouched if the transaction can't be included.
+    fn include(
+        &self,
+        updates: &mut IndexMap<Bytes32, CoinState>,
+        transaction: &PendingTransaction,
+    ) -> Result<(), SimulatorError> {
+        let mut staged = IndexMap::new();
+
+        for (coin_id, (coin, _hint)) in &transaction.additions {
+            staged.insert(*coin_id, CoinState::new(*coin, None, Some(self.height)));
+        }
+
+        let coin_spends = &transaction.spend_bundle.coin_spends;
+
+        self.check_absolute_timelocks(&transaction.conditions)
+            .map_err(|code| self.validation_error(coin_spends, code, None))?;
+
+        // Validate removals.
+        for spend in &transaction.conditions.spends {
+            let mut coin_state = updates
+                .get(&spend.coin_id)
+                .or_else(|| staged.get(&spend.coin_id))
+                .or_else(|| self.coin_states.get(&spend.coin_id))
+                .copied()
+                .ok_or_else(|| {
+                    self.validation_error(
+                        coin_spends,
+                        ErrorCode::UnknownUnspent,
+                        Some(spend.coin_id),
+                    )
+                })?;
+
+            if coin_state.spent_height.is_some() {
+                return Err(self
+                    .validation_error(coin_spends, ErrorCode::DoubleSpend, Some(spend.coin_id))
+                    .into());
+            }
+
+            let ephemeral = !self.coin_states.contains_key(&spend.coin_id);
+
+            self.check_relative_timelocks(spend, coin_state, ephemeral)
+                .map_err(|code| self.validation_error(coin_spends, code, Some(spend.coin_id)))?;
+
+            coin_state.spent_height = Some(self.height);
+            staged.insert(spend.coin_id, coin_state);
+        }
+
+        updates.extend(staged);
+
+        Ok(())
+    }
+
+    /// Attributes a validation error to the spend and condition which caused it.
+    /// If the coin is already known, the error is attributed to its spend.
+    fn validation_error(
+        &self,
+        coin_spends: &[CoinSpend],
+        code: ErrorCode,
+        coin_id: Option<Bytes32>,
+    ) -> ValidationError {
+        let report = BundleReport::new(coin_spends);
+        let mut error = ValidationError::new(code);
+
+        let (spend_index, condition_index) = if let Some(coin_id) = coin_id {
+            let spend_index = report
+                .spends
+                .iter()
+                .position(|spend| spend.coin.coin_id() == coin_id);
+
+            let condition_index = spend_index.and_then(|spend_index| {
+                report.spends[spend_index]
+                    .conditions
+                    .iter()
+                    .position(|condition| {
+                        condition
+                            .decoded
+                            .as_ref()
+                            .is_some_and(|condition| triggers(code, condition))
+                    })
+            });
+
+            (spend_index, condition_index)
+        } else {
+            let failure = report.diagnose(code);
+            (failure.spend_index, failure.condition_index)
+        };
+
+        error.coin_id = coin_id;
+
+        if let Some(spend_index) = spend_index {
+            let spend = &report.spends[spend_index];
+            error.spend_index = Some(spend_index);
+            error.coin_id = Some(spend.coin.coin_id());
+            error.condition = condition_index
+                .and_then(|condition_index| spend.conditions[condition_index].decoded.clone());
+        }
+
+        // The aggregated signature can't be split up, so every required signature is reported.
+        if code == ErrorCode::BadAggregateSignature {
+            error.required_signatures = RequiredSignature::from_coin_spends(
+                &mut Allocator::new(),
+                coin_spends,
+                &AggSigConstants::from(&self.config.constants),
+            )
+            .unwrap_or_default();
+        }
+
+        error
+    }
+
+    fn check_absolute_timelocks(
+        &self,
+        conds: &OwnedSpendBundleConditions,
+    ) -> Result<(), ErrorCode> {
+        let height = self.height;
+        let timestamp = self.timestamp();
+
+        if height < conds.height_absolute {
+            return Err(ErrorCode::AssertHeightAbsoluteFailed);
+        }
+
+        if timestamp < conds.seconds_absolute {
+            return Err(ErrorCode::AssertSecondsAbsoluteFailed);
+        }
+
+        if conds
+            .before_height_absolute
+            .is_some_and(|before| height >= before)
+        {
+            return Err(ErrorCode::AssertBeforeHeightAbsoluteFailed);
+        }
+
+        if conds
+            .before_seconds_absolute
+            .is_some_and(|before| timestamp >= before)
+        {
+            return Err(ErrorCode::AssertBeforeSecondsAbsoluteFailed);
+        }
+
+        Ok(())
+    }
+
+    fn check_relative_timelocks(
+        &self,
+        spend: &OwnedSpendConditions,
+        coin_state: CoinState,
+        ephemeral: bool,
+    ) -> Result<(), ErrorCode> {
+        let has_relative = spend.height_relative.is_some()
+            || spend.seconds_relative.is_some()
+            || spend.before_height_relative.is_some()
+            || spend.before_seconds_relative.is_some();
+
+        if ephemeral && has_relative {
+            return Err(ErrorCode::EphemeralRelativeCondition);
+        }
+
+        let height = self.height;
+        let timestamp = self.timestamp();
+
+        let created_height = coin_state.created_height.unwrap_or(height);
+        let created_timestamp = self.timestamp_of(created_height).unwrap_or(timestamp);
+
+        if spend
+            .height_relative
+            .is_some_and(|relative| height < created_height.saturating_add(relative))
+        {
+            return Err(ErrorCode::AssertHeightRelativeFailed);
+        }
+
+        if spend
+            .seconds_relative
+            .is_some_and(|relative| timestamp < created_timestamp.saturating_add(relative))
+        {
+            return Err(ErrorCode::AssertSecondsRelativeFailed);
+        }
+
+        if spend
+            .before_height_relative
+            .is_some_and(|relative| height >= created_height.saturating_add(relative))
+        {
+            return Err(ErrorCode::AssertBeforeHeightRelativeFailed);
+        }
+
+        if spend
+            .before_seconds_relative
+            .is_some_and(|relative| timestamp >= created_timestamp.saturating_add(relative))
+        {
+            return Err(ErrorCode::AssertBeforeSecondsRelativeFailed);
+        }
+
+        if spend
+            .birth_height
+            .is_some_and(|birth_height| birth_height != created_height)
+        {
+            return Err(ErrorCode::AssertMyBirthHeightFailed);
+        }
+
+        if spend
+            .birth_seconds
+            .is_some_and(|birth_seconds| birth_seconds != created_timestamp)
+        {
+            return Err(ErrorCode::AssertMyBirthSecondsFailed);
+        }
+
+        Ok(())
+    }
+
+    fn commit_block(
+        &mut self,
+        updates: &IndexMap<Bytes32, CoinState>,
+        transactions: Vec<PendingTransaction>,
+    ) {
+        let mut journal = Journal::default();
+
+        for (&coin_id, &coin_state) in updates {
+            let previous = self.coin_states.insert(coin_id, coin_state);
+            journal.coin_states.push((coin_id, previous));
+        }
+
+        for transaction in transactions {
+            for (coin_id, (_coin, hint)) in transaction.additions {
+                let Some(hint) = hint else {
+                    continue;
+                };
+
+                if self.hinted_coins.entry(hint).or_default().insert(coin_id) {
+                    journal.hints.push((hint, coin_id));
+                }
+            }
+
+            for coin_spend in transaction.spend_bundle.coin_spends {
+                let coin_id = coin_spend.coin.coin_id();
+
+                let previous = self
+                    .puzzle_and_solutions
+                    .insert(coin_id, (coin_spend.puzzle_reveal, coin_spend.solution));
+
+                journal.puzzle_and_solutions.push((coin_id, previous));
+            }
+        }
+
+        self.create_block(self.timestamp(), journal);
+
+        // Anything in the mempool which spends a coin that's now spent can never be included.
+        let coin_states = &self.coin_states;
+
+        self.mempool.retain(|_, item| {
+            item.removals.keys().all(|coin_id| {
+                coin_states
+                    .get(coin_id)
+                    .map_or(true, |coin_state| coin_state.spent_height.is_none())
+            })
+        });
+    }
+}
+
+fn can_replace(conflicts: &[&PendingTransaction], transaction: &PendingTransaction) -> bool {
+    let mut conflict_fee = 0;
+    let mut conflict_cost = 0;
+
+    for conflict in conflicts {
+        if conflict
+            .removals
+            .keys()
+            .any(|coin_id| !transaction.removals.contains_key(coin_id))
+        {
+            return false;
+        }
+
+        conflict_fee += u128::from(conflict.fee);
+        conflict_cost += u128::from(conflict.cost);
+    }
+
+    if u128::from(transaction.fee) * conflict_cost <= conflict_fee * u128::from(transaction.cost) {
+        return false;
+    }
+
+    u128::from(transaction.fee) >= conflict_fee + u128::from(MEMPOOL_MIN_FEE_INCREASE)
+}
+
+impl ChainReader for Simulator {
+    type Error = SimulatorError;
+
+    async fn peak_height(&self) -> Result<u32, SimulatorError> {
+        Ok(self.height)
+    }
+
+    async fn coin_states(&self, coin_ids: Vec<Bytes32>) -> Result<Vec<CoinState>, SimulatorError> {
+        Ok(self.lookup_coin_ids(&coin_ids.into_iter().collect()))
+    }
+
+    async fn coin_states_by_puzzle_hash(
+        &self,
+        puzzle_hashes: Vec<Bytes32>,
+        include_hinted: bool,
+    ) -> Result<Vec<CoinState>, SimulatorError> {
+        Ok(self.lookup_puzzle_hashes(puzzle_hashes.into_iter().collect(), include_hinted))
+    }
+
+    async fn children(&self, coin_id: Bytes32) -> Result<Vec<CoinState>, SimulatorError> {
+        Ok(Simulator::children(self, coin_id))
+    }
+
+    async fn puzzle_and_solution(
+        &self,
+        coin_id: Bytes32,
+        spent_height: u32,
+    ) -> Result<Option<(Program, Program)>, SimulatorError> {
+        if self
+            .coin_state(coin_id)
+            .map_or(true, |cs| cs.spent_height != Some(spent_height))
+        {
+            return Ok(None);
+        }
+
+        Ok(self.puzzle_and_solutions.get(&coin_id).cloned())
+    }
+}
+
+impl ChainWriter for Simulator {
+    async fn submit_transaction(
+        &mut self,
+        spend_bundle: SpendBundle,
+    ) -> Result<TransactionAck, SimulatorError> {
+        let transaction_id = spend_bundle.name();
+
+        match self.new_transaction(spend_bundle) {
+            Ok(_) => Ok(TransactionAck::new(transaction_id, 1, None)),
+            Err(SimulatorError::Validation(error)) => Ok(TransactionAck::new(
+                transaction_id,
+                3,
+                Some(format!("{:?}", ValidationErr(NodePtr::NIL, error.code))),
+            )),
+            Err(error) => Err(error),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chia_bls::Signature;
+    use chia_sdk_types::{
+        AggSigMe, AssertBeforeHeightAbsolute, AssertBeforeHeightRelative,
+        AssertBeforeSecondsRelative, AssertHeightAbsolute, AssertHeightRelative,
+        AssertSecondsRelative, Conditions, CreateCoin, ReserveFee,
+    };
+
+    use crate::{sign_transaction, to_program, to_puzzle};
+
+    use super::*;
+
+    fn spend(coin: Coin, conditions: Conditions) -> anyhow::Result<SpendBundle> {
+        let (_puzzle_hash, puzzle_reveal) = to_puzzle(1)?;
+        let solution = to_program(conditions)?;
+
+        Ok(SpendBundle::new(
+            vec![CoinSpend::new(coin, puzzle_reveal, solution)],
+            Signature::default(),
+        ))
+    }
+
+    #[test]
+    fn test_farm_block_batches_transactions() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let (puzzle_hash, _) = to_puzzle(1)?;
+
+        let a = sim.new_coin(puzzle_hash, 1000);
+        let b = sim.new_coin(puzzle_hash, 1000);
+
+        sim.add_to_mempool(spend(
+            a,
+            Conditions::new().with(CreateCoin::new(puzzle_hash, 900, Vec::new())),
+        )?)?;
+        sim.add_to_mempool(spend(
+            b,
+            Conditions::new().with(CreateCoin::new(puzzle_hash, 800, Vec::new())),
+        )?)?;
+
+        assert_eq!(sim.mempool_items().len(), 2);
+        assert_eq!(sim.coin_state(a.coin_id()).unwrap().spent_height, None);
+
+        let height = sim.height();
+        let updates = sim.farm_block();
+
+        assert_eq!(updates.len(), 4);
+        assert_eq!(sim.height(), height + 1);
+        assert!(sim.mempool_items().is_empty());
+        assert_eq!(
+            sim.coin_state(a.coin_id()).unwrap().spent_height,
+            Some(height)
+        );
+        assert_eq!(
+            sim.coin_state(b.coin_id()).unwrap().spent_height,
+            Some(height)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_replace_by_fee() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let (puzzle_hash, _) = to_puzzle(1)?;
+
+        let coin = sim.new_coin(puzzle_hash, 1_000_000_000);
+
+        let original = spend(
+            coin,
+            Conditions::new().with(CreateCoin::new(puzzle_hash, 999_000_000, Vec::new())),
+        )?;
+        let original_id = sim.add_to_mempool(original)?;
+
+        // The fee increase is too small.
+        let too_small = spend(
+            coin,
+            Conditions::new().with(CreateCoin::new(puzzle_hash, 998_000_000, Vec::new())),
+        )?;
+        assert_eq!(
+            sim.add_to_mempool(too_small).unwrap_err().error_code(),
+            Some(ErrorCode::MempoolConflict)
+        );
+
+        let replacement = spend(
+            coin,
+            Conditions::new().with(CreateCoin::new(puzzle_hash, 900_000_000, Vec::new())),
+        )?;
+        let replacement_id = sim.add_to_mempool(replacement)?;
+
+        assert!(sim.mempool_item(original_id).is_none());
+        assert_eq!(
+            sim.mempool_item(replacement_id)
+                .map(PendingTransaction::fee),
+            Some(100_000_000)
+        );
+
+        let updates = sim.farm_block();
+        let child = Coin::new(coin.coin_id(), puzzle_hash, 900_000_000);
+        assert!(updates.contains_key(&child.coin_id()));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_ephemeral_coin_in_block() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let (puzzle_hash, _) = to_puzzle(1)?;
+
+        let parent = sim.new_coin(puzzle_hash, 1000);
+        let child = Coin::new(parent.coin_id(), puzzle_hash, 1000);
+        let grandchild = Coin::new(child.coin_id(), puzzle_hash, 1000);
+
+        // The child spend pays a higher fee per cost, but depends on the parent spend.
+        sim.add_to_mempool(spend(
+            parent,
+            Conditions::new().with(CreateCoin::new(puzzle_hash, 1000, Vec::new())),
+        )?)?;
+        sim.add_to_mempool(spend(
+            child,
+            Conditions::new().with(CreateCoin::new(puzzle_hash, 500, Vec::new())),
+        )?)?;
+
+        let updates = sim.farm_block();
+        let height = sim.height() - 1;
+
+        assert_eq!(updates[&child.coin_id()].created_height, Some(height));
+        assert_eq!(updates[&child.coin_id()].spent_height, Some(height));
+        assert!(!updates.contains_key(&grandchild.coin_id()));
+        assert!(sim.mempool_items().is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_mempool_rejects_spent_coin() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let (puzzle_hash, _) = to_puzzle(1)?;
+
+        let coin = sim.new_coin(puzzle_hash, 1000);
+        sim.add_to_mempool(spend(coin, Conditions::new())?)?;
+
+        // Spending the coin directly makes the mempool item invalid.
+        sim.new_transaction(spend(
+            coin,
+            Conditions::new().with(CreateCoin::new(puzzle_hash, 1000, Vec::new())),
+        )?)?;
+        assert!(sim.mempool_items().is_empty());
+
+        assert_eq!(
+            sim.add_to_mempool(spend(coin, Conditions::new().with(ReserveFee::new(1)))?)
+                .unwrap_err()
+                .error_code(),
+            Some(ErrorCode::DoubleSpend)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_seconds_relative() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let (puzzle_hash, _) = to_puzzle(1)?;
+
+        let coin = sim.new_coin(puzzle_hash, 1000);
+        let bundle = spend(
+            coin,
+            Conditions::new().with(AssertSecondsRelative::new(100)),
+        )?;
+
+        assert_eq!(
+            sim.new_transaction(bundle.clone())
+                .unwrap_err()
+                .error_code(),
+            Some(ErrorCode::AssertSecondsRelativeFailed)
+        );
+
+        sim.pass_time(99);
+        assert!(sim.new_transaction(bundle.clone()).is_err());
+
+        sim.pass_time(1);
+        assert_eq!(sim.timestamp(), 100);
+        sim.new_transaction(bundle)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_relative_timelock_overflow() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let (puzzle_hash, _) = to_puzzle(1)?;
+
+        sim.pass_blocks(1);
+        sim.pass_time(100);
+        let coin = sim.new_coin(puzzle_hash, 1000);
+
+        // Timelocks which end past the maximum height or timestamp can never be satisfied.
+        let bundle = spend(
+            coin,
+            Conditions::new().with(AssertHeightRelative::new(u32::MAX)),
+        )?;
+        assert_eq!(
+            sim.new_transaction(bundle).unwrap_err().error_code(),
+            Some(ErrorCode::AssertHeightRelativeFailed)
+        );
+
+        let bundle = spend(
+            coin,
+            Conditions::new().with(AssertSecondsRelative::new(u64::MAX)),
+        )?;
+        assert_eq!(
+            sim.new_transaction(bundle).unwrap_err().error_code(),
+            Some(ErrorCode::AssertSecondsRelativeFailed)
+        );
+
+        // Likewise, they never expire.
+        let bundle = spend(
+            coin,
+            Conditions::new()
+                .with(AssertBeforeHeightRelative::new(u32::MAX))
+                .with(AssertBeforeSecondsRelative::new(u64::MAX)),
+        )?;
+        sim.new_transaction(bundle)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_mempool_mode() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let (puzzle_hash, puzzle_reveal) = to_puzzle(1)?;
+
+        // Unknown conditions are only rejected in mempool mode.
+        let coin = sim.new_coin(puzzle_hash, 1000);
+        let bundle = SpendBundle::new(
+            vec![CoinSpend::new(
+                coin,
+                puzzle_reveal,
+                to_program([(1000, ())])?,
+            )],
+            Signature::default(),
+        );
+
+        assert_eq!(
+            sim.new_transaction(bundle).unwrap_err().error_code(),
+            Some(ErrorCode::InvalidConditionOpcode)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_height_timelocks_in_mempool() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let (puzzle_hash, _) = to_puzzle(1)?;
+
+        let a = sim.new_coin(puzzle_hash, 1000);
+        let b = sim.new_coin(puzzle_hash, 1000);
+
+        let locked = sim.add_to_mempool(spend(
+            a,
+            Conditions::new().with(AssertHeightAbsolute::new(5)),
+        )?)?;
+        let expiring = sim.add_to_mempool(spend(
+            b,
+            Conditions::new().with(AssertBeforeHeightAbsolute::new(3)),
+        )?)?;
+
+        sim.pass_blocks(3);
+        assert_eq!(sim.timestamp(), 0);
+
+        // The expired transaction is evicted, and the locked one waits.
+        assert!(sim.farm_block().is_empty());
+        assert!(sim.mempool_item(expiring).is_none());
+        assert!(sim.mempool_item(locked).is_some());
+
+        sim.pass_blocks(1);
+        let updates = sim.farm_block();
+        assert!(updates.contains_key(&a.coin_id()));
+        assert!(sim.mempool_items().is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_rewind() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let (puzzle_hash, puzzle_reveal) = to_puzzle(1)?;
+        let hint = Bytes32::new([42; 32]);
+
+        let coin = sim.new_coin(puzzle_hash, 1000);
+        let child = Coin::new(coin.coin_id(), puzzle_hash, 1000);
+
+        sim.pass_time(10);
+        let header_hash = sim.header_hash();
+        let before = sim.clone();
+
+        sim.new_transaction(spend(
+            coin,
+            Conditions::new().with(CreateCoin::new(puzzle_hash, 1000, vec![hint.into()])),
+        )?)?;
+        sim.new_coin(puzzle_hash, 500);
+        sim.pass_time(10);
+
+        assert_eq!(sim.height(), 3);
+        assert_eq!(sim.hinted_coins(hint), vec![child.coin_id()]);
+
+        let updates = sim.rewind_to(1);
+
+        assert_eq!(sim.height(), 1);
+        assert_eq!(sim.header_hash(), header_hash);
+        assert_eq!(sim.timestamp(), 10);
+        assert_eq!(updates.len(), 3);
+        assert_eq!(
+            updates[&coin.coin_id()],
+            CoinState::new(coin, None, Some(0))
+        );
+        assert_eq!(updates[&child.coin_id()], CoinState::new(child, None, None));
+
+        assert!(sim.hinted_coins(hint).is_empty());
+        assert!(sim.puzzle_reveal(coin.coin_id()).is_none());
+        assert_eq!(sim.coin_states, before.coin_states);
+        assert_eq!(sim.hinted_coins, before.hinted_coins);
+
+        // The coin can be spent again on the new chain.
+        sim.new_transaction(SpendBundle::new(
+            vec![CoinSpend::new(coin, puzzle_reveal, to_program(())?)],
+            Signature::default(),
+        ))?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_reorg() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let (puzzle_hash, _) = to_puzzle(1)?;
+
+        let coin = sim.new_coin(puzzle_hash, 1000);
+        sim.pass_blocks(1);
+        let pending = sim.new_coin(puzzle_hash, 1000);
+
+        let bundle = spend(
+            coin,
+            Conditions::new().with(CreateCoin::new(puzzle_hash, 900, Vec::new())),
+        )?;
+        sim.new_transaction(bundle.clone())?;
+
+        let orphaned = sim.header_hash();
+        sim.add_to_mempool(spend(pending, Conditions::new())?)?;
+
+        // The transaction is undone, and the new blocks include the pending one instead.
+        let updates = sim.reorg(1, 2);
+
+        assert_eq!(sim.height(), 3);
+        assert_ne!(sim.header_hash_of(2), Some(orphaned));
+        assert_eq!(updates[&coin.coin_id()].spent_height, None);
+        assert!(sim.mempool_items().is_empty());
+
+        sim.new_transaction(bundle)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_cost_limits() -> anyhow::Result<()> {
+        let (puzzle_hash, _) = to_puzzle(1)?;
+
+        let mut sim = Simulator::new();
+        let coin = sim.new_coin(puzzle_hash, 1000);
+        let cost = sim.validate(spend(coin, Conditions::new())?)?.cost();
+
+        let mut sim = Simulator::with_config(SimulatorConfig {
+            max_bundle_cost: cost - 1,
+            ..SimulatorConfig::default()
+        });
+        let coin = sim.new_coin(puzzle_hash, 1000);
+
+        assert_eq!(
+            sim.new_transaction(spend(coin, Conditions::new())?)
+                .unwrap_err()
+                .error_code(),
+            Some(ErrorCode::CostExceeded)
+        );
+
+        // Only one of the transactions fits in each block.
+        let mut sim = Simulator::with_config(SimulatorConfig {
+            max_block_cost: cost,
+            ..SimulatorConfig::default()
+        });
+        let a = sim.new_coin(puzzle_hash, 1000);
+        let b = sim.new_coin(puzzle_hash, 1000);
+
+        sim.add_to_mempool(spend(a, Conditions::new())?)?;
+        sim.add_to_mempool(spend(b, Conditions::new())?)?;
+
+        assert_eq!(sim.farm_block().len(), 1);
+        assert_eq!(sim.mempool_items().len(), 1);
+        assert_eq!(sim.farm_block().len(), 1);
+        assert!(sim.mempool_items().is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_mainnet_signatures() -> anyhow::Result<()> {
+        let (puzzle_hash, puzzle_reveal) = to_puzzle(1)?;
+        let sk = test_secret_key()?;
+
+        let coin_spends = |coin: Coin| -> anyhow::Result<Vec<CoinSpend>> {
+            Ok(vec![CoinSpend::new(
+                coin,
+                puzzle_reveal.clone(),
+                to_program([AggSigMe::new(sk.public_key(), b"hello".to_vec().into())])?,
+            )])
+        };
+
+        let mut sim = Simulator::with_config(SimulatorConfig::mainnet());
+        let coin = sim.new_coin(puzzle_hash, 1000);
+        sim.spend_coins(coin_spends(coin)?, &[sk.clone()])?;
+
+        // Signatures for testnet aren't valid on mainnet.
+        let coin = sim.new_coin(puzzle_hash, 1000);
+        let coin_spends = coin_spends(coin)?;
+        let signature = sign_transaction(&coin_spends, &[sk])?;
+
+        assert_eq!(
+            sim.new_transaction(SpendBundle::new(coin_spends, signature))
+                .unwrap_err()
+                .error_code(),
+            Some(ErrorCode::BadAggregateSignature)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_validation_error_context() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let (puzzle_hash, puzzle_reveal) = to_puzzle(1)?;
+
+        let a = sim.new_coin(puzzle_hash, 1000);
+        let b = sim.new_coin(puzzle_hash, 1000);
+
+        let mut bundle = spend(a, Conditions::new())?;
+        bundle.coin_spends.extend(
+            spend(
+                b,
+                Conditions::new()
+                    .with(ReserveFee::new(0))
+                    .with(AssertSecondsRelative::new(100)),
+            )?
+            .coin_spends,
+        );
+
+        let SimulatorError::Validation(error) = sim.new_transaction(bundle).unwrap_err() else {
+            panic!("expected a validation error");
+        };
+
+        assert_eq!(error.code, ErrorCode::AssertSecondsRelativeFailed);
+        assert_eq!(error.spend_index, Some(1));
+        assert_eq!(error.coin_id, Some(b.coin_id()));
+        assert_eq!(
+            error.condition,
+            Some(AssertSecondsRelative::new(100).into())
+        );
+
+        // Signatures which can't be made with the given keys are reported.
+        let sk = test_secret_key()?;
+        let coin = sim.new_coin(puzzle_hash, 1000);
+
+        let SimulatorError::Validation(error) = sim
+            .spend_coins(
+                vec![CoinSpend::new(
+                    coin,
+                    puzzle_reveal,
+                    to_program([AggSigMe::new(sk.public_key(), b"hello".to_vec().into())])?,
+                )],
+                &[],
+            )
+            .unwrap_err()
+        else {
+            panic!("expected a validation error");
+        };
+
+        assert_eq!(error.code, ErrorCode::BadAggregateSignature);
+        assert_eq!(error.coin_id, Some(coin.coin_id()));
+        assert_eq!(error.required_signatures.len(), 1);
+        assert_eq!(error.required_signatures[0].public_key(), sk.public_key());
+
+        Ok(())
+    }
 }
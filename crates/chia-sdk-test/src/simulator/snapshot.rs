@@ -0,0 +1,313 @@
+use std::{fs, io::Cursor, path::Path};
+
+use chia_consensus::consensus_constants::ConsensusConstants;
+use chia_protocol::{Bytes32, CoinState, NodeType, Program, SpendBundle};
+use chia_traits::Streamable;
+use fastrand::Rng;
+
+use crate::{SimulatorConfig, SimulatorError};
+
+use super::{Journal, Simulator};
+
+const SNAPSHOT_VERSION: u32 = 2;
+
+type JournalData = (
+    Vec<(Bytes32, Option<CoinState>)>,
+    Vec<(Bytes32, Bytes32)>,
+    Vec<(Bytes32, Option<(Program, Program)>)>,
+);
+
+/// A copy of the full state of a [`Simulator`], which can be restored later or saved to a file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SimulatorSnapshot(Simulator);
+
+impl Simulator {
+    /// Takes a snapshot of the current state, including the mempool and the history needed to rewind.
+    pub fn snapshot(&self) -> SimulatorSnapshot {
+        SimulatorSnapshot(self.clone())
+    }
+
+    /// Replaces the current state with a snapshot, so that it can be restored more than once.
+    pub fn restore(&mut self, snapshot: &SimulatorSnapshot) {
+        *self = snapshot.0.clone();
+    }
+}
+
+impl From<SimulatorSnapshot> for Simulator {
+    fn from(snapshot: SimulatorSnapshot) -> Self {
+        snapshot.0
+    }
+}
+
+impl SimulatorSnapshot {
+    /// Saves the snapshot to a file, creating or truncating it.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), SimulatorError> {
+        fs::write(path, self.to_bytes()?)?;
+        Ok(())
+    }
+
+    /// Loads a snapshot which was previously saved to a file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, SimulatorError> {
+        Self::from_bytes(&fs::read(path)?)
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>, SimulatorError> {
+        let sim = &self.0;
+        let config = &sim.config;
+        let mut out = Vec::new();
+
+        SNAPSHOT_VERSION.stream(&mut out)?;
+
+        config.constants.stream(&mut out)?;
+        config.seed.stream(&mut out)?;
+        config.max_block_cost.stream(&mut out)?;
+        config.max_bundle_cost.stream(&mut out)?;
+        config.flags.stream(&mut out)?;
+        (config.max_subscriptions as u64).stream(&mut out)?;
+        (config.max_response_coins as u64).stream(&mut out)?;
+        (config.puzzle_state_batch_size as u64).stream(&mut out)?;
+        config.network_id.stream(&mut out)?;
+        config.node_type.stream(&mut out)?;
+
+        sim.rng.get_seed().stream(&mut out)?;
+        sim.height.stream(&mut out)?;
+        sim.header_hashes.stream(&mut out)?;
+        sim.timestamps.stream(&mut out)?;
+
+        let coin_states: Vec<CoinState> = sim.coin_states.values().copied().collect();
+        coin_states.stream(&mut out)?;
+
+        let hinted_coins: Vec<(Bytes32, Vec<Bytes32>)> = sim
+            .hinted_coins
+            .iter()
+            .map(|(hint, coin_ids)| (*hint, coin_ids.iter().copied().collect()))
+            .collect();
+        hinted_coins.stream(&mut out)?;
+
+        let puzzle_and_solutions: Vec<(Bytes32, (Program, Program))> = sim
+            .puzzle_and_solutions
+            .iter()
+            .map(|(coin_id, puzzle_and_solution)| (*coin_id, puzzle_and_solution.clone()))
+            .collect();
+        puzzle_and_solutions.stream(&mut out)?;
+
+        let mempool: Vec<SpendBundle> = sim
+            .mempool
+            .values()
+            .map(|item| item.spend_bundle.clone())
+            .collect();
+        mempool.stream(&mut out)?;
+
+        let inserted: Vec<JournalData> = sim.inserted.iter().map(Journal::to_data).collect();
+        inserted.stream(&mut out)?;
+
+        let blocks: Vec<JournalData> = sim.blocks.iter().map(Journal::to_data).collect();
+        blocks.stream(&mut out)?;
+
+        Ok(out)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SimulatorError> {
+        let mut input = Cursor::new(bytes);
+
+        let version = u32::parse::<false>(&mut input)?;
+
+        if version != SNAPSHOT_VERSION {
+            return Err(SimulatorError::UnsupportedSnapshotVersion(version));
+        }
+
+        let config = SimulatorConfig {
+            constants: ConsensusConstants::parse::<false>(&mut input)?,
+            seed: u64::parse::<false>(&mut input)?,
+            max_block_cost: u64::parse::<false>(&mut input)?,
+            max_bundle_cost: u64::parse::<false>(&mut input)?,
+            flags: u32::parse::<false>(&mut input)?,
+            max_subscriptions: parse_usize(&mut input)?,
+            max_response_coins: parse_usize(&mut input)?,
+            puzzle_state_batch_size: parse_usize(&mut input)?,
+            network_id: String::parse::<false>(&mut input)?,
+            node_type: NodeType::parse::<false>(&mut input)?,
+            ..SimulatorConfig::default()
+        };
+
+        let mut sim = Simulator::with_config(config);
+
+        sim.rng = Rng::with_seed(u64::parse::<false>(&mut input)?);
+        sim.height = u32::parse::<false>(&mut input)?;
+        sim.header_hashes = Vec::parse::<false>(&mut input)?;
+        sim.timestamps = Vec::parse::<false>(&mut input)?;
+
+        sim.coin_states = Vec::<CoinState>::parse::<false>(&mut input)?
+            .into_iter()
+            .map(|coin_state| (coin_state.coin.coin_id(), coin_state))
+            .collect();
+
+        sim.hinted_coins = Vec::<(Bytes32, Vec<Bytes32>)>::parse::<false>(&mut input)?
+            .into_iter()
+            .map(|(hint, coin_ids)| (hint, coin_ids.into_iter().collect()))
+            .collect();
+
+        sim.puzzle_and_solutions =
+            Vec::<(Bytes32, (Program, Program))>::parse::<false>(&mut input)?
+                .into_iter()
+                .collect();
+
+        let mempool = Vec::<SpendBundle>::parse::<false>(&mut input)?;
+
+        sim.inserted = Vec::<JournalData>::parse::<false>(&mut input)?
+            .into_iter()
+            .map(Journal::from_data)
+            .collect();
+
+        sim.blocks = Vec::<JournalData>::parse::<false>(&mut input)?
+            .into_iter()
+            .map(Journal::from_data)
+            .collect();
+
+        if input.position() != bytes.len() as u64 {
+            return Err(chia_traits::Error::InputTooLarge.into());
+        }
+
+        if sim.header_hashes.len() != sim.height as usize + 1
+            || sim.timestamps.len() != sim.header_hashes.len()
+            || sim.inserted.len() != sim.header_hashes.len()
+            || sim.blocks.len() != sim.height as usize
+        {
+            return Err(SimulatorError::InvalidSnapshot);
+        }
+
+        // The mempool items are validated again, since their conditions aren't saved.
+        for spend_bundle in mempool {
+            let transaction = sim.validate(spend_bundle)?;
+            sim.mempool.insert(transaction.transaction_id, transaction);
+        }
+
+        Ok(Self(sim))
+    }
+}
+
+impl Journal {
+    fn to_data(&self) -> JournalData {
+        (
+            self.coin_states.clone(),
+            self.hints.clone(),
+            self.puzzle_and_solutions.clone(),
+        )
+    }
+
+    fn from_data((coin_states, hints, puzzle_and_solutions): JournalData) -> Self {
+        Self {
+            coin_states,
+            hints,
+            puzzle_and_solutions,
+        }
+    }
+}
+
+fn parse_usize(input: &mut Cursor<&[u8]>) -> Result<usize, SimulatorError> {
+    usize::try_from(u64::parse::<false>(input)?).map_err(|_| SimulatorError::InvalidSnapshot)
+}
+
+#[cfg(test)]
+mod tests {
+    use chia_bls::Signature;
+    use chia_protocol::{Coin, CoinSpend};
+    use chia_sdk_types::{Conditions, CreateCoin};
+
+    use crate::{to_program, to_puzzle};
+
+    use super::*;
+
+    #[test]
+    fn test_snapshot_restore() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let (puzzle_hash, puzzle_reveal) = to_puzzle(1)?;
+
+        let coin = sim.new_coin(puzzle_hash, 1000);
+        let snapshot = sim.snapshot();
+
+        let spend_bundle = SpendBundle::new(
+            vec![CoinSpend::new(coin, puzzle_reveal, to_program(())?)],
+            Signature::default(),
+        );
+
+        sim.new_transaction(spend_bundle.clone())?;
+        assert!(sim
+            .coin_state(coin.coin_id())
+            .unwrap()
+            .spent_height
+            .is_some());
+
+        // The same snapshot can be restored more than once.
+        for _ in 0..2 {
+            sim.restore(&snapshot);
+            assert_eq!(sim.height(), 0);
+            assert!(sim
+                .coin_state(coin.coin_id())
+                .unwrap()
+                .spent_height
+                .is_none());
+            sim.new_transaction(spend_bundle.clone())?;
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_snapshot_file() -> anyhow::Result<()> {
+        let mut sim = Simulator::with_config(SimulatorConfig {
+            node_type: NodeType::Wallet,
+            ..SimulatorConfig::mainnet()
+        });
+        let (puzzle_hash, puzzle_reveal) = to_puzzle(1)?;
+        let hint = Bytes32::new([42; 32]);
+
+        let coin = sim.new_coin(puzzle_hash, 1000);
+        let pending = sim.new_coin(puzzle_hash, 1000);
+
+        sim.new_transaction(SpendBundle::new(
+            vec![CoinSpend::new(
+                coin,
+                puzzle_reveal.clone(),
+                to_program(Conditions::new().with(CreateCoin::new(
+                    puzzle_hash,
+                    1000,
+                    vec![hint.into()],
+                )))?,
+            )],
+            Signature::default(),
+        ))?;
+        sim.pass_time(60);
+        sim.add_to_mempool(SpendBundle::new(
+            vec![CoinSpend::new(pending, puzzle_reveal, to_program(())?)],
+            Signature::default(),
+        ))?;
+
+        let dir =
+            std::env::temp_dir().join(format!("chia-sdk-test-snapshot-{}", std::process::id()));
+        fs::create_dir_all(&dir)?;
+        let path = dir.join("simulator.bin");
+
+        sim.snapshot().save(&path)?;
+        let mut loaded = Simulator::from(SimulatorSnapshot::load(&path)?);
+        fs::remove_dir_all(&dir)?;
+
+        assert_eq!(loaded, sim);
+        assert_eq!(loaded.config().network_id, "mainnet");
+        assert_eq!(loaded.config().node_type, NodeType::Wallet);
+
+        // The loaded simulator continues deterministically, and can still rewind.
+        let child = Coin::new(coin.coin_id(), puzzle_hash, 1000);
+        assert_eq!(
+            loaded.new_coin(puzzle_hash, 1),
+            sim.new_coin(puzzle_hash, 1)
+        );
+        assert_eq!(loaded.farm_block(), sim.farm_block());
+        assert_eq!(loaded.hinted_coins(hint), vec![child.coin_id()]);
+
+        loaded.rewind_to(0);
+        assert!(loaded.coin_state(child.coin_id()).is_none());
+
+        Ok(())
+    }
+}
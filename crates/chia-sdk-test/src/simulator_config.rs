@@ -0,0 +1,81 @@
+use std::time::Duration;
+
+use chia_consensus::consensus_constants::ConsensusConstants;
+use chia_protocol::{NodeType, ProtocolMessageTypes, RejectStateReason};
+use chia_sdk_types::{MAINNET_CONSTANTS, TESTNET11_CONSTANTS};
+use indexmap::IndexMap;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SimulatorConfig {
+    /// The consensus constants, which determine the genesis challenge and the additional data
+    /// that `AGG_SIG_*` conditions are signed with.
+    pub constants: ConsensusConstants,
+    /// The seed used to generate coin parents and header hashes.
+    pub seed: u64,
+    /// The maximum total cost of the spend bundles included in a single block.
+    pub max_block_cost: u64,
+    /// The maximum cost of a single spend bundle.
+    pub max_bundle_cost: u64,
+    /// Additional flags to run spend bundles with, on top of the mempool mode flags,
+    /// which are always applied since spend bundles are validated as the mempool would.
+    pub flags: u32,
+    pub max_subscriptions: usize,
+    pub max_response_coins: usize,
+    pub puzzle_state_batch_size: usize,
+    /// The network id which the peer simulator responds to handshakes with.
+    pub network_id: String,
+    /// The node type which the peer simulator responds to handshakes with.
+    pub node_type: NodeType,
+    /// Faults which the peer simulator injects when it receives a message of the given type.
+    /// These don't affect the [`Simulator`](crate::Simulator) itself, and aren't saved in snapshots.
+    pub faults: IndexMap<ProtocolMessageTypes, PeerFault>,
+}
+
+/// A fault which the peer simulator injects instead of responding normally to a message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PeerFault {
+    /// Waits for the given duration before responding. Later messages from the same peer
+    /// are handled after the delay as well, since each connection is handled in order.
+    Delay(Duration),
+    /// Silently ignores the message, without responding.
+    Drop,
+    /// Responds with a rejection for the given reason.
+    /// Only supported for `RequestPuzzleState` and `RequestCoinState`.
+    Reject(RejectStateReason),
+    /// Closes the connection without responding.
+    Disconnect,
+    /// Responds to a `Handshake` with the given network id, instead of the configured one.
+    WrongNetwork(String),
+    /// Sends the current peak this many times as unsolicited messages before responding,
+    /// in order to exceed the inbound rate limit of the peer.
+    Flood(u32),
+}
+
+impl SimulatorConfig {
+    /// The default config, but with mainnet consensus constants and network id.
+    pub fn mainnet() -> Self {
+        Self {
+            constants: MAINNET_CONSTANTS.clone(),
+            network_id: "mainnet".to_string(),
+            ..Self::default()
+        }
+    }
+}
+
+impl Default for SimulatorConfig {
+    fn default() -> Self {
+        Self {
+            constants: TESTNET11_CONSTANTS.clone(),
+            seed: 1337,
+            max_block_cost: 11_000_000_000,
+            max_bundle_cost: 7_700_000_000,
+            flags: 0,
+            max_subscriptions: 200_000,
+            max_response_coins: 100_000,
+            puzzle_state_batch_size: 30_000,
+            network_id: "testnet11".to_string(),
+            node_type: NodeType::FullNode,
+            faults: IndexMap::new(),
+        }
+    }
+}
@@ -0,0 +1,538 @@
+use std::fmt;
+
+use chia_bls::{PublicKey, SecretKey};
+use chia_protocol::{Bytes, Bytes32, Coin, Program};
+use chia_puzzles::{cat::CatArgs, nft::NftMetadata, LineageProof};
+use chia_sdk_driver::{Cat, DataStoreMetadata, DidInfo, NftInfo};
+use chia_sdk_types::{
+    AggSigAmount, AggSigMe, AggSigParent, AggSigParentAmount, AggSigParentPuzzle, AggSigPuzzle,
+    AggSigPuzzleAmount, AggSigUnsafe, AssertBeforeHeightAbsolute, AssertBeforeHeightRelative,
+    AssertBeforeSecondsAbsolute, AssertBeforeSecondsRelative, AssertCoinAnnouncement,
+    AssertConcurrentPuzzle, AssertConcurrentSpend, AssertEphemeral, AssertHeightAbsolute,
+    AssertHeightRelative, AssertMyAmount, AssertMyBirthHeight, AssertMyBirthSeconds,
+    AssertMyCoinId, AssertMyParentId, AssertMyPuzzleHash, AssertPuzzleAnnouncement,
+    AssertSecondsAbsolute, AssertSecondsRelative, Condition, Conditions, CreateCoin,
+    CreateCoinAnnouncement, CreatePuzzleAnnouncement, MeltSingleton, ReceiveMessage, Remark,
+    ReserveFee, RunCatTail, SendMessage, Softfork, TradePrice, TransferNft,
+    UpdateDataStoreMerkleRoot, UpdateNftMetadata,
+};
+use clvm_traits::{FromClvm, ToClvm};
+use clvmr::Allocator;
+use proptest::{collection::vec, option, prelude::*};
+
+pub fn arb_bytes32() -> impl Strategy<Value = Bytes32> {
+    any::<[u8; 32]>().prop_map(Bytes32::new)
+}
+
+pub fn arb_bytes() -> impl Strategy<Value = Bytes> {
+    vec(any::<u8>(), 0..64).prop_map(Bytes::new)
+}
+
+pub fn arb_coin() -> impl Strategy<Value = Coin> {
+    (arb_bytes32(), arb_bytes32(), any::<u64>()).prop_map(
+        |(parent_coin_info, puzzle_hash, amount)| Coin::new(parent_coin_info, puzzle_hash, amount),
+    )
+}
+
+/// A valid public key, derived from a random secret key.
+pub fn arb_public_key() -> impl Strategy<Value = PublicKey> {
+    any::<[u8; 32]>().prop_map(|seed| SecretKey::from_seed(&seed).public_key())
+}
+
+pub fn arb_lineage_proof() -> impl Strategy<Value = LineageProof> {
+    (arb_bytes32(), arb_bytes32(), any::<u64>()).prop_map(
+        |(parent_parent_coin_info, parent_inner_puzzle_hash, parent_amount)| LineageProof {
+            parent_parent_coin_info,
+            parent_inner_puzzle_hash,
+            parent_amount,
+        },
+    )
+}
+
+/// A CLVM list of random atoms.
+pub fn arb_program() -> impl Strategy<Value = Program> {
+    vec(vec(any::<u8>(), 0..8), 0..4).prop_map(|atoms| {
+        let mut allocator = Allocator::new();
+        let atoms: Vec<Bytes> = atoms.into_iter().map(Bytes::new).collect();
+        let ptr = atoms.to_clvm(&mut allocator).unwrap();
+        Program::from_clvm(&allocator, ptr).unwrap()
+    })
+}
+
+/// Any condition which can be output by a puzzle, including the ones used by the standard
+/// singleton, CAT, NFT and data layer puzzles.
+pub fn arb_condition() -> impl Strategy<Value = Condition<Program>> {
+    prop_oneof![
+        arb_program().prop_map(|rest| Remark::new(rest).into()),
+        (arb_public_key(), arb_bytes()).prop_map(|(pk, msg)| AggSigParent::new(pk, msg).into()),
+        (arb_public_key(), arb_bytes()).prop_map(|(pk, msg)| AggSigPuzzle::new(pk, msg).into()),
+        (arb_public_key(), arb_bytes()).prop_map(|(pk, msg)| AggSigAmount::new(pk, msg).into()),
+        (arb_public_key(), arb_bytes())
+            .prop_map(|(pk, msg)| AggSigPuzzleAmount::new(pk, msg).into()),
+        (arb_public_key(), arb_bytes())
+            .prop_map(|(pk, msg)| AggSigParentAmount::new(pk, msg).into()),
+        (arb_public_key(), arb_bytes())
+            .prop_map(|(pk, msg)| AggSigParentPuzzle::new(pk, msg).into()),
+        (arb_public_key(), arb_bytes()).prop_map(|(pk, msg)| AggSigUnsafe::new(pk, msg).into()),
+        (arb_public_key(), arb_bytes()).prop_map(|(pk, msg)| AggSigMe::new(pk, msg).into()),
+        (arb_bytes32(), any::<u64>(), vec(arb_bytes(), 0..3)).prop_map(
+            |(puzzle_hash, amount, memos)| CreateCoin::new(puzzle_hash, amount, memos).into()
+        ),
+        any::<u64>().prop_map(|amount| ReserveFee::new(amount).into()),
+        arb_bytes().prop_map(|message| CreateCoinAnnouncement::new(message).into()),
+        arb_bytes32().prop_map(|id| AssertCoinAnnouncement::new(id).into()),
+        arb_bytes().prop_map(|message| CreatePuzzleAnnouncement::new(message).into()),
+        arb_bytes32().prop_map(|id| AssertPuzzleAnnouncement::new(id).into()),
+        arb_bytes32().prop_map(|coin_id| AssertConcurrentSpend::new(coin_id).into()),
+        arb_bytes32().prop_map(|puzzle_hash| AssertConcurrentPuzzle::new(puzzle_hash).into()),
+        (0..64u8, arb_bytes(), vec(arb_program(), 0..3))
+            .prop_map(|(mode, message, data)| SendMessage::new(mode, message, data).into()),
+        (0..64u8, arb_bytes(), vec(arb_program(), 0..3))
+            .prop_map(|(mode, message, data)| ReceiveMessage::new(mode, message, data).into()),
+        arb_bytes32().prop_map(|coin_id| AssertMyCoinId::new(coin_id).into()),
+        arb_bytes32().prop_map(|parent_id| AssertMyParentId::new(parent_id).into()),
+        arb_bytes32().prop_map(|puzzle_hash| AssertMyPuzzleHash::new(puzzle_hash).into()),
+        any::<u64>().prop_map(|amount| AssertMyAmount::new(amount).into()),
+        any::<u64>().prop_map(|seconds| AssertMyBirthSeconds::new(seconds).into()),
+        any::<u32>().prop_map(|height| AssertMyBirthHeight::new(height).into()),
+        Just(AssertEphemeral::new().into()),
+        any::<u64>().prop_map(|seconds| AssertSecondsRelative::new(seconds).into()),
+        any::<u64>().prop_map(|seconds| AssertSecondsAbsolute::new(seconds).into()),
+        any::<u32>().prop_map(|height| AssertHeightRelative::new(height).into()),
+        any::<u32>().prop_map(|height| AssertHeightAbsolute::new(height).into()),
+        any::<u64>().prop_map(|seconds| AssertBeforeSecondsRelative::new(seconds).into()),
+        any::<u64>().prop_map(|seconds| AssertBeforeSecondsAbsolute::new(seconds).into()),
+        any::<u32>().prop_map(|height| AssertBeforeHeightRelative::new(height).into()),
+        any::<u32>().prop_map(|height| AssertBeforeHeightAbsolute::new(height).into()),
+        (any::<u64>(), arb_program()).prop_map(|(cost, rest)| Softfork::new(cost, rest).into()),
+        Just(MeltSingleton::new().into()),
+        (
+            option::of(arb_bytes32()),
+            vec(
+                (any::<u64>(), arb_bytes32()).prop_map(|(amount, puzzle_hash)| TradePrice {
+                    amount,
+                    puzzle_hash
+                }),
+                0..3
+            ),
+            option::of(arb_bytes32()),
+        )
+            .prop_map(|(did_id, trade_prices, did_inner_puzzle_hash)| {
+                TransferNft::new(did_id, trade_prices, did_inner_puzzle_hash).into()
+            }),
+        (arb_program(), arb_program())
+            .prop_map(|(program, solution)| RunCatTail::new(program, solution).into()),
+        (arb_program(), arb_program())
+            .prop_map(|(puzzle, solution)| { UpdateNftMetadata::new(puzzle, solution).into() }),
+        (arb_bytes32(), vec(arb_bytes(), 0..3))
+            .prop_map(|(root, memos)| { UpdateDataStoreMerkleRoot::new(root, memos).into() }),
+    ]
+}
+
+pub fn arb_conditions() -> impl Strategy<Value = Conditions<Program>> {
+    vec(arb_condition(), 0..8).prop_map(|conditions| Conditions::default().extend(conditions))
+}
+
+pub fn arb_nft_metadata() -> impl Strategy<Value = NftMetadata> {
+    (
+        any::<u64>(),
+        any::<u64>(),
+        vec(any::<String>(), 0..3),
+        option::of(arb_bytes32()),
+        vec(any::<String>(), 0..3),
+        option::of(arb_bytes32()),
+        vec(any::<String>(), 0..3),
+        option::of(arb_bytes32()),
+    )
+        .prop_map(
+            |(
+                edition_number,
+                edition_total,
+                data_uris,
+                data_hash,
+                metadata_uris,
+                metadata_hash,
+                license_uris,
+                license_hash,
+            )| NftMetadata {
+                edition_number,
+                edition_total,
+                data_uris,
+                data_hash,
+                metadata_uris,
+                metadata_hash,
+                license_uris,
+                license_hash,
+            },
+        )
+}
+
+pub fn arb_data_store_metadata() -> impl Strategy<Value = DataStoreMetadata> {
+    (
+        arb_bytes32(),
+        option::of(any::<String>()),
+        option::of(any::<String>()),
+        option::of(any::<u64>()),
+    )
+        .prop_map(|(root_hash, label, description, bytes)| DataStoreMetadata {
+            root_hash,
+            label,
+            description,
+            bytes,
+        })
+}
+
+pub fn arb_did_info<M>(metadata: impl Strategy<Value = M>) -> impl Strategy<Value = DidInfo<M>>
+where
+    M: fmt::Debug,
+{
+    (
+        arb_bytes32(),
+        option::of(arb_bytes32()),
+        any::<u64>(),
+        metadata,
+        arb_bytes32(),
+    )
+        .prop_map(
+            |(
+                launcher_id,
+                recovery_list_hash,
+                num_verifications_required,
+                metadata,
+                p2_puzzle_hash,
+            )| {
+                DidInfo::new(
+                    launcher_id,
+                    recovery_list_hash,
+                    num_verifications_required,
+                    metadata,
+                    p2_puzzle_hash,
+                )
+            },
+        )
+}
+
+pub fn arb_nft_info<M>(metadata: impl Strategy<Value = M>) -> impl Strategy<Value = NftInfo<M>>
+where
+    M: fmt::Debug,
+{
+    (
+        arb_bytes32(),
+        metadata,
+        arb_bytes32(),
+        option::of(arb_bytes32()),
+        arb_bytes32(),
+        0..=10_000u16,
+        arb_bytes32(),
+    )
+        .prop_map(
+            |(
+                launcher_id,
+                metadata,
+                metadata_updater_puzzle_hash,
+                current_owner,
+                royalty_puzzle_hash,
+                royalty_ten_thousandths,
+                p2_puzzle_hash,
+            )| {
+                NftInfo::new(
+                    launcher_id,
+                    metadata,
+                    metadata_updater_puzzle_hash,
+                    current_owner,
+                    royalty_puzzle_hash,
+                    royalty_ten_thousandths,
+                    p2_puzzle_hash,
+                )
+            },
+        )
+}
+
+/// Between one and four CATs of the same asset id, which can be spent together in a ring.
+///
+/// Each CAT's parent is consistent with its lineage proof, so the ring is valid on chain
+/// once the coins exist and are spent with the given p2 puzzle.
+pub fn arb_cat_ring(p2_puzzle_hash: Bytes32) -> impl Strategy<Value = Vec<Cat>> {
+    (
+        arb_bytes32(),
+        vec((arb_lineage_proof(), 1..=1_000_000_000_000u64), 1..=4),
+    )
+        .prop_map(move |(asset_id, cats)| {
+            let puzzle_hash = CatArgs::curry_tree_hash(asset_id, p2_puzzle_hash.into()).into();
+
+            cats.into_iter()
+                .map(|(lineage_proof, amount)| {
+                    let parent = Coin::new(
+                        lineage_proof.parent_parent_coin_info,
+                        CatArgs::curry_tree_hash(
+                            asset_id,
+                            lineage_proof.parent_inner_puzzle_hash.into(),
+                        )
+                        .into(),
+                        lineage_proof.parent_amount,
+                    );
+
+                    Cat::new(
+                        Coin::new(parent.coin_id(), puzzle_hash, amount),
+                        Some(lineage_proof),
+                        asset_id,
+                        p2_puzzle_hash,
+                    )
+                })
+                .collect()
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use chia_puzzles::{singleton::SingletonArgs, Proof};
+    use chia_sdk_driver::{CatLayer, CatSpend, Did, Layer, Nft, Puzzle, Spend, SpendContext};
+    use clvm_utils::tree_hash_atom;
+    use proptest::test_runner::Config;
+
+    use crate::Simulator;
+
+    use super::*;
+
+    fn round_trip<T>(value: &T) -> T
+    where
+        T: ToClvm<Allocator> + FromClvm<Allocator>,
+    {
+        let mut allocator = Allocator::new();
+        let ptr = value.to_clvm(&mut allocator).unwrap();
+        T::from_clvm(&allocator, ptr).unwrap()
+    }
+
+    /// A singleton coin for the given inner puzzle hash, whose parent matches the lineage proof.
+    fn singleton_coin(
+        launcher_id: Bytes32,
+        inner_puzzle_hash: Bytes32,
+        lineage_proof: LineageProof,
+        amount: u64,
+    ) -> Coin {
+        let parent = Coin::new(
+            lineage_proof.parent_parent_coin_info,
+            SingletonArgs::curry_tree_hash(
+                launcher_id,
+                lineage_proof.parent_inner_puzzle_hash.into(),
+            )
+            .into(),
+            lineage_proof.parent_amount,
+        );
+
+        Coin::new(
+            parent.coin_id(),
+            SingletonArgs::curry_tree_hash(launcher_id, inner_puzzle_hash.into()).into(),
+            amount,
+        )
+    }
+
+    proptest! {
+        #[test]
+        fn test_conditions_round_trip(conditions in arb_conditions()) {
+            prop_assert_eq!(round_trip(&conditions), conditions);
+        }
+
+        #[test]
+        fn test_nft_metadata_round_trip(metadata in arb_nft_metadata()) {
+            prop_assert_eq!(round_trip(&metadata), metadata);
+        }
+
+        #[test]
+        fn test_data_store_metadata_round_trip(metadata in arb_data_store_metadata()) {
+            prop_assert_eq!(round_trip(&metadata), metadata);
+        }
+
+        #[test]
+        fn test_cat_layer_round_trip(asset_id in arb_bytes32(), p2 in arb_program()) {
+            let ctx = &mut SpendContext::new();
+            let p2 = ctx.alloc(&p2)?;
+
+            let ptr = CatLayer::new(asset_id, p2).construct_puzzle(ctx)?;
+            let puzzle = Puzzle::parse(&ctx.allocator, ptr);
+            let layer = CatLayer::<Puzzle>::parse_puzzle(&ctx.allocator, puzzle)?.unwrap();
+
+            prop_assert_eq!(layer.asset_id, asset_id);
+            prop_assert_eq!(layer.inner_puzzle.ptr(), p2);
+        }
+
+        #[test]
+        fn test_did_info_round_trip(
+            info in arb_did_info(vec((any::<String>(), any::<String>()), 0..3)),
+            p2 in arb_program(),
+        ) {
+            let ctx = &mut SpendContext::new();
+            let p2 = ctx.alloc(&p2)?;
+            let info = DidInfo {
+                p2_puzzle_hash: ctx.tree_hash(p2).into(),
+                ..info
+            };
+
+            let ptr = info.clone().into_layers(p2).construct_puzzle(ctx)?;
+            let puzzle = Puzzle::parse(&ctx.allocator, ptr);
+            let (parsed, p2_puzzle) = DidInfo::parse(&ctx.allocator, puzzle)?.unwrap();
+
+            prop_assert_eq!(parsed, info);
+            prop_assert_eq!(p2_puzzle.ptr(), p2);
+        }
+
+        #[test]
+        fn test_nft_info_round_trip(
+            info in arb_nft_info(arb_nft_metadata()),
+            p2 in arb_program(),
+        ) {
+            let ctx = &mut SpendContext::new();
+            let p2 = ctx.alloc(&p2)?;
+            let info = NftInfo {
+                p2_puzzle_hash: ctx.tree_hash(p2).into(),
+                ..info
+            };
+
+            let ptr = info.clone().into_layers(p2).construct_puzzle(ctx)?;
+            let puzzle = Puzzle::parse(&ctx.allocator, ptr);
+            let (parsed, p2_puzzle) = NftInfo::parse(&ctx.allocator, puzzle)?.unwrap();
+
+            prop_assert_eq!(parsed, info);
+            prop_assert_eq!(p2_puzzle.ptr(), p2);
+        }
+    }
+
+    // These run spends through the simulator, so fewer cases are used.
+    proptest! {
+        #![proptest_config(Config::with_cases(16))]
+
+        #[test]
+        fn test_cat_ring_spend(
+            cats in arb_cat_ring(tree_hash_atom(&[1]).into()),
+            p2_puzzle_hash in arb_bytes32(),
+        ) {
+            let mut sim = Simulator::new();
+            let ctx = &mut SpendContext::new();
+            let p2 = ctx.alloc(&1)?;
+
+            // Each CAT creates a child with the amount of the next CAT in the ring.
+            let amounts: Vec<u64> = (0..cats.len())
+                .map(|index| cats[(index + 1) % cats.len()].coin.amount)
+                .collect();
+
+            let mut cat_spends = Vec::new();
+
+            for (cat, amount) in cats.iter().zip(&amounts) {
+                let solution = ctx.alloc(&[CreateCoin::new(
+                    p2_puzzle_hash,
+                    *amount,
+                    vec![p2_puzzle_hash.into()],
+                )])?;
+                cat_spends.push(CatSpend::new(*cat, Spend::new(p2, solution)));
+                sim.insert_coin(cat.coin);
+            }
+
+            Cat::spend_all(ctx, &cat_spends)?;
+            let coin_spends = ctx.take();
+            sim.spend_coins(coin_spends.clone(), &[])?;
+
+            for ((cat, amount), coin_spend) in cats.iter().zip(amounts).zip(coin_spends) {
+                let puzzle = ctx.alloc(&coin_spend.puzzle_reveal)?;
+                let puzzle = Puzzle::parse(&ctx.allocator, puzzle);
+                let solution = ctx.alloc(&coin_spend.solution)?;
+
+                let children =
+                    Cat::parse_children(&mut ctx.allocator, cat.coin, puzzle, solution)?.unwrap();
+
+                prop_assert_eq!(children, vec![cat.wrapped_child(p2_puzzle_hash, amount)]);
+            }
+        }
+
+        #[test]
+        fn test_did_parse_child(
+            info in arb_did_info(vec((any::<String>(), any::<String>()), 0..3)),
+            lineage_proof in arb_lineage_proof(),
+            amount in any::<u64>().prop_map(|amount| amount | 1),
+            p2_puzzle_hash in arb_bytes32(),
+        ) {
+            let mut sim = Simulator::new();
+            let ctx = &mut SpendContext::new();
+            let p2 = ctx.alloc(&1)?;
+
+            let info = DidInfo {
+                p2_puzzle_hash: ctx.tree_hash(p2).into(),
+                ..info
+            };
+            let coin = singleton_coin(
+                info.launcher_id,
+                info.inner_puzzle_hash().into(),
+                lineage_proof,
+                amount,
+            );
+            let did = Did::new(coin, Proof::Lineage(lineage_proof), info);
+
+            let solution = ctx.alloc(&[CreateCoin::new(
+                p2_puzzle_hash,
+                amount,
+                vec![p2_puzzle_hash.into()],
+            )])?;
+            did.spend(ctx, Spend::new(p2, solution))?;
+
+            let coin_spend = ctx.take().remove(0);
+            sim.insert_coin(coin);
+            sim.spend_coins(vec![coin_spend.clone()], &[])?;
+
+            let expected = did.wrapped_child(p2_puzzle_hash, did.info.metadata.clone());
+
+            let puzzle = ctx.alloc(&coin_spend.puzzle_reveal)?;
+            let puzzle = Puzzle::parse(&ctx.allocator, puzzle);
+            let solution = ctx.alloc(&coin_spend.solution)?;
+            let child = Did::parse_child(&mut ctx.allocator, coin, puzzle, solution, expected.coin)?;
+
+            prop_assert_eq!(child, Some(expected));
+        }
+
+        #[test]
+        fn test_nft_parse_child(
+            info in arb_nft_info(arb_nft_metadata()),
+            lineage_proof in arb_lineage_proof(),
+            p2_puzzle_hash in arb_bytes32(),
+        ) {
+            let mut sim = Simulator::new();
+            let ctx = &mut SpendContext::new();
+            let p2 = ctx.alloc(&1)?;
+
+            let info = NftInfo {
+                p2_puzzle_hash: ctx.tree_hash(p2).into(),
+                ..info
+            };
+            // The NFT state layer only allows the child to be created with an amount of 1.
+            let coin = singleton_coin(
+                info.launcher_id,
+                info.inner_puzzle_hash().into(),
+                lineage_proof,
+                1,
+            );
+            let nft = Nft::new(coin, Proof::Lineage(lineage_proof), info);
+
+            let solution = ctx.alloc(&[CreateCoin::new(
+                p2_puzzle_hash,
+                1,
+                vec![p2_puzzle_hash.into()],
+            )])?;
+            nft.spend(ctx, Spend::new(p2, solution))?;
+
+            let coin_spend = ctx.take().remove(0);
+            sim.insert_coin(coin);
+            sim.spend_coins(vec![coin_spend.clone()], &[])?;
+
+            let expected = nft.wrapped_child(
+                p2_puzzle_hash,
+                nft.info.current_owner,
+                nft.info.metadata.clone(),
+            );
+
+            let puzzle = ctx.alloc(&coin_spend.puzzle_reveal)?;
+            let puzzle = Puzzle::parse(&ctx.allocator, puzzle);
+            let solution = ctx.alloc(&coin_spend.solution)?;
+            let child = Nft::<NftMetadata>::parse_child(&mut ctx.allocator, coin, puzzle, solution)?;
+
+            prop_assert_eq!(child, Some(expected));
+        }
+    }
+}
@@ -13,13 +13,23 @@ pub fn sign_transaction(
     coin_spends: &[CoinSpend],
     secret_keys: &[SecretKey],
 ) -> Result<Signature, SimulatorError> {
-    let mut allocator = Allocator::new();
-
-    let required_signatures = RequiredSignature::from_coin_spends(
-        &mut allocator,
+    sign_transaction_with(
         coin_spends,
+        secret_keys,
         &AggSigConstants::new(TESTNET11_CONSTANTS.agg_sig_me_additional_data),
-    )?;
+    )
+}
+
+/// Signs the coin spends with the secret keys, using the given network's `AGG_SIG_*` constants.
+pub fn sign_transaction_with(
+    coin_spends: &[CoinSpend],
+    secret_keys: &[SecretKey],
+    constants: &AggSigConstants,
+) -> Result<Signature, SimulatorError> {
+    let mut allocator = Allocator::new();
+
+    let required_signatures =
+        RequiredSignature::from_coin_spends(&mut allocator, coin_spends, constants)?;
 
     let key_pairs = secret_keys
         .iter()
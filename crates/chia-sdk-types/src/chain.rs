@@ -0,0 +1,48 @@
+use std::future::Future;
+
+use chia_protocol::{Bytes32, CoinState, Program, SpendBundle, TransactionAck};
+
+/// Read access to the state of the blockchain, regardless of whether it's backed by a peer,
+/// the full node RPC, or a simulator.
+pub trait ChainReader {
+    type Error;
+
+    /// The height of the current peak.
+    fn peak_height(&self) -> impl Future<Output = Result<u32, Self::Error>> + Send;
+
+    /// Looks up the coin states of the given coin ids. Unknown coins are omitted.
+    fn coin_states(
+        &self,
+        coin_ids: Vec<Bytes32>,
+    ) -> impl Future<Output = Result<Vec<CoinState>, Self::Error>> + Send;
+
+    /// Looks up the coin states of every coin with one of the given puzzle hashes,
+    /// and optionally every coin hinted with one of them.
+    fn coin_states_by_puzzle_hash(
+        &self,
+        puzzle_hashes: Vec<Bytes32>,
+        include_hinted: bool,
+    ) -> impl Future<Output = Result<Vec<CoinState>, Self::Error>> + Send;
+
+    /// Looks up the coin states of the children of a coin.
+    fn children(
+        &self,
+        coin_id: Bytes32,
+    ) -> impl Future<Output = Result<Vec<CoinState>, Self::Error>> + Send;
+
+    /// Looks up the puzzle reveal and solution of a coin which was spent at the given height.
+    fn puzzle_and_solution(
+        &self,
+        coin_id: Bytes32,
+        spent_height: u32,
+    ) -> impl Future<Output = Result<Option<(Program, Program)>, Self::Error>> + Send;
+}
+
+/// Write access to the blockchain, for submitting transactions.
+pub trait ChainWriter: ChainReader {
+    /// Submits a spend bundle, and returns whether it was accepted.
+    fn submit_transaction(
+        &mut self,
+        spend_bundle: SpendBundle,
+    ) -> impl Future<Output = Result<TransactionAck, Self::Error>> + Send;
+}
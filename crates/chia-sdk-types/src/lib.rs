@@ -1,8 +1,10 @@
+mod chain;
 mod condition;
 mod conditions;
 mod constants;
 mod run_puzzle;
 
+pub use chain::*;
 pub use condition::*;
 pub use conditions::*;
 pub use constants::*;
@@ -1,6 +1,6 @@
 use chia_bls::PublicKey;
 use chia_protocol::{Coin, CoinSpend};
-use chia_sdk_driver::{DriverError, Spend, SpendContext};
+use chia_sdk_driver::{DriverError, Puzzle, PuzzleRegistry, Spend, SpendContext};
 use chia_sdk_test::{test_secret_key, Simulator};
 use chia_sdk_types::Conditions;
 use clvm_traits::{FromClvm, ToClvm};
@@ -139,6 +139,24 @@ fn main() -> anyhow::Result<()> {
     // Sign and submit the transaction to the simulator.
     // This will produce an error if the transaction is not successful.
     let coin_spends = ctx.take();
+
+    // Wallets can identify the custom puzzle by registering it alongside the built-in layers.
+    let mut registry = PuzzleRegistry::with_builtin_layers();
+
+    registry.register_fn(CUSTOM_P2_PUZZLE_HASH, "Custom", |allocator, puzzle| {
+        let Some(curried) = puzzle.as_curried() else {
+            return Ok(None);
+        };
+        CustomArgs::from_clvm(allocator, curried.args)?;
+        Ok(Some(Vec::new()))
+    });
+
+    let puzzle_reveal = ctx.alloc(&coin_spends[0].puzzle_reveal)?;
+    let puzzle = Puzzle::parse(&ctx.allocator, puzzle_reveal);
+    let recognized = registry.recognize(&ctx.allocator, puzzle);
+
+    println!("Recognized layers {:?}", recognized.layer_names());
+
     sim.spend_coins(coin_spends, &[sk])?;
 
     println!("Transaction was successful.");